@@ -0,0 +1,64 @@
+use battlesnake_game_types::compact_representation::StandardCellBoard4Snakes11x11;
+use battlesnake_game_types::types::{
+    build_snake_id_map, Move, SimulableGame, SimulatorInstruments, SnakeIDGettableGame,
+};
+use battlesnake_game_types::wire_representation::Game as DEGame;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[derive(Debug)]
+struct Instruments;
+impl SimulatorInstruments for Instruments {
+    fn observe_simulation(&self, _: std::time::Duration) {}
+}
+
+fn late_stage() -> StandardCellBoard4Snakes11x11 {
+    let game_fixture = include_str!("../fixtures/late_stage.json");
+    let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+    let g = g.expect("the json literal is valid");
+    let snake_id_mapping = build_snake_id_map(&g);
+    g.as_cell_board(&snake_id_mapping).unwrap()
+}
+
+// Run with and without `--features bitboard` to compare the bitset against decoding cells
+fn bench_is_move_immediately_fatal(c: &mut Criterion) {
+    let compact = late_stage();
+    let snake_ids = compact.get_snake_ids();
+
+    c.bench_function("is move immediately fatal", |b| {
+        b.iter(|| {
+            snake_ids
+                .iter()
+                .flat_map(|sid| Move::all_iter().map(move |mv| (sid, mv)))
+                .filter(|(sid, mv)| black_box(&compact).is_move_immediately_fatal(sid, *mv))
+                .count()
+        })
+    });
+}
+
+// The mask is copied with every board, so this is the cost the feature adds to a search node
+fn bench_clone_and_simulate(c: &mut Criterion) {
+    let compact = late_stage();
+    let moves = compact
+        .get_snake_ids()
+        .into_iter()
+        .map(|sid| (sid, Move::all().to_vec()))
+        .collect::<Vec<_>>();
+
+    c.bench_function("clone board", |b| {
+        b.iter(|| black_box(*black_box(&compact)))
+    });
+    c.bench_function("simulate every joint move", |b| {
+        b.iter(|| {
+            black_box(&compact)
+                .simulate_with_moves(&Instruments, moves.iter().cloned())
+                .count()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_is_move_immediately_fatal,
+    bench_clone_and_simulate
+);
+criterion_main!(benches);
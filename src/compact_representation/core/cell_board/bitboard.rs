@@ -0,0 +1,149 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign};
+
+use crate::compact_representation::{core::Cell, CellNum};
+
+/// The array of u64 words a [BitBoard] is stored in, picked by the board's `CellNum`
+pub trait BitBoardWords:
+    Debug + Copy + Clone + PartialEq + Eq + Hash + AsRef<[u64]> + AsMut<[u64]> + Send + Sync + 'static
+{
+    /// every bit cleared
+    const EMPTY: Self;
+}
+
+impl<const N: usize> BitBoardWords for [u64; N] {
+    const EMPTY: Self = [0; N];
+}
+
+/// A set of cells with one bit per cell, indexed by `CellIndex::as_usize`. Set operations are
+/// done a whole word at a time, so intersecting masks of large boards is cheap.
+///
+/// The number of words comes from the board's cell index type, as stable Rust can't size an
+/// array from `BOARD_SIZE`. `u8` boards hold at most 256 cells and so take 4 words, while `u16`
+/// boards take the 40 words of a 50x50 board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitBoard<T: CellNum> {
+    words: T::BitBoardWords,
+}
+
+impl<T: CellNum> Default for BitBoard<T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T: CellNum> BitBoard<T> {
+    /// how many cells a bitboard can hold
+    pub const CAPACITY: usize = std::mem::size_of::<T::BitBoardWords>() * 8;
+
+    /// a bitboard without any cells
+    pub fn empty() -> Self {
+        BitBoard {
+            words: T::BitBoardWords::EMPTY,
+        }
+    }
+
+    /// the cells holding a snake's head or body
+    pub(crate) fn from_cells(cells: &[Cell<T>]) -> Self {
+        assert!(cells.len() <= Self::CAPACITY);
+        let mut board = Self::empty();
+        for (idx, cell) in cells.iter().enumerate() {
+            board.set(idx, cell.is_body() || cell.is_head());
+        }
+        board
+    }
+
+    /// whether the cell is in the set
+    #[inline]
+    pub fn contains(&self, idx: usize) -> bool {
+        self.words.as_ref()[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// adds or removes the cell
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: bool) {
+        let bit = 1 << (idx % 64);
+        let word = &mut self.words.as_mut()[idx / 64];
+        if value {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
+    }
+
+    /// adds the cell
+    #[inline]
+    pub fn insert(&mut self, idx: usize) {
+        self.set(idx, true)
+    }
+
+    /// removes the cell
+    #[inline]
+    pub fn remove(&mut self, idx: usize) {
+        self.set(idx, false)
+    }
+
+    /// how many cells are in the set
+    pub fn count(&self) -> u32 {
+        self.words.as_ref().iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// whether no cells are in the set
+    pub fn is_empty(&self) -> bool {
+        self.words.as_ref().iter().all(|w| *w == 0)
+    }
+
+    /// the cells in this set that aren't in `other`
+    pub fn difference(&self, other: &BitBoard<T>) -> BitBoard<T> {
+        let mut words = self.words;
+        for (word, other) in words.as_mut().iter_mut().zip(other.words.as_ref()) {
+            *word &= !other;
+        }
+        BitBoard { words }
+    }
+
+    /// the cells in the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words
+            .as_ref()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, word)| {
+                let mut word = *word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(i * 64 + bit)
+                })
+            })
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $fn:ident, $assign_op:ident, $assign_fn:ident, $sym:tt) => {
+        impl<T: CellNum> $op for BitBoard<T> {
+            type Output = BitBoard<T>;
+
+            fn $fn(mut self, rhs: BitBoard<T>) -> BitBoard<T> {
+                self $sym rhs;
+                self
+            }
+        }
+
+        impl<T: CellNum> $assign_op for BitBoard<T> {
+            fn $assign_fn(&mut self, rhs: BitBoard<T>) {
+                for (word, other) in self.words.as_mut().iter_mut().zip(rhs.words.as_ref()) {
+                    *word $sym other;
+                }
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &=);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, |=);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^=);
@@ -0,0 +1,284 @@
+use std::fmt::Write;
+
+use crate::compact_representation::core::{Cell, CellIndex, CellNum as CN, ConversionError};
+use crate::compact_representation::dimensions::Dimensions;
+use crate::types::SnakeId;
+
+use super::{CellBoard, GameMode, Occupancy, RulesetSettings};
+
+/// width, height, hazard damage, you, the number of snakes, the game mode, whether there are
+/// ruleset settings, the settings themselves and the turn (u16)
+const HEADER_LEN: usize = 7 + RulesetSettings::BYTES_LEN + 2;
+/// health (u8), length (u16) and head (u16) of each snake
+const SNAKE_LEN: usize = 1 + 2 + 2;
+/// every cell is packed as a u32
+const CELL_LEN: usize = 4;
+/// a delta run is its offset (u32) and length (u16) followed by the new bytes
+const RUN_HEADER_LEN: usize = 4 + 2;
+/// a frame of `serialize_game` starts with its encoded length and number of runs (u32s)
+const FRAME_HEADER_LEN: usize = 4 + 4;
+
+impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// Encodes this board as bytes. The layout is a header of width, height, hazard damage, you,
+    /// the number of snakes, the game mode, the ruleset settings and the turn, then the health,
+    /// length and head of every snake, then every cell on the playable board packed as a u32.
+    /// Multi byte values are little endian. Cells outside the playable board are always empty so
+    /// they are left out
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let area = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        let mut bytes = Vec::with_capacity(HEADER_LEN + SNAKE_LEN * MAX_SNAKES + CELL_LEN * area);
+
+        bytes.push(self.get_actual_width());
+        bytes.push(self.get_actual_height());
+        bytes.push(self.hazard_damage);
+        bytes.push(self.you.0);
+        bytes.push(MAX_SNAKES as u8);
+        bytes.push(self.game_mode.map_or(0, game_mode_to_u8));
+        bytes.push(self.ruleset_settings.is_some() as u8);
+        bytes.extend_from_slice(&self.ruleset_settings.unwrap_or_default().to_bytes());
+        bytes.extend_from_slice(&self.turn.to_le_bytes());
+        for id in 0..MAX_SNAKES {
+            bytes.push(self.healths[id]);
+            bytes.extend_from_slice(&self.lengths[id].to_le_bytes());
+            bytes.extend_from_slice(&(self.heads[id].as_usize() as u16).to_le_bytes());
+        }
+        for cell in self.cells[..area].iter() {
+            bytes.extend_from_slice(&cell.pack_as_u32().to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Decodes a board written by `to_bytes`. Boards written with fewer snakes can be read in to
+    /// a board with room for more
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConversionError> {
+        let () = Self::STORAGE_MATCHES_DIMENSIONS;
+        let header = bytes
+            .get(..HEADER_LEN)
+            .ok_or(ConversionError::MalformedBytes)?;
+        let (width, height, hazard_damage, you, snakes) = (
+            header[0],
+            header[1],
+            header[2],
+            SnakeId(header[3]),
+            header[4] as usize,
+        );
+        let game_mode = match header[5] {
+            0 => None,
+            mode => Some(game_mode_from_u8(mode).ok_or(ConversionError::MalformedBytes)?),
+        };
+        let ruleset_settings = match header[6] {
+            0 => None,
+            1 => Some(
+                RulesetSettings::from_bytes([header[7], header[8], header[9], header[10]])
+                    .ok_or(ConversionError::MalformedBytes)?,
+            ),
+            _ => return Err(ConversionError::MalformedBytes),
+        };
+        let turn = u16::from_le_bytes([header[11], header[12]]);
+
+        let area = width as usize * height as usize;
+        Self::check_dimensions(width as u32, height as u32)?;
+        let dimensions = D::from_dimensions(width, height);
+        if snakes > MAX_SNAKES {
+            return Err(ConversionError::TooManySnakes { count: snakes });
+        }
+        if bytes.len() != HEADER_LEN + SNAKE_LEN * snakes + CELL_LEN * area
+            || you.as_usize() >= MAX_SNAKES
+        {
+            return Err(ConversionError::MalformedBytes);
+        }
+
+        let read_u16 = |at: usize| u16::from_le_bytes([bytes[at], bytes[at + 1]]);
+        let mut healths = [0; MAX_SNAKES];
+        let mut lengths = [0; MAX_SNAKES];
+        let mut heads = [CellIndex::<T>::from_usize(0); MAX_SNAKES];
+        for id in 0..snakes {
+            let at = HEADER_LEN + SNAKE_LEN * id;
+            let head = read_u16(at + 3) as usize;
+            if head >= area {
+                return Err(ConversionError::MalformedBytes);
+            }
+            healths[id] = bytes[at];
+            lengths[id] = read_u16(at + 1);
+            heads[id] = CellIndex::<T>::from_usize(head);
+        }
+
+        let mut cells = [Cell::<T>::empty(); BOARD_SIZE];
+        let cell_bytes = &bytes[HEADER_LEN + SNAKE_LEN * snakes..];
+        for (cell, packed) in cells.iter_mut().zip(cell_bytes.chunks_exact(CELL_LEN)) {
+            let packed = u32::from_le_bytes([packed[0], packed[1], packed[2], packed[3]]);
+            if (packed >> 16) as usize >= area || ((packed >> 8) & 0xff) as usize >= MAX_SNAKES {
+                return Err(ConversionError::MalformedBytes);
+            }
+            *cell = Cell::from_u32(packed);
+        }
+
+        Ok(CellBoard {
+            hazard_damage,
+            cells,
+            healths,
+            heads,
+            lengths,
+            dimensions,
+            you,
+            occupancy: Occupancy::from_cells(&cells),
+            game_mode,
+            ruleset_settings,
+            turn,
+        })
+    }
+
+    /// Encodes a sequence of boards, like the frames of a game, far more compactly than calling
+    /// `to_bytes` on each of them. Every frame is stored as the runs of bytes of its `to_bytes`
+    /// encoding that differ from the previous frame's, and the first frame is diffed against
+    /// nothing. Most cells don't change from turn to turn so the runs are short.
+    ///
+    /// The layout is the frame count as a u32, then for every frame its encoded length and its
+    /// number of runs as u32s, followed by the runs. A run is its offset as a u32 and length as a
+    /// u16 followed by that many bytes. Multi byte values are little endian
+    pub fn serialize_game<'a>(boards: impl IntoIterator<Item = &'a Self>) -> Vec<u8>
+    where
+        Self: 'a,
+    {
+        let mut out = vec![0; 4];
+        let mut frames = 0u32;
+        let mut previous = Vec::new();
+        for board in boards {
+            let current = board.to_bytes();
+            let runs = delta_runs(&previous, &current);
+            out.extend_from_slice(&(current.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+            for (start, end) in runs {
+                out.extend_from_slice(&(start as u32).to_le_bytes());
+                out.extend_from_slice(&((end - start) as u16).to_le_bytes());
+                out.extend_from_slice(&current[start..end]);
+            }
+            previous = current;
+            frames += 1;
+        }
+        out[..4].copy_from_slice(&frames.to_le_bytes());
+
+        out
+    }
+
+    /// Decodes every frame written by `serialize_game`. The lengths and counts in `bytes` are
+    /// checked against what this board type and the input can hold before anything is allocated,
+    /// so untrusted input can't cause huge allocations
+    pub fn deserialize_game(bytes: &[u8]) -> Result<Vec<Self>, ConversionError> {
+        let max_len = HEADER_LEN + SNAKE_LEN * MAX_SNAKES + CELL_LEN * BOARD_SIZE;
+        let mut at = 0;
+        let mut take = |len: usize| {
+            let taken = bytes
+                .get(at..at + len)
+                .ok_or(ConversionError::MalformedBytes)?;
+            at += len;
+            Ok(taken)
+        };
+        let read_u32 = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize;
+
+        let frames = read_u32(take(4)?);
+        if frames > bytes.len() / FRAME_HEADER_LEN {
+            return Err(ConversionError::MalformedBytes);
+        }
+        let mut boards = Vec::new();
+        let mut current = Vec::new();
+        for _ in 0..frames {
+            let len = read_u32(take(4)?);
+            let runs = read_u32(take(4)?);
+            if len > max_len || runs > bytes.len() / RUN_HEADER_LEN {
+                return Err(ConversionError::MalformedBytes);
+            }
+            current.resize(len, 0);
+            for _ in 0..runs {
+                let header = take(RUN_HEADER_LEN)?;
+                let start = read_u32(header);
+                let run = u16::from_le_bytes([header[4], header[5]]) as usize;
+                current
+                    .get_mut(start..start + run)
+                    .ok_or(ConversionError::MalformedBytes)?
+                    .copy_from_slice(take(run)?);
+            }
+            boards.push(Self::from_bytes(&current)?);
+        }
+        if at != bytes.len() {
+            return Err(ConversionError::MalformedBytes);
+        }
+
+        Ok(boards)
+    }
+
+    /// A deterministic string key for this board, the lowercase hex encoding of `to_bytes`.
+    /// Equal boards always have equal keys, so it can be used to correlate logs with cached
+    /// evaluations
+    pub fn cache_key(&self) -> String {
+        self.to_bytes().iter().fold(String::new(), |mut key, byte| {
+            write!(key, "{:02x}", byte).unwrap();
+            key
+        })
+    }
+
+    /// Decodes a board from a key produced by `cache_key`
+    pub fn from_cache_key(key: &str) -> Result<Self, ConversionError> {
+        if !key.is_ascii() || !key.len().is_multiple_of(2) {
+            return Err(ConversionError::MalformedBytes);
+        }
+        let bytes = (0..key.len())
+            .step_by(2)
+            .map(|at| u8::from_str_radix(&key[at..at + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| ConversionError::MalformedBytes)?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// The `[start, end)` ranges of `current` that differ from `previous`, where bytes past the end
+/// of `previous` always differ. Changes separated by fewer bytes than a run header are merged in
+/// to one run, since a new run would cost more than the unchanged bytes in between
+fn delta_runs(previous: &[u8], current: &[u8]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (idx, byte) in current.iter().enumerate() {
+        if previous.get(idx) == Some(byte) {
+            continue;
+        }
+        match runs.last_mut() {
+            Some((start, end))
+                if idx - *end < RUN_HEADER_LEN && idx + 1 - *start <= u16::MAX as usize =>
+            {
+                *end = idx + 1
+            }
+            _ => runs.push((idx, idx + 1)),
+        }
+    }
+
+    runs
+}
+
+/// the non zero byte `to_bytes` stores for a game mode, 0 is left for boards without one
+pub(super) fn game_mode_to_u8(game_mode: GameMode) -> u8 {
+    match game_mode {
+        GameMode::Standard => 1,
+        GameMode::Wrapped => 2,
+        GameMode::Royale => 3,
+        GameMode::Constrictor => 4,
+        GameMode::Solo => 5,
+        GameMode::Other => 6,
+    }
+}
+
+/// the inverse of `game_mode_to_u8`
+pub(super) fn game_mode_from_u8(byte: u8) -> Option<GameMode> {
+    match byte {
+        1 => Some(GameMode::Standard),
+        2 => Some(GameMode::Wrapped),
+        3 => Some(GameMode::Royale),
+        4 => Some(GameMode::Constrictor),
+        5 => Some(GameMode::Solo),
+        6 => Some(GameMode::Other),
+        _ => None,
+    }
+}
@@ -0,0 +1,123 @@
+use itertools::Itertools;
+
+use crate::{
+    compact_representation::{
+        core::{dimensions::Dimensions, ConformanceError},
+        CellNum,
+    },
+    types::{build_snake_id_map, Move, SnakeBodyGettableGame, SnakeId},
+    wire_representation::{Game, Position},
+};
+
+use super::{CellBoard, CellIndex, EvaluateMode, GameMode};
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// Checks the simulator against a log of an official game. `frames` are the boards of
+    /// consecutive turns and `moves[i]` holds the move every snake made between `frames[i]` and
+    /// `frames[i + 1]`, indexed by the `SnakeId` that `build_snake_id_map` gives it in the first
+    /// frame. Moves of dead snakes are ignored.
+    ///
+    /// Every frame is converted and simulated with its moves, in the mode of its ruleset, and
+    /// compared to the next frame. Each turn starts from the logged frame, so a divergence is
+    /// reported once, on the first turn it happens. The simulator doesn't spawn food or
+    /// hazards, so food that appears and hazards are not compared
+    pub fn conformance_check(
+        frames: &[Game],
+        moves: &[[Move; MAX_SNAKES]],
+    ) -> Result<(), ConformanceError> {
+        let first = match frames.first() {
+            Some(first) => first,
+            None => return Ok(()),
+        };
+        let snake_ids = build_snake_id_map(first);
+        let convert = |frame: &Game| {
+            Self::convert_from_game(frame.clone(), &snake_ids).map_err(|error| {
+                ConformanceError::Conversion {
+                    turn: frame.turn,
+                    error,
+                }
+            })
+        };
+
+        let mut board = convert(first)?;
+        for (turn, (frame, next_frame)) in frames.iter().tuple_windows().enumerate() {
+            let turn_moves = moves
+                .get(turn)
+                .ok_or(ConformanceError::MissingMoves { turn: frame.turn })?;
+            let mode = match board.game_mode {
+                Some(GameMode::Wrapped) => EvaluateMode::Wrapped,
+                Some(GameMode::Solo) => EvaluateMode::Solo,
+                _ => EvaluateMode::Standard,
+            };
+
+            let simulated = board.step_once(*turn_moves, mode);
+            let expected = convert(next_frame)?;
+            let differences = simulated.conformance_differences(&expected);
+            if !differences.is_empty() {
+                return Err(ConformanceError::Diverged {
+                    turn: next_frame.turn,
+                    differences,
+                });
+            }
+            board = expected;
+        }
+
+        Ok(())
+    }
+
+    /// every way the snakes and food of this board differ from `expected`, except for food
+    /// that only `expected` has
+    fn conformance_differences(&self, expected: &Self) -> Vec<String> {
+        let width = self.get_actual_width();
+        let positions = |body: Vec<_>| {
+            body.into_iter()
+                .map(|idx: CellIndex<T>| {
+                    let Position { x, y } = idx.into_position(width);
+                    (x, y)
+                })
+                .collect_vec()
+        };
+
+        let mut differences = Vec::new();
+        for id in 0..MAX_SNAKES {
+            let sid = SnakeId(id as u8);
+            match (self.healths[id] > 0, expected.healths[id] > 0) {
+                (false, false) => {}
+                (true, false) => {
+                    differences.push(format!("snake {} survived, expected it to die", id))
+                }
+                (false, true) => {
+                    differences.push(format!("snake {} died, expected it to survive", id))
+                }
+                (true, true) => {
+                    if self.healths[id] != expected.healths[id] {
+                        differences.push(format!(
+                            "snake {} has {} health, expected {}",
+                            id, self.healths[id], expected.healths[id]
+                        ));
+                    }
+                    let body = positions(self.get_snake_body_vec(&sid));
+                    let expected_body = positions(expected.get_snake_body_vec(&sid));
+                    if body != expected_body {
+                        differences.push(format!(
+                            "snake {} has body {:?}, expected {:?}",
+                            id, body, expected_body
+                        ));
+                    }
+                }
+            }
+        }
+
+        let area = width as usize * self.get_actual_height() as usize;
+        for idx in 0..area {
+            if self.cells[idx].is_food() && !expected.cells[idx].is_food() {
+                let Position { x, y } = CellIndex::<T>::from_usize(idx).into_position(width);
+                differences.push(format!("food at ({}, {}) is still on the board", x, y));
+            }
+        }
+
+        differences
+    }
+}
@@ -6,6 +6,7 @@ use tracing::instrument;
 use crate::{
     compact_representation::{core::dimensions::Dimensions, CellNum},
     types::{self, HeadGettableGame, Move, SnakeId, N_MOVES},
+    wire_representation::Position,
 };
 
 use super::{CellBoard, CellIndex};
@@ -13,8 +14,192 @@ use super::{CellBoard, CellIndex};
 /// Which mode to evaluate in
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EvaluateMode {
+    /// snakes moving off an edge come back on the opposite one
     Wrapped,
+    /// the edges of the board are walls
     Standard,
+    /// like `Standard`, for boards with a single snake. Collisions between snakes can't happen
+    /// so the simulator skips resolving them
+    Solo,
+}
+
+/// The ruleset a board was converted from, so that code holding a board can tell whether its
+/// simulation rules match the game it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    /// the `standard` ruleset
+    Standard,
+    /// the `wrapped` ruleset, where snakes move off one edge and on to the opposite one
+    Wrapped,
+    /// the `royale` ruleset, where hazards shrink the board
+    Royale,
+    /// the `constrictor` ruleset, where snakes grow every turn
+    Constrictor,
+    /// the `solo` ruleset, with a single snake
+    Solo,
+    /// any other ruleset name
+    Other,
+}
+
+impl GameMode {
+    /// the mode for a ruleset name as sent by the battlesnake server
+    pub fn from_ruleset_name(name: &str) -> Self {
+        match name {
+            "standard" => GameMode::Standard,
+            "wrapped" => GameMode::Wrapped,
+            "royale" => GameMode::Royale,
+            "constrictor" => GameMode::Constrictor,
+            "solo" => GameMode::Solo,
+            _ => GameMode::Other,
+        }
+    }
+
+    /// the ruleset name the battlesnake server uses for this mode, `None` for `Other` since the
+    /// original name isn't kept
+    pub fn ruleset_name(&self) -> Option<&'static str> {
+        match self {
+            GameMode::Standard => Some("standard"),
+            GameMode::Wrapped => Some("wrapped"),
+            GameMode::Royale => Some("royale"),
+            GameMode::Constrictor => Some("constrictor"),
+            GameMode::Solo => Some("solo"),
+            GameMode::Other => None,
+        }
+    }
+
+    /// whether boards from this mode simulate with wrapped edges
+    pub fn is_wrapped(&self) -> bool {
+        *self == GameMode::Wrapped
+    }
+}
+
+/// Decides which snake, if any, survives a head to head collision
+#[derive(Debug, Clone, Copy, Default)]
+pub enum HeadToHeadPolicy {
+    /// The official rule: the strictly longest snake survives, if the longest snakes are tied
+    /// they all die
+    #[default]
+    Standard,
+    /// A custom rule. It is given the id and length of every snake that moved on to the cell and
+    /// returns the survivor, or `None` if they all die. Returning a snake that wasn't in the
+    /// collision also kills them all
+    Custom(fn(&[(SnakeId, u16)]) -> Option<SnakeId>),
+}
+
+impl HeadToHeadPolicy {
+    /// the snake that survives a head to head collision between `snakes`, given as ids and
+    /// lengths
+    pub fn survivor(&self, snakes: &[(SnakeId, u16)]) -> Option<SnakeId> {
+        match self {
+            HeadToHeadPolicy::Standard => {
+                let max_length = snakes.iter().map(|(_, length)| *length).max()?;
+                let mut longest = snakes.iter().filter(|(_, length)| *length == max_length);
+                match (longest.next(), longest.next()) {
+                    (Some((id, _)), None) => Some(*id),
+                    _ => None,
+                }
+            }
+            HeadToHeadPolicy::Custom(policy) => policy(snakes),
+        }
+    }
+}
+
+/// How eating food changes a snake's health
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FoodHealthMode {
+    /// The official rule: eating restores the snake to full health
+    #[default]
+    ResetToFull,
+    /// Eating adds `amount` to the snake's health, up to `cap`
+    Add {
+        /// the health each food gives
+        amount: u8,
+        /// the most health a snake can have after eating
+        cap: u8,
+    },
+}
+
+impl FoodHealthMode {
+    /// the health of a snake that had `health` left after moving and then ate
+    pub fn health_after_eating(&self, health: u8) -> u8 {
+        match self {
+            FoodHealthMode::ResetToFull => 100,
+            FoodHealthMode::Add { amount, cap } => health.saturating_add(*amount).min(*cap),
+        }
+    }
+}
+
+/// Rule settings for the simulator, for experimenting with variants of the official rules. The
+/// default is the official rules
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimSettings {
+    /// how head to head collisions are resolved
+    pub head_to_head: HeadToHeadPolicy,
+    /// how eating food changes health
+    pub food_health: FoodHealthMode,
+}
+
+/// The board at each phase of a single simulated turn, in the order the simulator applies them.
+/// The phases follow the official rules, except that food spawning is skipped
+#[derive(Debug, Clone)]
+pub struct SimTrace<B> {
+    /// the board before the turn
+    pub before: B,
+    /// after every tail has moved, health has been reduced (including hazard damage) and food
+    /// has been eaten, growing the tail. Snakes that moved off the board or starved are already
+    /// removed, the other heads haven't moved yet
+    pub after_tails_and_food: B,
+    /// after head to head collisions have been resolved, which clears the collision cell when
+    /// nobody survives
+    pub after_head_to_head: B,
+    /// the final board, with heads moved and every snake that collided removed. This is the
+    /// same board `simulate_with_moves` produces
+    pub result: B,
+}
+
+/// Why a snake was eliminated during a simulated turn, following the official elimination causes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EliminationCause {
+    /// the snake moved off the edge of the board
+    WallCollision,
+    /// the snake's health reached 0, from moving or from hazard damage
+    OutOfHealth,
+    /// the snake moved in to its own body, including its neck
+    SelfCollision,
+    /// the snake moved in to the body of this other snake
+    BodyCollision(SnakeId),
+    /// the snake lost or tied a head to head collision with this other snake
+    HeadToHead(SnakeId),
+}
+
+/// Something that happened during a turn simulated by `simulate_logged`. The simulator doesn't
+/// spawn food, so there are no events for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimEvent {
+    /// the snake made this move, putting its head at `to`. Off the board moves report the
+    /// position past the edge, wrapped moves the position they came back on at
+    Moved {
+        /// the snake that moved
+        id: SnakeId,
+        /// the move it made
+        mv: Move,
+        /// where its head went
+        to: Position,
+    },
+    /// the snake ate the food at this position
+    Ate {
+        /// the snake that ate
+        id: SnakeId,
+        /// where the food was
+        at: Position,
+    },
+    /// the snake was eliminated
+    Died {
+        /// the snake that died
+        id: SnakeId,
+        /// why it died
+        cause: EliminationCause,
+    },
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -47,9 +232,13 @@ pub struct AliveMoveResult<T: CellNum> {
 pub enum SinglePlayerMoveResult<T: CellNum> {
     /// Represents the given snake is alive after phase 1 of evaluation
     Alive(AliveMoveResult<T>),
-    /// Represents the snake died during phase 1. Cause it ran into a snake (including itself)
-    /// [excluding head to heads] or went out of bounds
+    /// Represents the snake died during phase 1. Cause it went out of bounds or ran out of
+    /// health, so it is removed before any collisions are checked
     Dead,
+    /// Represents the snake moving back in to its own neck. Like the official self collision it
+    /// is eliminated, but its body stays on the board until every collision of the turn has been
+    /// resolved, so other snakes still run in to it
+    SelfCollision(AliveMoveResult<T>),
 }
 
 impl<T: CellNum> SinglePlayerMoveResult<T> {
@@ -61,21 +250,48 @@ impl<T: CellNum> SinglePlayerMoveResult<T> {
     }
 
     pub fn is_dead(&self) -> bool {
-        matches!(self, SinglePlayerMoveResult::Dead)
+        matches!(
+            self,
+            SinglePlayerMoveResult::Dead | SinglePlayerMoveResult::SelfCollision(_)
+        )
     }
 }
 
 impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
 {
-    pub fn generate_state<'a, S: 'a>(
+    pub fn generate_state<'a, S>(
+        &self,
+        moves: impl Iterator<Item = &'a (SnakeId, S)>,
+        mode: EvaluateMode,
+    ) -> [[SinglePlayerMoveResult<T>; N_MOVES]; MAX_SNAKES]
+    where
+        S: Borrow<[Move]> + 'a,
+    {
+        self.generate_state_with_food_health(moves, mode, FoodHealthMode::ResetToFull)
+    }
+
+    /// `generate_state`, where the health of snakes that eat is given by `food_health`
+    pub fn generate_state_with_food_health<'a, S>(
         &self,
         moves: impl Iterator<Item = &'a (SnakeId, S)>,
         mode: EvaluateMode,
+        food_health: FoodHealthMode,
     ) -> [[SinglePlayerMoveResult<T>; N_MOVES]; MAX_SNAKES]
     where
-        S: Borrow<[Move]>,
+        S: Borrow<[Move]> + 'a,
     {
+        debug_assert!(
+            self.game_mode
+                .is_none_or(|game_mode| game_mode.is_wrapped() == (mode == EvaluateMode::Wrapped)),
+            "simulating a board from a {:?} game in {:?} mode",
+            self.game_mode,
+            mode
+        );
+        debug_assert!(
+            mode != EvaluateMode::Solo || self.healths.iter().filter(|h| **h > 0).count() <= 1,
+            "simulating a board with more than one snake in solo mode"
+        );
         let mut new_heads = [[SinglePlayerMoveResult::Dead; 4]; MAX_SNAKES];
 
         for (id, mvs) in moves {
@@ -95,15 +311,16 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
                         )
                     });
 
-                let new_head_position =
-                    old_head.into_position(Self::width()).add_vec(m.to_vector());
+                let new_head_position = old_head
+                    .into_position(self.get_actual_width())
+                    .add_vec(m.to_vector());
                 let new_head = match mode {
                     EvaluateMode::Wrapped => self.as_wrapped_cell_index(new_head_position),
-                    EvaluateMode::Standard => {
+                    EvaluateMode::Standard | EvaluateMode::Solo => {
                         if self.off_board(new_head_position) {
                             continue;
                         } else {
-                            CellIndex::<T>::new(new_head_position, Self::width())
+                            CellIndex::<T>::new(new_head_position, self.get_actual_width())
                         }
                     }
                 };
@@ -124,10 +341,6 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
 
                     prev
                 };
-                if new_head == neck {
-                    continue;
-                }
-
                 let old_tail_cell = self.get_cell(old_tail);
                 let new_tail = if old_tail_cell.is_stacked() {
                     old_tail
@@ -137,17 +350,17 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
                         .expect("We specifically went to a tail so this shouldn't fail")
                 };
 
+                let ate_food = self.get_cell(new_head).is_food();
                 let mut new_health = self.healths[id.as_usize()];
                 new_health = new_health.saturating_sub(1);
-                if self.get_cell(new_head).is_hazard() {
-                    new_health = new_health.saturating_sub(self.hazard_damage);
+                // like the official rules, a snake that eats on a hazard takes no hazard damage
+                if !ate_food {
+                    new_health = new_health.saturating_sub(self.hazard_damage_at(new_head));
                 }
-
-                let ate_food = self.get_cell(new_head).is_food();
                 let mut new_length = self.lengths[id.as_usize()];
 
                 if ate_food {
-                    new_health = 100;
+                    new_health = food_health.health_after_eating(new_health);
                     new_length = new_length.saturating_add(1);
                 };
 
@@ -155,17 +368,21 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
                     continue;
                 };
 
-                new_heads[id.as_usize()][m.as_index()] =
-                    SinglePlayerMoveResult::Alive(AliveMoveResult {
-                        id: *id,
-                        new_head,
-                        old_head,
-                        new_tail,
-                        old_tail,
-                        new_health,
-                        ate_food,
-                        new_length,
-                    });
+                let result = AliveMoveResult {
+                    id: *id,
+                    new_head,
+                    old_head,
+                    new_tail,
+                    old_tail,
+                    new_health,
+                    ate_food,
+                    new_length,
+                };
+                new_heads[id.as_usize()][m.as_index()] = if new_head == neck {
+                    SinglePlayerMoveResult::SelfCollision(result)
+                } else {
+                    SinglePlayerMoveResult::Alive(result)
+                };
             }
         }
 
@@ -175,16 +392,185 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
     #[instrument(level = "trace", skip_all)]
     pub fn evaluate_moves_with_state<'a>(
         &self,
-        moves: impl Iterator<Item = &'a (SnakeId, crate::types::Move)>,
+        moves: impl Iterator<Item = &'a (SnakeId, crate::types::Move)> + Clone,
         new_heads: &[[SinglePlayerMoveResult<T>; N_MOVES]; MAX_SNAKES],
+        policy: HeadToHeadPolicy,
+        mode: EvaluateMode,
+    ) -> Self
+    where
+        <Self as types::SnakeIDGettableGame>::SnakeIDType: 'a,
+    {
+        self.evaluate_moves_observed(moves, new_heads, policy, mode, |_| {})
+    }
+
+    /// Simulates a single turn where every living snake makes the move at its `SnakeId` in
+    /// `moves`, the entries for dead snakes are ignored. This gives the same board as taking the
+    /// only result of `simulate_with_moves` with one move per snake, but without boxing an
+    /// iterator, building the product of moves or allocating, so it is the fastest way to
+    /// advance a board once every move is decided
+    pub fn step_once(&self, moves: [Move; MAX_SNAKES], mode: EvaluateMode) -> Self {
+        let mut single_moves = [(SnakeId(0), Move::Up); MAX_SNAKES];
+        let mut move_lists = [(SnakeId(0), [Move::Up]); MAX_SNAKES];
+        let mut living = 0;
+        for (id, mv) in moves.iter().enumerate() {
+            if self.healths[id] > 0 {
+                let id = SnakeId(id as u8);
+                single_moves[living] = (id, *mv);
+                move_lists[living] = (id, [*mv]);
+                living += 1;
+            }
+        }
+
+        let states = self.generate_state(move_lists[..living].iter(), mode);
+        self.evaluate_moves_with_state(
+            single_moves[..living].iter(),
+            &states,
+            HeadToHeadPolicy::Standard,
+            mode,
+        )
+    }
+
+    /// Simulates a single turn with one move per snake, recording the board after each phase of
+    /// the evaluation. This copies the board at every phase, so it is meant for debugging rather
+    /// than search
+    pub fn simulate_traced(&self, moves: &[(SnakeId, Move)], mode: EvaluateMode) -> SimTrace<Self> {
+        let single_moves = moves.iter().map(|(id, mv)| (*id, [*mv])).collect_vec();
+        let states = self.generate_state(single_moves.iter(), mode);
+
+        let mut phases = Vec::with_capacity(2);
+        let result = self.evaluate_moves_observed(
+            moves.iter(),
+            &states,
+            HeadToHeadPolicy::Standard,
+            mode,
+            |b| phases.push(*b),
+        );
+
+        SimTrace {
+            before: *self,
+            after_tails_and_food: phases[0],
+            after_head_to_head: phases[1],
+            result,
+        }
+    }
+
+    /// Simulates a single turn with one move per snake, like `simulate_traced`, and returns the
+    /// resulting board with a log of what happened. Every move is logged first, in the order of
+    /// `moves`, with the food each snake ate right after its move, then every elimination. Moves
+    /// for dead snakes are ignored. This allocates the log, so it is meant for analysis rather
+    /// than search
+    pub fn simulate_logged(
+        &self,
+        moves: &[(SnakeId, Move)],
+        mode: EvaluateMode,
+    ) -> (Self, Vec<SimEvent>) {
+        let single_moves = moves.iter().map(|(id, mv)| (*id, [*mv])).collect_vec();
+        let states = self.generate_state(single_moves.iter(), mode);
+
+        let mut phases = Vec::with_capacity(2);
+        let result = self.evaluate_moves_observed(
+            moves.iter(),
+            &states,
+            HeadToHeadPolicy::Standard,
+            mode,
+            |b| phases.push(*b),
+        );
+
+        let width = self.get_actual_width();
+        let living = moves
+            .iter()
+            .filter(|(id, _)| self.healths[id.as_usize()] > 0)
+            .map(|(id, mv)| (*id, *mv, states[id.as_usize()][mv.as_index()]))
+            .collect_vec();
+        let mut events = Vec::with_capacity(living.len() * 2);
+
+        for (id, mv, state) in living.iter() {
+            let mut to = self.heads[id.as_usize()]
+                .into_position(width)
+                .add_vec(mv.to_vector());
+            if mode == EvaluateMode::Wrapped {
+                to = self.as_wrapped_cell_index(to).into_position(width);
+            }
+            events.push(SimEvent::Moved {
+                id: *id,
+                mv: *mv,
+                to,
+            });
+
+            if let SinglePlayerMoveResult::Alive(alive)
+            | SinglePlayerMoveResult::SelfCollision(alive) = state
+            {
+                if alive.ate_food {
+                    events.push(SimEvent::Ate {
+                        id: *id,
+                        at: alive.new_head.into_position(width),
+                    });
+                }
+            }
+        }
+
+        for (id, mv, state) in living.iter() {
+            if result.healths[id.as_usize()] > 0 {
+                continue;
+            }
+            let cause = match state {
+                SinglePlayerMoveResult::Dead => {
+                    let to = self.heads[id.as_usize()]
+                        .into_position(width)
+                        .add_vec(mv.to_vector());
+                    if mode != EvaluateMode::Wrapped && self.off_board(to) {
+                        EliminationCause::WallCollision
+                    } else {
+                        EliminationCause::OutOfHealth
+                    }
+                }
+                SinglePlayerMoveResult::SelfCollision(_) => EliminationCause::SelfCollision,
+                SinglePlayerMoveResult::Alive(alive) => {
+                    // body collisions are checked against the board before any head moved
+                    match phases[0].get_cell(alive.new_head).get_snake_id() {
+                        Some(owner) if owner == *id => EliminationCause::SelfCollision,
+                        Some(owner) => EliminationCause::BodyCollision(owner),
+                        None => {
+                            let opponent = living
+                                .iter()
+                                .find_map(|(other, _, other_state)| match other_state {
+                                    SinglePlayerMoveResult::Alive(other_alive)
+                                        if other != id
+                                            && other_alive.new_head == alive.new_head =>
+                                    {
+                                        Some(*other)
+                                    }
+                                    _ => None,
+                                })
+                                .expect("a snake that didn't run in to a body lost a head to head");
+                            EliminationCause::HeadToHead(opponent)
+                        }
+                    }
+                }
+            };
+            events.push(SimEvent::Died { id: *id, cause });
+        }
+
+        (result, events)
+    }
+
+    /// `evaluate_moves_with_state`, calling `observe` with the intermediate board after the
+    /// tails, health and food are applied and again after head to head collisions are resolved
+    fn evaluate_moves_observed<'a>(
+        &self,
+        moves: impl Iterator<Item = &'a (SnakeId, crate::types::Move)> + Clone,
+        new_heads: &[[SinglePlayerMoveResult<T>; N_MOVES]; MAX_SNAKES],
+        policy: HeadToHeadPolicy,
+        mode: EvaluateMode,
+        mut observe: impl FnMut(&Self),
     ) -> Self
     where
         <Self as types::SnakeIDGettableGame>::SnakeIDType: 'a,
     {
-        let moves = moves.collect_vec();
         let mut new = *self;
+        new.turn = new.turn.saturating_add(1);
 
-        for (id, m) in moves.iter() {
+        for (id, m) in moves.clone() {
             let result = new_heads[id.as_usize()][m.as_index()];
 
             match result {
@@ -197,6 +583,16 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
                     ate_food,
                     new_length,
                     ..
+                })
+                | SinglePlayerMoveResult::SelfCollision(AliveMoveResult {
+                    id,
+                    old_head,
+                    new_tail,
+                    old_tail,
+                    new_health,
+                    ate_food,
+                    new_length,
+                    ..
                 }) => {
                     // Step 1a is delayed and done later. This is to not run into issues with
                     // overriding someone elses tail which would break the representation and make it
@@ -229,13 +625,15 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
             }
         }
 
+        observe(&new);
+
         // Step 3: Any new food spawning will be placed in empty squares on the board.
         // This step is ignored because we don't want to guess at food spawn locations as they are
         // random
         let mut to_kill = [false; MAX_SNAKES];
 
         // Step 4c-d: Collision besides head to head
-        for (id, m) in moves.iter() {
+        for (id, m) in moves.clone() {
             let result = new_heads[id.as_usize()][m.as_index()];
 
             if let SinglePlayerMoveResult::Alive(AliveMoveResult { id, new_head, .. }) = result {
@@ -247,25 +645,37 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
             }
         }
 
-        // Step 4e: Head to Head collisions
-        let grouped_heads = moves
-            .iter()
-            .map(|(id, m)| new_heads[id.as_usize()][m.as_index()])
-            .filter_map(|result| result.to_alive_struct())
-            .into_group_map_by(|t| t.new_head);
-        let head_to_head_collistions = grouped_heads
-            .iter()
-            .filter(|(_key, values)| values.len() >= 2);
-
-        for (head_to_head_collision_pos, snake_move_info) in head_to_head_collistions {
-            let max_length = snake_move_info
-                .iter()
-                .map(|i| (*i, new.get_length(i.id)))
-                .max_by_key(|x| x.1)
-                .unwrap()
-                .1;
-            let snake_ids = snake_move_info.iter().map(|i| i.id).collect_vec();
-            let cell = new.get_cell(*head_to_head_collision_pos);
+        // Step 4e: Head to Head collisions, a lone snake can't collide with anyone
+        let alive = moves
+            .clone()
+            .filter(|_| mode != EvaluateMode::Solo)
+            .filter_map(|(id, m)| new_heads[id.as_usize()][m.as_index()].to_alive_struct());
+        for (i, AliveMoveResult { new_head, .. }) in alive.clone().enumerate() {
+            // every collision is resolved once, by the first snake that moved on to the cell
+            if alive
+                .clone()
+                .take(i)
+                .any(|other| other.new_head == new_head)
+            {
+                continue;
+            }
+            let head_to_head_collision_pos = new_head;
+            let mut snakes = [(SnakeId(0), 0); MAX_SNAKES];
+            let mut count = 0;
+            for other in alive
+                .clone()
+                .filter(|other| other.new_head == head_to_head_collision_pos)
+                .take(MAX_SNAKES)
+            {
+                snakes[count] = (other.id, new.get_length(other.id));
+                count += 1;
+            }
+            if count < 2 {
+                continue;
+            }
+            let snakes = &snakes[..count];
+            let in_collision = |id: SnakeId| snakes.iter().any(|(sid, _)| *sid == id);
+            let cell = new.get_cell(head_to_head_collision_pos);
             // consider this board:
             //   s . . f . . s s s 3 s
             //   s s s . . . . s s . .
@@ -287,66 +697,57 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
             // snake 0 will be removed, causing the body to go in to an inconsistent state
             let head_to_head_collision_on_another_snake = cell.is_body_segment()
                 && !cell.is_head()
-                && !snake_ids.contains(&cell.get_snake_id().unwrap());
-
-            let multiple_snakes_max_length = snake_move_info
-                .iter()
-                .filter(|x| new.get_length(x.id) == max_length)
-                .count()
-                != 1;
+                && !in_collision(cell.get_snake_id().unwrap());
 
-            let winner = if multiple_snakes_max_length || head_to_head_collision_on_another_snake {
+            let winner = if head_to_head_collision_on_another_snake {
                 None
             } else {
-                Some(
-                    snake_move_info
-                        .iter()
-                        .map(|i| (*i, new.get_length(i.id)))
-                        .max_by_key(|x| x.1)
-                        .unwrap()
-                        .0,
-                )
+                policy
+                    .survivor(snakes)
+                    .filter(|winner| in_collision(*winner))
             };
 
-            for AliveMoveResult { id: dead, .. } in snake_move_info
-                .iter()
-                .filter(|x| Some(x.id) != winner.map(|x| x.id))
-            {
+            for (dead, _) in snakes.iter().filter(|(id, _)| Some(*id) != winner) {
                 to_kill[dead.as_usize()] = true;
             }
 
             if winner.is_none() && !head_to_head_collision_on_another_snake {
-                new.cell_remove(*head_to_head_collision_pos);
+                new.cell_remove(head_to_head_collision_pos);
             }
         }
 
-        for result in moves
-            .iter()
-            .map(|(id, m)| new_heads[id.as_usize()][m.as_index()])
-        {
-            if let SinglePlayerMoveResult::Alive(AliveMoveResult {
-                id,
-                old_head,
-                new_head,
-                new_tail,
-                ..
-            }) = result
-            {
-                if to_kill[id.as_usize()] {
-                    // Kill any player killed via collisions
-                    new.kill_and_remove(id);
-                } else {
-                    // Move Head
-                    new.heads[id.as_usize()] = new_head;
-                    new.set_cell_head(new_head, id, new_tail);
+        observe(&new);
 
-                    let old_head_cell = self.get_cell(old_head);
-                    if old_head_cell.is_triple_stacked_piece() {
-                        new.set_cell_double_stacked(old_head, id, new_head);
+        // Every collision has been checked, only now are the eliminated snakes removed
+        for result in moves.map(|(id, m)| new_heads[id.as_usize()][m.as_index()]) {
+            match result {
+                SinglePlayerMoveResult::Alive(AliveMoveResult {
+                    id,
+                    old_head,
+                    new_head,
+                    new_tail,
+                    ..
+                }) => {
+                    if to_kill[id.as_usize()] {
+                        // Kill any player killed via collisions
+                        new.kill_and_remove(id);
                     } else {
-                        new.set_cell_body_piece(old_head, id, new_head);
+                        // Move Head
+                        new.heads[id.as_usize()] = new_head;
+                        new.set_cell_head(new_head, id, new_tail);
+
+                        let old_head_cell = self.get_cell(old_head);
+                        if old_head_cell.is_triple_stacked_piece() {
+                            new.set_cell_double_stacked(old_head, id, new_head);
+                        } else {
+                            new.set_cell_body_piece(old_head, id, new_head);
+                        }
                     }
                 }
+                SinglePlayerMoveResult::SelfCollision(AliveMoveResult { id, .. }) => {
+                    new.kill_and_remove(id)
+                }
+                SinglePlayerMoveResult::Dead => {}
             }
         }
 
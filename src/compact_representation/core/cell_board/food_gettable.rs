@@ -1,12 +1,14 @@
+use itertools::Itertools;
+
 use crate::{
     compact_representation::{
         core::{dimensions::Dimensions, CellIndex},
         CellNum,
     },
-    types::FoodGettableGame,
+    types::{FoodGettableGame, SnakeId},
 };
 
-use super::CellBoard;
+use super::{CellBoard, EvaluateMode};
 
 impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> FoodGettableGame
     for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
@@ -29,3 +31,35 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
             .collect()
     }
 }
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// the cells from which a food can be eaten in one move, that will be free to stand on next
+    /// turn. Sorted by index without duplicates
+    pub fn food_approach_cells(&self, mode: EvaluateMode) -> Vec<CellIndex<T>> {
+        self.get_all_food_as_native_positions()
+            .into_iter()
+            .flat_map(|food| self.neighbor_cells(food, mode))
+            .map(|(_, cell)| cell)
+            .filter(|cell| self.will_be_free_next_turn(*cell))
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// whether any food is within `radius` of `sid`'s head by `manhattan_distance`, a cheap
+    /// check to run before a real search for food. Always false for dead snakes
+    pub fn food_within(&self, sid: SnakeId, radius: u32, mode: EvaluateMode) -> bool {
+        if self.healths[sid.as_usize()] == 0 {
+            return false;
+        }
+
+        let head = self.heads[sid.as_usize()];
+        let area = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        self.cells[..area].iter().enumerate().any(|(idx, cell)| {
+            cell.is_food()
+                && self.manhattan_distance(head, CellIndex::from_usize(idx), mode) <= radius
+        })
+    }
+}
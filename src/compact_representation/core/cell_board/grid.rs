@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::{SnakeBodyGettableGame, SnakeId},
+    wire_representation::Position,
+};
+
+use super::{
+    place_snake_cells, Cell, CellBoard, CellIndex, ConversionError, EvaluateMode, Occupancy,
+};
+
+/// What a single cell of a board holds, independent of the board representation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CellContents {
+    /// Nothing is on this cell
+    Empty {
+        /// how many hazards are stacked on this cell, 0 if it isn't a hazard
+        hazards: u8,
+    },
+    /// A food is on this cell
+    Food {
+        /// how many hazards are stacked on this cell, 0 if it isn't a hazard
+        hazards: u8,
+    },
+    /// A snake occupies this cell
+    Snake {
+        /// the snake occupying the cell
+        id: SnakeId,
+        /// how far along the body this cell is, the head is segment 0. For stacked cells this
+        /// is the segment closest to the head
+        segment: u16,
+        /// how many segments of the body are stacked on this cell, between 1 and 3
+        stack: u8,
+        /// how many hazards are stacked on this cell, 0 if it isn't a hazard
+        hazards: u8,
+    },
+}
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// what is on the given cell
+    pub fn what_is_at(&self, idx: CellIndex<T>) -> CellContents {
+        let cell = self.get_cell(idx);
+        let hazards = cell.hazard_stack();
+
+        if let Some(id) = cell.get_snake_id() {
+            let body = self.get_snake_body_vec(&id);
+            let segment = body.iter().position(|c| *c == idx).unwrap_or(0) as u16;
+            let stack = body.iter().filter(|c| **c == idx).count() as u8;
+
+            CellContents::Snake {
+                id,
+                segment,
+                stack,
+                hazards,
+            }
+        } else if cell.is_food() {
+            CellContents::Food { hazards }
+        } else {
+            CellContents::Empty { hazards }
+        }
+    }
+
+    /// exports the board as rows of cells indexed as `grid[y][x]`, with `grid[0]` being the
+    /// `y = 0` row
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_grid(&self) -> Vec<Vec<CellContents>> {
+        let width = self.get_actual_width();
+        let height = self.get_actual_height();
+
+        let mut grid = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let cell = self.cells[y as usize * width as usize + x as usize];
+                        let hazards = cell.hazard_stack();
+                        if cell.is_food() {
+                            CellContents::Food { hazards }
+                        } else {
+                            CellContents::Empty { hazards }
+                        }
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        for (id, health) in self.healths.iter().enumerate() {
+            if *health == 0 {
+                continue;
+            }
+            let id = SnakeId(id as u8);
+            let body = self.get_snake_body_vec(&id);
+            for (segment, idx) in body.iter().enumerate().unique_by(|(_, idx)| **idx) {
+                let pos = idx.into_position(width);
+                grid[pos.y as usize][pos.x as usize] = CellContents::Snake {
+                    id,
+                    segment: segment as u16,
+                    stack: body.iter().filter(|c| *c == idx).count() as u8,
+                    hazards: self.get_cell(*idx).hazard_stack(),
+                };
+            }
+        }
+
+        grid
+    }
+
+    /// builds a board from rows of cells indexed as `grid[y][x]`, the inverse of `to_grid`.
+    ///
+    /// Every snake in the grid gets 100 health and `SnakeId(0)` is you. A snake's cells are
+    /// ordered by `segment`, and must form a chain starting at segment 0 (the head), where each
+    /// cell's segment is the previous cell's segment plus its stack, and consecutive cells are
+    /// adjacent (wrapping around the edges in wrapped mode). Only the tail may be stacked, and a
+    /// stack of 3 is only allowed for a snake that occupies a single cell
+    pub fn from_grid(
+        grid: &[Vec<CellContents>],
+        hazard_damage: u8,
+        mode: EvaluateMode,
+    ) -> Result<Self, ConversionError> {
+        let () = Self::STORAGE_MATCHES_DIMENSIONS;
+        let height = grid.len();
+        let width = grid.first().map(|row| row.len()).unwrap_or(0);
+        if grid.iter().any(|row| row.len() != width) {
+            return Err(ConversionError::RaggedGrid);
+        }
+        Self::check_dimensions(width as u32, height as u32)?;
+        let width = width as u8;
+        let height = height as u8;
+
+        let mut cells = [Cell::empty(); BOARD_SIZE];
+        let mut snake_cells: Vec<(SnakeId, u16, u8, Position)> = vec![];
+        let mut hazard_stacks = Vec::with_capacity(width as usize * height as usize);
+        for (y, row) in grid.iter().enumerate() {
+            for (x, contents) in row.iter().enumerate() {
+                let position = Position::new(x as i32, y as i32);
+                let cell = &mut cells[CellIndex::<T>::new(position, width).as_usize()];
+                let hazards = match *contents {
+                    CellContents::Empty { hazards } => hazards,
+                    CellContents::Food { hazards } => {
+                        cell.set_food();
+                        hazards
+                    }
+                    CellContents::Snake {
+                        id,
+                        segment,
+                        stack,
+                        hazards,
+                    } => {
+                        if id.as_usize() >= MAX_SNAKES {
+                            return Err(ConversionError::TooManySnakes {
+                                count: id.as_usize() + 1,
+                            });
+                        }
+                        snake_cells.push((id, segment, stack, position));
+                        hazards
+                    }
+                };
+                hazard_stacks.push((position, hazards));
+            }
+        }
+
+        let mut healths = [0; MAX_SNAKES];
+        let mut heads = [CellIndex::from_i32(0); MAX_SNAKES];
+        let mut lengths = [0; MAX_SNAKES];
+
+        snake_cells.sort_by_key(|(id, segment, _, _)| (id.0, *segment));
+        for (id, group) in &snake_cells.iter().group_by(|(id, _, _, _)| *id) {
+            let group = group.collect_vec();
+            let malformed = ConversionError::MalformedSnake { id };
+
+            let mut body = VecDeque::new();
+            let mut expected_segment = 0;
+            let mut previous: Option<Position> = None;
+            for (i, (_, segment, stack, position)) in group.iter().enumerate() {
+                let is_tail = i == group.len() - 1;
+                let valid_stack = match stack {
+                    1 => true,
+                    2 => is_tail && i != 0,
+                    3 => group.len() == 1,
+                    _ => false,
+                };
+                let adjacent = previous
+                    .map(|p| are_adjacent(p, *position, width, height, mode))
+                    .unwrap_or(true);
+                if *segment != expected_segment || !valid_stack || !adjacent {
+                    return Err(malformed);
+                }
+
+                body.extend(std::iter::repeat_n(*position, *stack as usize));
+                expected_segment += *stack as u16;
+                previous = Some(*position);
+            }
+
+            healths[id.as_usize()] = 100;
+            lengths[id.as_usize()] = body.len() as u16;
+            heads[id.as_usize()] = place_snake_cells(&mut cells, id, body[0], &body, width);
+        }
+        // placing a snake overwrites its cells, so hazards go on last
+        for (position, hazards) in hazard_stacks {
+            cells[CellIndex::<T>::new(position, width).as_usize()].set_hazard_stack(hazards);
+        }
+
+        Ok(CellBoard {
+            hazard_damage,
+            cells,
+            healths,
+            heads,
+            lengths,
+            dimensions: D::from_dimensions(width, height),
+            you: SnakeId(0),
+            occupancy: Occupancy::from_cells(&cells),
+            game_mode: None,
+            ruleset_settings: None,
+            turn: 0,
+        })
+    }
+}
+
+fn are_adjacent(a: Position, b: Position, width: u8, height: u8, mode: EvaluateMode) -> bool {
+    let dx = (a.x - b.x).abs();
+    let dy = (a.y - b.y).abs();
+    let (dx, dy) = match mode {
+        EvaluateMode::Wrapped => (dx.min(width as i32 - dx), dy.min(height as i32 - dy)),
+        EvaluateMode::Standard | EvaluateMode::Solo => (dx, dy),
+    };
+
+    dx + dy == 1
+}
@@ -3,7 +3,7 @@ use crate::{
     types::HazardSettableGame,
 };
 
-use super::CellBoard;
+use super::{CellBoard, CellIndex};
 
 impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> HazardSettableGame
     for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
@@ -16,3 +16,17 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
         self.cells[pos.0.as_usize()].clear_hazard();
     }
 }
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// Lays the hazards from a pattern generator like `hazard_sinkhole` over the board, as cells
+    /// with the number of hazards to stack on them. The hazards are added to the ones already on
+    /// each cell, capped at `MAX_HAZARD_STACK`, so several patterns can be overlaid
+    pub fn apply_hazard_map(&mut self, hazards: impl IntoIterator<Item = (CellIndex<T>, u8)>) {
+        for (idx, stack) in hazards {
+            let cell = &mut self.cells[idx.as_usize()];
+            cell.set_hazard_stack(cell.hazard_stack().saturating_add(stack));
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
 use crate::{
@@ -56,7 +57,8 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
             }
             writeln!(f)?;
         }
-        let hash_repr = self.pack_as_hash();
+        // sort the keys so the output is byte identical across runs
+        let hash_repr: BTreeMap<_, _> = self.pack_as_hash().into_iter().collect();
         writeln!(f, "{}", serde_json::to_string(&hash_repr).unwrap())?;
         Ok(())
     }
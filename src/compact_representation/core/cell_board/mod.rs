@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-use std::error::Error;
-use std::slice::Iter;
+use std::collections::{HashMap, VecDeque};
 
 use itertools::Itertools;
 use rand::seq::IteratorRandom;
 
 use crate::types::EmptyCellGettableGame;
+use crate::types::FoodPlacer;
+use crate::types::FoodPlacerGame;
 use crate::types::SnakeIDMap;
 use crate::types::SnakeId;
 use crate::types::StandardFoodPlaceableGame;
@@ -16,24 +16,52 @@ use super::dimensions::Dimensions;
 use super::Cell;
 use super::CellIndex;
 use super::CellNum as CN;
+use super::ConversionError;
 use super::{DOUBLE_STACK, TRIPLE_STACK};
 
+#[cfg(feature = "bitboard")]
+mod bitboard;
+mod bytes;
+mod conformance;
 mod eval;
 mod food_gettable;
+mod grid;
 mod hazard_queryable;
 mod hazard_settable;
 mod head_gettable;
 mod health_gettable;
 mod length_gettable;
 mod neck_queryable;
+mod neighbors;
+mod occupancy;
 mod position_gettable;
+mod reachability;
+mod royale;
+mod ruleset;
+mod sinkhole;
 mod size_determinable;
 mod snake_body_gettable;
 mod snake_id_gettable;
+mod symmetry;
+mod threats;
+mod turn_determinable;
 mod victor_determinable;
+mod wire;
 mod you_determinable;
 
-pub use eval::EvaluateMode;
+#[cfg(feature = "bitboard")]
+pub use bitboard::{BitBoard, BitBoardWords};
+use bytes::{game_mode_from_u8, game_mode_to_u8};
+pub use eval::{
+    EliminationCause, EvaluateMode, FoodHealthMode, GameMode, HeadToHeadPolicy, SimEvent,
+    SimSettings, SimTrace,
+};
+pub use grid::CellContents;
+use occupancy::Occupancy;
+pub use ruleset::RulesetSettings;
+pub use sinkhole::SinkholeSchedule;
+pub use symmetry::{Symmetry, SymmetryGroup};
+pub use threats::{DangerWeights, MoveLegality};
 
 /// A compact board representation that is significantly faster for simulation than
 /// `battlesnake_game_types::wire_representation::Game`.
@@ -50,25 +78,106 @@ pub struct CellBoard<
     heads: [CellIndex<T>; MAX_SNAKES],
     lengths: [u16; MAX_SNAKES],
     dimensions: DimensionsType,
+    you: SnakeId,
+    occupancy: Occupancy<T>,
+    /// the ruleset this board was converted from, `None` for boards that weren't built from a game
+    game_mode: Option<GameMode>,
+    /// the settings of the game this board was converted from, `None` when they weren't sent
+    ruleset_settings: Option<RulesetSettings>,
+    /// the game turn, taken from the game this board was converted from and advanced by every
+    /// simulated turn
+    turn: u16,
 }
 
-#[allow(dead_code)]
 fn get_snake_id(
     snake: &crate::wire_representation::BattleSnake,
     snake_ids: &SnakeIDMap,
-) -> Option<SnakeId> {
+) -> Result<Option<SnakeId>, ConversionError> {
     if snake.health == 0 {
-        None
+        Ok(None)
     } else {
-        Some(*snake_ids.get(&snake.id).unwrap())
+        snake_ids
+            .get(&snake.id)
+            .copied()
+            .map(Some)
+            .ok_or_else(|| ConversionError::UnknownSnakeId {
+                id: snake.id.clone(),
+            })
     }
 }
 
+/// writes the cells for a single snake's body, given head first, and returns the index of
+/// its head
+fn place_snake_cells<T: CN, const BOARD_SIZE: usize>(
+    cells: &mut [Cell<T>; BOARD_SIZE],
+    snake_id: SnakeId,
+    head: Position,
+    body: &VecDeque<Position>,
+    width: u8,
+) -> CellIndex<T> {
+    let counts = &body.iter().counts();
+
+    let head_idx: CellIndex<T> = CellIndex::new(head, width);
+    let mut next_index = head_idx;
+    for (idx, pos) in body.iter().unique().enumerate() {
+        let cell_idx = CellIndex::new(*pos, width);
+        let count = counts.get(pos).unwrap();
+        // callers validate the body, so these only catch bugs in that validation
+        if idx == 0 {
+            debug_assert!(cell_idx == head_idx);
+        }
+        cells[cell_idx.0.as_usize()] = if *count == TRIPLE_STACK {
+            Cell::make_triple_stacked_piece(snake_id)
+        } else if *pos == head {
+            debug_assert!(*count != DOUBLE_STACK);
+            let tail_index = CellIndex::new(*body.back().unwrap(), width);
+            Cell::make_snake_head(snake_id, tail_index)
+        } else if *count == DOUBLE_STACK {
+            Cell::make_double_stacked_piece(snake_id, next_index)
+        } else {
+            Cell::make_body_piece(snake_id, next_index)
+        };
+        next_index = cell_idx;
+    }
+
+    head_idx
+}
+
 impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
 {
-    pub fn iter_healths(&self) -> Iter<'_, u8> {
-        self.healths.iter()
+    /// Ties `BOARD_SIZE` to compile time dimensions. Every constructor evaluates this, so a board
+    /// type like `CellBoard<u8, Fixed<11, 11>, 100, 4>` fails to compile when it is first built
+    /// instead of misbehaving at runtime
+    const STORAGE_MATCHES_DIMENSIONS: () = if let Some((width, height)) = D::FIXED {
+        assert!(
+            width as usize * height as usize == BOARD_SIZE,
+            "BOARD_SIZE must be exactly width * height for fixed dimensions"
+        )
+    };
+
+    /// Checks a `width` by `height` board against both the storage and the dimensions of this
+    /// board type. Every conversion goes through here, so games, grids and bytes agree on which
+    /// sizes fit
+    fn check_dimensions(width: u32, height: u32) -> Result<(), ConversionError> {
+        let too_large = width as u64 * height as u64 > BOARD_SIZE as u64
+            || width > u8::MAX as u32
+            || height > u8::MAX as u32;
+        if too_large || !D::can_hold(width, height) {
+            return Err(ConversionError::BoardTooLarge { width, height });
+        }
+
+        Ok(())
+    }
+
+    /// the id and head of every living snake, ordered by `SnakeId`
+    pub fn alive_heads(&self) -> impl Iterator<Item = (SnakeId, CellIndex<T>)> + '_ {
+        self.healths
+            .iter()
+            .zip(self.heads.iter())
+            .enumerate()
+            .filter(|(_, (health, _))| **health > 0)
+            .map(|(id, (_, head))| (SnakeId(id as u8), *head))
     }
 
     /// Asserts that all tails eventually loop back to a head and panics if the board is inconsistent
@@ -128,11 +237,30 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
             "cells".to_string(),
             self.cells.iter().map(|x| x.pack_as_u32()).collect(),
         );
+        hash.insert("you".to_string(), vec![self.you.0 as u32]);
+        if let Some(game_mode) = self.game_mode {
+            hash.insert(
+                "game_mode".to_string(),
+                vec![game_mode_to_u8(game_mode) as u32],
+            );
+        }
+        if let Some(settings) = self.ruleset_settings {
+            hash.insert(
+                "ruleset_settings".to_string(),
+                vec![
+                    settings.food_spawn_chance as u32,
+                    settings.minimum_food as u32,
+                    settings.shrink_every_n_turns as u32,
+                ],
+            );
+        }
+        hash.insert("turn".to_string(), vec![self.turn as u32]);
         hash
     }
 
     /// unpacks a packed hash repr back in to a CellBoard
     pub fn from_packed_hash(hash: &HashMap<String, Vec<u32>>) -> Self {
+        let () = Self::STORAGE_MATCHES_DIMENSIONS;
         let hazard_damage = hash.get("hazard_damage").unwrap()[0] as u8;
         let actual_width = hash.get("actual_width").unwrap()[0] as u8;
         let actual_height = hash
@@ -165,6 +293,17 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         }
 
         let dimensions = D::from_dimensions(actual_width, actual_height);
+        // older packed boards don't record you, in which case you were always SnakeId(0)
+        let you = SnakeId(hash.get("you").map(|y| y[0] as u8).unwrap_or(0));
+        let game_mode = hash
+            .get("game_mode")
+            .and_then(|m| game_mode_from_u8(m[0] as u8));
+        let ruleset_settings = hash.get("ruleset_settings").map(|s| RulesetSettings {
+            food_spawn_chance: s[0] as u8,
+            minimum_food: s[1] as u8,
+            shrink_every_n_turns: s[2] as u16,
+        });
+        let turn = hash.get("turn").map_or(0, |t| t[0] as u16);
 
         CellBoard {
             hazard_damage,
@@ -173,6 +312,11 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
             heads,
             lengths,
             dimensions,
+            you,
+            occupancy: Occupancy::from_cells(&cells),
+            game_mode,
+            ruleset_settings,
+            turn,
         }
     }
 
@@ -209,12 +353,22 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
             } else {
                 panic!("We should never get here");
             }
-            CellIndex::<T>::new(new_head_position, Self::width())
+            CellIndex::<T>::new(new_head_position, self.get_actual_width())
         } else {
-            CellIndex::<T>::new(new_head_position, Self::width())
+            CellIndex::<T>::new(new_head_position, self.get_actual_width())
         }
     }
 
+    /// the ruleset this board was converted from, `None` if it wasn't built from a game
+    pub fn game_mode(&self) -> Option<GameMode> {
+        self.game_mode
+    }
+
+    /// the ruleset settings of the game this board was converted from, if the server sent any
+    pub fn ruleset_settings(&self) -> Option<RulesetSettings> {
+        self.ruleset_settings
+    }
+
     pub fn get_actual_width(&self) -> u8 {
         self.dimensions.width()
     }
@@ -244,24 +398,94 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         self.kill(sid);
     }
 
-    /// Builds a cellboard from a given game, will return an error if the game doesn't match
-    /// the provided BOARD_SIZE or MAX_SNAKES. You are encouraged to use `CellBoard4Snakes11x11`
-    /// for the common game layout
-    pub fn convert_from_game(game: Game, snake_ids: &SnakeIDMap) -> Result<Self, Box<dyn Error>> {
-        if game.board.width * game.board.height > BOARD_SIZE as u32 {
-            return Err("game size doesn't fit in the given board size".into());
+    /// Checks whether a game can be converted to this board, collecting every problem instead
+    /// of stopping at the first one. An empty result means the board shape, snake count, snake
+    /// ids and bodies are all valid; ids missing from a snake id map aren't checked here
+    pub fn validate_game(game: &Game) -> Vec<ConversionError> {
+        Self::conversion_errors(game)
+    }
+
+    /// the problems that make `convert_from_game` fail, in the order they are checked
+    fn conversion_errors(game: &Game) -> Vec<ConversionError> {
+        let mut errors = vec![];
+
+        if let Err(e) = Self::check_dimensions(game.board.width, game.board.height) {
+            errors.push(e);
         }
 
         if game.board.snakes.len() > MAX_SNAKES {
-            return Err("too many snakes".into());
+            errors.push(ConversionError::TooManySnakes {
+                count: game.board.snakes.len(),
+            });
         }
 
+        // the id map is keyed by wire id, so snakes sharing an id would silently overwrite
+        // each other
+        errors.extend(
+            game.board
+                .snakes
+                .iter()
+                .map(|s| &s.id)
+                .duplicates()
+                .map(|id| ConversionError::DuplicateSnakeId { id: id.clone() }),
+        );
+
+        let off_board = |p: &Position| {
+            p.x < 0 || p.y < 0 || p.x >= game.board.width as i32 || p.y >= game.board.height as i32
+        };
         for snake in &game.board.snakes {
+            if snake.body.front() != Some(&snake.head) {
+                errors.push(ConversionError::HeadBodyMismatch {
+                    id: snake.id.clone(),
+                });
+            }
+            if snake.body.iter().any(off_board) {
+                errors.push(ConversionError::BodyOffBoard {
+                    id: snake.id.clone(),
+                });
+            }
             let counts = &snake.body.iter().counts();
-            if counts.values().any(|v| *v == TRIPLE_STACK) && counts.len() != 1 {
-                return Err(format!("snake {} has a bad body stack (3 segs on same square and more than one unique position)", snake.id).into());
+            let bad_triple = counts.values().any(|v| *v == TRIPLE_STACK) && counts.len() != 1;
+            // the head can never be doubled, only the tail grows in place
+            if bad_triple || counts.get(&snake.head) == Some(&DOUBLE_STACK) {
+                errors.push(ConversionError::BadBodyStack {
+                    id: snake.id.clone(),
+                });
+            }
+        }
+
+        // a cell holds a single snake, so placing a body over another would corrupt both.
+        // Snakes sharing an id are already reported as duplicates
+        let living = game.board.snakes.iter().filter(|s| s.health > 0);
+        for (a, b) in living.tuple_combinations() {
+            if a.id != b.id && a.body.iter().any(|pos| b.body.contains(pos)) {
+                errors.push(ConversionError::OverlappingBodies {
+                    a: a.id.clone(),
+                    b: b.id.clone(),
+                });
             }
         }
+
+        errors
+    }
+
+    /// Builds a cellboard from a given game, will return an error if the game doesn't match
+    /// the provided BOARD_SIZE or MAX_SNAKES. You are encouraged to use `CellBoard4Snakes11x11`
+    /// for the common game layout
+    pub fn convert_from_game(game: Game, snake_ids: &SnakeIDMap) -> Result<Self, ConversionError> {
+        let () = Self::STORAGE_MATCHES_DIMENSIONS;
+        if let Some(error) = Self::conversion_errors(&game).into_iter().next() {
+            return Err(error);
+        }
+
+        let you = match snake_ids.get(&game.you.id) {
+            Some(id) => *id,
+            None => {
+                return Err(ConversionError::UnknownSnakeId {
+                    id: game.you.id.clone(),
+                })
+            }
+        };
         let width = game.board.width as u8;
         let height = game.board.height as u8;
 
@@ -271,7 +495,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         let mut lengths: [u16; MAX_SNAKES] = [0; MAX_SNAKES];
 
         for snake in &game.board.snakes {
-            let snake_id = match get_snake_id(snake, snake_ids) {
+            let snake_id = match get_snake_id(snake, snake_ids)? {
                 Some(value) => value,
                 None => continue,
             };
@@ -281,33 +505,8 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
                 continue;
             }
             lengths[snake_id.0 as usize] = snake.body.len() as u16;
-
-            let counts = &snake.body.iter().counts();
-
-            let head_idx = CellIndex::new(snake.head, width);
-            let mut next_index = head_idx;
-            for (idx, pos) in snake.body.iter().unique().enumerate() {
-                let cell_idx = CellIndex::new(*pos, width);
-                let count = counts.get(pos).unwrap();
-                if idx == 0 {
-                    assert!(cell_idx == head_idx);
-                    heads[snake_id.0 as usize] = head_idx;
-                }
-                cells[cell_idx.0.as_usize()] = if *count == TRIPLE_STACK {
-                    Cell::make_triple_stacked_piece(snake_id)
-                } else if *pos == snake.head {
-                    // head can never be doubled, so let's assert it here, the cost of
-                    // one comparison is worth the saftey imo
-                    assert!(*count != DOUBLE_STACK);
-                    let tail_index = CellIndex::new(*snake.body.back().unwrap(), width);
-                    Cell::make_snake_head(snake_id, tail_index)
-                } else if *count == DOUBLE_STACK {
-                    Cell::make_double_stacked_piece(snake_id, next_index)
-                } else {
-                    Cell::make_body_piece(snake_id, next_index)
-                };
-                next_index = cell_idx;
-            }
+            heads[snake_id.0 as usize] =
+                place_snake_cells(&mut cells, snake_id, snake.head, &snake.body, width);
         }
         for y in 0..height {
             for x in 0..width {
@@ -317,8 +516,16 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
                 };
                 let cell_idx: CellIndex<T> = CellIndex::new(position, width);
 
-                if game.board.hazards.contains(&position) {
-                    cells[cell_idx.0.as_usize()].set_hazard();
+                // stacked hazards are listed once per hazard
+                let hazards = game
+                    .board
+                    .hazards
+                    .iter()
+                    .filter(|h| **h == position)
+                    .count();
+                if hazards > 0 {
+                    cells[cell_idx.0.as_usize()]
+                        .set_hazard_stack(hazards.min(u8::MAX as usize) as u8);
                 }
 
                 if game.board.food.contains(&position) {
@@ -335,6 +542,15 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
             healths,
             lengths,
             dimensions,
+            you,
+            occupancy: Occupancy::from_cells(&cells),
+            game_mode: Some(GameMode::from_ruleset_name(&game.game.ruleset.name)),
+            ruleset_settings: game
+                .game
+                .ruleset
+                .settings
+                .as_ref()
+                .map(RulesetSettings::from_settings),
             hazard_damage: game
                 .game
                 .ruleset
@@ -342,6 +558,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
                 .as_ref()
                 .map(|s| s.hazard_damage_per_turn)
                 .unwrap_or(15) as u8,
+            turn: game.turn.clamp(0, u16::MAX as i32) as u16,
         })
     }
     fn get_cell(&self, cell_index: CellIndex<T>) -> Cell<T> {
@@ -360,11 +577,38 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     pub fn get_length(&self, snake_id: SnakeId) -> u16 {
         self.lengths[snake_id.0 as usize]
     }
+
+    /// every snake's health, indexed by `SnakeId`. Dead snakes have 0
+    pub fn healths(&self) -> [u8; MAX_SNAKES] {
+        self.healths
+    }
+
+    /// every snake's length, indexed by `SnakeId`. Dead snakes have 0, dying resets it
+    pub fn lengths(&self) -> [u16; MAX_SNAKES] {
+        self.lengths
+    }
+
+    /// the health this snake has left, the usual tie break between snakes of equal length
+    pub fn total_health(&self, snake_id: SnakeId) -> u8 {
+        self.healths[snake_id.as_usize()]
+    }
+
+    /// Ranks two snakes, `Ordering::Greater` means `a` is ahead of `b`. The longer snake is
+    /// ahead, if they are equally long the one with more health is ahead and if that ties too
+    /// the one with the lower `SnakeId` is ahead. Dead snakes have 0 length and health, so they
+    /// rank behind every living snake. `Ordering::Equal` only when `a` and `b` are the same snake
+    pub fn compare_snakes(&self, a: SnakeId, b: SnakeId) -> std::cmp::Ordering {
+        self.lengths[a.as_usize()]
+            .cmp(&self.lengths[b.as_usize()])
+            .then(self.healths[a.as_usize()].cmp(&self.healths[b.as_usize()]))
+            .then(b.0.cmp(&a.0))
+    }
     /// Mutibaly call remove on the specified cell
     pub fn cell_remove(&mut self, cell_index: CellIndex<T>) {
         let mut old_cell = self.get_cell(cell_index);
         old_cell.remove();
         self.cells[cell_index.0.as_usize()] = old_cell;
+        self.sync_occupancy(cell_index.0.as_usize());
     }
     /// Set the given index to a Snake Body Piece
     pub fn set_cell_body_piece(
@@ -376,6 +620,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         let mut old_cell = self.get_cell(cell_index);
         old_cell.set_body_piece(sid, next_id);
         self.cells[cell_index.0.as_usize()] = old_cell;
+        self.sync_occupancy(cell_index.0.as_usize());
     }
 
     /// Set the given index as a double stacked snake
@@ -388,6 +633,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         let mut old_cell = self.get_cell(cell_index);
         old_cell.set_double_stacked(sid, next_id);
         self.cells[cell_index.0.as_usize()] = old_cell;
+        self.sync_occupancy(cell_index.0.as_usize());
     }
 
     /// Set the given index as a snake head
@@ -400,6 +646,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         let mut old_cell = self.get_cell(old_head_index);
         old_cell.set_head(sid, next_id);
         self.cells[old_head_index.0.as_usize()] = old_cell;
+        self.sync_occupancy(old_head_index.0.as_usize());
     }
 
     /// gets the snake ID at a given index, returns None if the provided index is not a snake cell
@@ -412,6 +659,18 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         self.get_cell(cell_idx).is_hazard()
     }
 
+    /// how many hazards are stacked on this cell, 0 if it isn't a hazard
+    pub fn hazard_stack(&self, cell_idx: CellIndex<T>) -> u8 {
+        self.get_cell(cell_idx).hazard_stack()
+    }
+
+    /// the hazard damage a head on this cell takes, the hazard damage once for every hazard
+    /// stacked on it
+    pub fn hazard_damage_at(&self, cell_idx: CellIndex<T>) -> u8 {
+        self.hazard_damage
+            .saturating_mul(self.get_cell(cell_idx).hazard_stack())
+    }
+
     /// determines if this cell is a snake head (including triple stacked)
     pub fn cell_is_snake_head(&self, cell_idx: CellIndex<T>) -> bool {
         self.get_cell(cell_idx).is_head()
@@ -445,9 +704,185 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         }
     }
 
-    /// determin the width of the CellBoard
-    pub fn width() -> u8 {
-        (BOARD_SIZE as f32).sqrt() as u8
+    /// determines if this cell is guaranteed to not hold a snake next turn, either because it
+    /// is not a snake cell now or because it is a tail that will be vacated. Stacked tails stay in
+    /// place, so they are not free. A tail is vacated even if its snake eats this turn, since the
+    /// new segment is only added at the end of the turn
+    pub fn will_be_free_next_turn(&self, cell_idx: CellIndex<T>) -> bool {
+        let cell = self.get_cell(cell_idx);
+        !(cell.is_body() || cell.is_head()) || self.cell_is_single_tail(cell_idx)
+    }
+
+    /// whether this snake ate food on the previous turn, inferred from its tail being double
+    /// stacked: the new segment is added on top of the tail, so the tail stays in place for one
+    /// turn while the snake grows.
+    ///
+    /// On the first turn of a game every snake's tail is still double stacked from the triple
+    /// stacked starting position, so this also returns true there even though nothing was
+    /// eaten. Returns false for dead snakes
+    pub fn just_ate(&self, sid: SnakeId) -> bool {
+        if self.healths[sid.as_usize()] == 0 {
+            return false;
+        }
+        let head = self.heads[sid.as_usize()];
+        self.get_cell(head)
+            .get_tail_position(head)
+            .map(|tail| self.get_cell(tail).is_double_stacked_piece())
+            .unwrap_or(false)
+    }
+
+    /// the most body segments stacked on any one of this snake's cells: 1 if nothing is
+    /// stacked, 2 after eating or on the first turn, and 3 for a snake still entirely on its
+    /// starting cell. Returns 0 for dead snakes. Useful for checking growth accounting
+    pub fn max_stack(&self, sid: SnakeId) -> u8 {
+        if self.healths[sid.as_usize()] == 0 {
+            return 0;
+        }
+        let stack = |cell: Cell<T>| {
+            if cell.is_triple_stacked_piece() {
+                3
+            } else if cell.is_double_stacked_piece() {
+                2
+            } else {
+                1
+            }
+        };
+
+        let head = self.heads[sid.as_usize()];
+        let mut max = stack(self.get_cell(head));
+        let mut current = self.get_cell(head).get_tail_position(head);
+        while let Some(idx) = current {
+            max = max.max(stack(self.get_cell(idx)));
+            current = self.get_cell(idx).get_next_index();
+        }
+        max
+    }
+
+    /// the average position of this snake's body cells, rounded towards zero, with stacked
+    /// cells counted once. In wrapped mode a body can straddle the edges and the average
+    /// means nothing, so the head position is returned instead. `None` for dead snakes, which
+    /// have no body left
+    pub fn snake_centroid(&self, sid: SnakeId, mode: EvaluateMode) -> Option<Position> {
+        if self.healths[sid.as_usize()] == 0 {
+            return None;
+        }
+        let width = self.get_actual_width();
+        let head = self.heads[sid.as_usize()];
+        if mode == EvaluateMode::Wrapped {
+            return Some(head.into_position(width));
+        }
+
+        let (mut x, mut y, mut count) = (0, 0, 0);
+        let mut current = self.get_cell(head).get_tail_position(head);
+        while let Some(idx) = current {
+            let pos = idx.into_position(width);
+            x += pos.x;
+            y += pos.y;
+            count += 1;
+            current = self.get_cell(idx).get_next_index();
+        }
+        if count == 0 {
+            return Some(head.into_position(width));
+        }
+        Some(Position {
+            x: x / count,
+            y: y / count,
+        })
+    }
+
+    /// how many turns until this snake starves if it eats nothing and its head stays in the
+    /// same kind of cell it is in now: health decays by 1 per turn, plus the hazard damage of
+    /// every hazard stacked under the head. Returns 0 for dead snakes
+    pub fn turns_until_starve(&self, sid: SnakeId) -> u16 {
+        let health = self.healths[sid.as_usize()] as u16;
+        let damage_per_turn = 1 + self.hazard_damage_at(self.heads[sid.as_usize()]) as u16;
+
+        health.div_ceil(damage_per_turn)
+    }
+
+    /// the turn this snake starves on if it eats nothing, `turns_until_starve` turns from now
+    pub fn projected_starve_turn(&self, sid: SnakeId) -> u16 {
+        self.turn.saturating_add(self.turns_until_starve(sid))
+    }
+
+    /// this snake's health minus the hazard damage it takes next turn if its head stays in a
+    /// hazard. The regular decay of 1 isn't included. Can be negative, a snake at or below 0 dies
+    /// next turn unless it leaves the hazard or eats. Returns 0 for dead snakes
+    pub fn effective_health(&self, sid: SnakeId) -> i32 {
+        let health = self.healths[sid.as_usize()] as i32;
+        if health == 0 {
+            return health;
+        }
+
+        health - self.hazard_damage_at(self.heads[sid.as_usize()]) as i32
+    }
+
+    /// this snake's length minus the length of the longest other living snake. Positive means
+    /// it wins a head to head against every enemy. Without living enemies this is its own
+    /// length, and dead snakes count as length 0
+    pub fn length_advantage(&self, me: SnakeId) -> i32 {
+        let living_length = |id: usize| {
+            if self.healths[id] > 0 {
+                self.lengths[id] as i32
+            } else {
+                0
+            }
+        };
+        let longest_enemy = (0..MAX_SNAKES)
+            .filter(|id| *id != me.as_usize())
+            .map(living_length)
+            .max()
+            .unwrap_or(0);
+
+        living_length(me.as_usize()) - longest_enemy
+    }
+
+    /// Whether this board is a freshly started game: turn 0, and every living snake has full
+    /// health and all 3 of its segments stacked on a distinct one of the standard spawn points.
+    /// The official rules only use fixed spawn points on 7x7, 11x11 and 19x19 boards, the corners
+    /// and edge midpoints one cell in from the walls, so other sizes are never a standard start.
+    /// Food isn't checked
+    pub fn is_standard_start(&self) -> bool {
+        let width = self.get_actual_width();
+        if self.turn != 0 || width != self.get_actual_height() || ![7, 11, 19].contains(&width) {
+            return false;
+        }
+
+        let (low, mid, high) = (1, (width - 1) / 2, width - 2);
+        let spawn_points = [
+            (low, low),
+            (low, high),
+            (high, low),
+            (high, high),
+            (low, mid),
+            (mid, low),
+            (mid, high),
+            (high, mid),
+        ];
+        let mut living = 0;
+        let all_spawned = self.alive_heads().all(|(id, head)| {
+            living += 1;
+            self.healths[id.as_usize()] == 100
+                && self.lengths[id.as_usize()] == 3
+                && self.get_cell(head).is_triple_stacked_piece()
+                && spawn_points.contains(&head.to_xy(width))
+        });
+
+        all_spawned && living > 0
+    }
+
+    /// the fraction of the playable board that is occupied by snakes, between 0 and 1.
+    ///
+    /// Stacked snake segments occupy a single cell. Food and hazards do not count towards
+    /// fullness, hazards are still traversable so they are treated as free space.
+    pub fn board_fullness(&self) -> f32 {
+        let total = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        let occupied = self.cells[..total]
+            .iter()
+            .filter(|c| c.is_body() || c.is_head())
+            .count();
+
+        occupied as f32 / total as f32
     }
 }
 
@@ -469,18 +904,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     StandardFoodPlaceableGame for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
 {
     fn place_food(&mut self, rng: &mut impl rand::Rng) {
-        // TODO: Get these constants from the game
-        let min_food = 1;
-        let food_spawn_chance = 0.15;
-
-        // This is an optimization when min_food is 1. We know we don't need to spawn food if there if any of the board
-        // so we can short circuit on the first food we find
-        let food_to_add = if !self.cells.iter().any(|c| c.is_food()) {
-            min_food
-        } else {
-            usize::from(rng.gen_bool(food_spawn_chance))
-        };
-
+        let food_to_add = self.food_to_spawn(rng);
         if food_to_add == 0 {
             return;
         }
@@ -493,6 +917,48 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     }
 }
 
+impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> FoodPlacerGame
+    for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    fn place_food_with(&mut self, rng: &mut impl rand::Rng, placer: &mut impl FoodPlacer<Self>) {
+        self.place_food_from(rng, |board| placer.next_food(board))
+    }
+}
+
+impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// how many food the standard rules spawn this turn
+    fn food_to_spawn(&self, rng: &mut impl rand::Rng) -> usize {
+        let settings = self.ruleset_settings.unwrap_or_default();
+        let min_food = settings.minimum_food as usize;
+        // only `from_settings` clamps the chance, and `gen_bool` panics on more than 100%
+        let food_spawn_chance = settings.food_spawn_chance.min(100) as f64 / 100.0;
+
+        let food = self.cells.iter().filter(|c| c.is_food()).count();
+        if food < min_food {
+            min_food - food
+        } else {
+            usize::from(rng.gen_bool(food_spawn_chance))
+        }
+    }
+
+    /// spawns as many food as the standard rules call for, on the cells `next_food` picks
+    pub fn place_food_from(
+        &mut self,
+        rng: &mut impl rand::Rng,
+        mut next_food: impl FnMut(&Self) -> Option<CellIndex<T>>,
+    ) {
+        for _ in 0..self.food_to_spawn(rng) {
+            match next_food(self) {
+                Some(idx) if self.get_cell(idx).is_empty() => self.cells[idx.as_usize()].set_food(),
+                Some(_) => {}
+                None => break,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::compact_representation::dimensions::Square;
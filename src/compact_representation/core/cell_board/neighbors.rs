@@ -0,0 +1,61 @@
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::Move,
+};
+
+use super::{CellBoard, CellIndex, EvaluateMode};
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// the cells that can be reached from `idx` in a single move, along with the move that
+    /// gets there. In wrapped mode moves off the edge of the board wrap around, otherwise they
+    /// are skipped
+    pub fn neighbor_cells(
+        &self,
+        idx: CellIndex<T>,
+        mode: EvaluateMode,
+    ) -> impl Iterator<Item = (Move, CellIndex<T>)> + '_ {
+        let width = self.get_actual_width();
+        let height = self.get_actual_height() as i32;
+        let pos = idx.into_position(width);
+
+        Move::all_iter().filter_map(move |mv| {
+            let mut new_pos = pos.add_vec(mv.to_vector());
+            if self.off_board(new_pos) {
+                match mode {
+                    EvaluateMode::Wrapped => {
+                        new_pos.x = new_pos.x.rem_euclid(width as i32);
+                        new_pos.y = new_pos.y.rem_euclid(height);
+                    }
+                    EvaluateMode::Standard | EvaluateMode::Solo => return None,
+                }
+            }
+
+            Some((mv, CellIndex::new(new_pos, width)))
+        })
+    }
+
+    /// how many of the cells next to `idx` can currently be moved in to, i.e. are on the board
+    /// and hold no snake. Degree 1 cells are dead ends and degree 2 cells are corridors. In
+    /// wrapped mode neighbors across the edge count
+    pub fn open_degree(&self, idx: CellIndex<T>, mode: EvaluateMode) -> u8 {
+        self.neighbor_cells(idx, mode)
+            .filter(|(_, neighbor)| {
+                let cell = self.get_cell(*neighbor);
+                !(cell.is_body() || cell.is_head())
+            })
+            .count() as u8
+    }
+
+    /// `open_degree` for every cell on the board in one sweep. Indexes past the end of the
+    /// board are 0
+    pub fn open_degree_map(&self, mode: EvaluateMode) -> [u8; BOARD_SIZE] {
+        let mut degrees = [0; BOARD_SIZE];
+        let area = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        for (idx, degree) in degrees[..area].iter_mut().enumerate() {
+            *degree = self.open_degree(CellIndex::from_usize(idx), mode);
+        }
+        degrees
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::{Move, SnakeId},
+};
+
+#[cfg(feature = "bitboard")]
+use super::{BitBoard, CellIndex};
+use super::{CellBoard, EvaluateMode};
+
+/// One bit per cell that is set when any snake's head or body is on it. The mask is kept in sync
+/// with the cells by every method that writes a snake cell, so collision checks can test a single
+/// bit instead of decoding a cell
+#[cfg(feature = "bitboard")]
+pub type Occupancy<T> = BitBoard<T>;
+
+/// Without the `bitboard` feature the occupancy mask is zero sized and every update is a no-op
+#[cfg(not(feature = "bitboard"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Occupancy<T>(std::marker::PhantomData<T>);
+
+#[cfg(not(feature = "bitboard"))]
+impl<T: CellNum> Occupancy<T> {
+    pub fn from_cells(_cells: &[crate::compact_representation::core::Cell<T>]) -> Self {
+        Occupancy(std::marker::PhantomData)
+    }
+
+    #[inline]
+    pub fn set(&mut self, _idx: usize, _occupied: bool) {}
+}
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// records whether the cell at `idx` holds a snake, after it has been written
+    #[inline]
+    pub(crate) fn sync_occupancy(&mut self, idx: usize) {
+        let cell = self.cells[idx];
+        self.occupancy.set(idx, cell.is_body() || cell.is_head());
+    }
+
+    /// the cells holding any snake's head or body
+    #[cfg(feature = "bitboard")]
+    pub fn all_bodies_mask(&self) -> BitBoard<T> {
+        self.occupancy
+    }
+
+    /// the cells holding this snake's head or body, empty for dead snakes
+    #[cfg(feature = "bitboard")]
+    pub fn body_mask(&self, sid: SnakeId) -> BitBoard<T> {
+        let mut mask = BitBoard::empty();
+        if self.healths[sid.as_usize()] == 0 {
+            return mask;
+        }
+        let head = self.heads[sid.as_usize()];
+        let mut current = self.get_cell(head).get_tail_position(head);
+        while let Some(idx) = current {
+            mask.insert(idx.as_usize());
+            current = self.get_cell(idx).get_next_index();
+        }
+        mask.insert(head.as_usize());
+        mask
+    }
+
+    /// the cells without a snake that are orthogonally next to any snake's head or body. Each
+    /// occupied cell's neighbors are checked against the occupancy mask in turn, following
+    /// `mode` at the edges. Food and hazard cells are included
+    #[cfg(feature = "bitboard")]
+    pub fn body_frontier(&self, mode: EvaluateMode) -> BitBoard<T> {
+        let mut frontier = BitBoard::empty();
+        for idx in self.occupancy.iter() {
+            for (_, neighbor) in self.neighbor_cells(CellIndex::from_usize(idx), mode) {
+                if !self.occupancy.contains(neighbor.as_usize()) {
+                    frontier.insert(neighbor.as_usize());
+                }
+            }
+        }
+        frontier
+    }
+
+    /// whether moving `sid` in direction `mv` is certain death: off the edge of a standard
+    /// board, or in to a snake cell that won't be vacated next turn. Head to head collisions
+    /// aren't certain, so they are not considered. With the `bitboard` feature the snake check
+    /// is a single bit test
+    pub fn is_move_immediately_fatal(&self, sid: SnakeId, mv: Move, mode: EvaluateMode) -> bool {
+        let head = self.heads[sid.as_usize()];
+        let target = match self.neighbor_cells(head, mode).find(|(m, _)| *m == mv) {
+            Some((_, target)) => target,
+            None => return true,
+        };
+
+        #[cfg(feature = "bitboard")]
+        {
+            self.occupancy.contains(target.as_usize()) && !self.cell_is_single_tail(target)
+        }
+        #[cfg(not(feature = "bitboard"))]
+        {
+            !self.will_be_free_next_turn(target)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bitboard"))]
+mod tests {
+    use itertools::Itertools;
+    use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+    use super::{CellBoard, EvaluateMode, Occupancy};
+    use crate::{
+        compact_representation::{core::simulate_with_moves, dimensions::Square},
+        game_fixture,
+        types::{build_snake_id_map, Move, SimulatorInstruments},
+    };
+
+    #[derive(Debug)]
+    struct Instruments;
+    impl SimulatorInstruments for Instruments {
+        fn observe_simulation(&self, _: std::time::Duration) {}
+    }
+
+    #[test]
+    fn test_occupancy_stays_in_sync() {
+        let g = game_fixture(include_str!("../../../../fixtures/start_of_game.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let mut board =
+            CellBoard::<u8, Square, { 11 * 11 }, 4>::convert_from_game(g, &snake_ids).unwrap();
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        while board.alive_heads().count() > 0 {
+            assert_eq!(board.occupancy, Occupancy::from_cells(&board.cells));
+            let moves = board
+                .alive_heads()
+                .map(|(sid, _)| {
+                    let safe = Move::all_iter()
+                        .filter(|mv| {
+                            !board.is_move_immediately_fatal(sid, *mv, EvaluateMode::Standard)
+                        })
+                        .collect_vec();
+                    (sid, [*safe.choose(&mut rng).unwrap_or(&Move::Up)])
+                })
+                .collect_vec();
+            let (_, next) =
+                simulate_with_moves(&board, &Instruments, moves, EvaluateMode::Standard)
+                    .next()
+                    .unwrap();
+            board = next;
+        }
+        assert_eq!(board.occupancy, Occupancy::from_cells(&board.cells));
+    }
+
+    #[test]
+    fn test_occupancy_is_sized_by_cell_index() {
+        // a u8 index reaches 256 cells, so the 7x7, 11x11 and 15x15 boards need 4 words
+        assert_eq!(std::mem::size_of::<Occupancy<u8>>(), 4 * 8);
+        assert_eq!(Occupancy::<u8>::CAPACITY, 256);
+        assert_eq!(Occupancy::<u16>::CAPACITY, 40 * 64);
+    }
+}
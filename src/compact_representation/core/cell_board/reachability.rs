@@ -0,0 +1,259 @@
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::SnakeId,
+};
+
+use super::{CellBoard, CellIndex, EvaluateMode};
+
+const UNOWNED: u8 = u8::MAX;
+const TIED: u8 = u8::MAX - 1;
+/// `longest_escape_corridor` stops at paths this long
+const ESCAPE_CORRIDOR_MAX_DEPTH: u16 = 32;
+/// the most cells `longest_escape_corridor` expands before settling for the longest path so far
+const ESCAPE_CORRIDOR_BUDGET: u32 = 4096;
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// Counts the cells each snake can reach strictly before every other snake, using a single
+    /// breadth first flood fill started from all heads at once (a voronoi partition of the board).
+    ///
+    /// Snake heads and bodies are treated as walls and the heads themselves are not counted.
+    /// Cells that two or more snakes reach on the same turn are tied: they are excluded from
+    /// every snake's count, and the flood fill does not continue through them.
+    pub fn reachable_all(&self, mode: EvaluateMode) -> [u16; MAX_SNAKES] {
+        let mut owners = [UNOWNED; BOARD_SIZE];
+        let mut distances = [0u16; BOARD_SIZE];
+        let mut distance = 0;
+        let mut frontier = Vec::with_capacity(MAX_SNAKES);
+
+        for (id, head) in self.alive_heads() {
+            owners[head.as_usize()] = id.0;
+            frontier.push(head);
+        }
+
+        let mut next_frontier: Vec<CellIndex<T>> = Vec::new();
+        while !frontier.is_empty() {
+            distance += 1;
+            for idx in frontier.drain(..) {
+                let owner = owners[idx.as_usize()];
+                if owner == TIED {
+                    continue;
+                }
+
+                for (_, neighbor) in self.neighbor_cells(idx, mode) {
+                    let cell = self.get_cell(neighbor);
+                    if cell.is_body() || cell.is_head() {
+                        continue;
+                    }
+
+                    let current = owners[neighbor.as_usize()];
+                    if current == UNOWNED {
+                        owners[neighbor.as_usize()] = owner;
+                        distances[neighbor.as_usize()] = distance;
+                        next_frontier.push(neighbor);
+                    } else if current != owner && distances[neighbor.as_usize()] == distance {
+                        owners[neighbor.as_usize()] = TIED;
+                    }
+                }
+            }
+            std::mem::swap(&mut frontier, &mut next_frontier);
+        }
+
+        let mut counts = [0; MAX_SNAKES];
+        for (idx, owner) in owners.iter().enumerate() {
+            let owner = *owner as usize;
+            if owner < MAX_SNAKES && !self.get_cell(CellIndex::from_usize(idx)).is_head() {
+                counts[owner] += 1;
+            }
+        }
+
+        counts
+    }
+
+    /// The number of moves `sid`'s head needs to reach every cell, found with a breadth first
+    /// search from the head. Heads and bodies of every snake are treated as walls, so they and
+    /// any cell walled off from the head are `None`. The head itself is `Some(0)`, and a dead
+    /// snake reaches nothing
+    pub fn distance_field(&self, sid: SnakeId, mode: EvaluateMode) -> [Option<u16>; BOARD_SIZE] {
+        self.distance_field_avoiding(sid, &[], mode)
+    }
+
+    /// How many cells `sid` can reach, like counting its `distance_field`, but without
+    /// starting through the cells next to its head that a living enemy at least as long could
+    /// also move in to. Stepping there would lose or tie a head to head, so the flood fill only
+    /// reaches those cells, and anything behind them, through other routes. The head isn't
+    /// counted, and a dead snake reaches nothing
+    pub fn head_to_head_safe_reachable_squares(&self, sid: SnakeId, mode: EvaluateMode) -> u16 {
+        let my_length = self.lengths[sid.as_usize()];
+        let contested = self
+            .contested_cells(sid, mode)
+            .into_iter()
+            .filter(|(_, enemy)| self.lengths[enemy.as_usize()] >= my_length)
+            .map(|(cell, _)| cell)
+            .collect::<Vec<_>>();
+
+        self.distance_field_avoiding(sid, &contested, mode)
+            .iter()
+            .filter(|distance| matches!(distance, Some(d) if *d > 0))
+            .count() as u16
+    }
+
+    /// `distance_field` where the head can't move straight in to the cells in `avoid`, they can
+    /// still be reached later through other cells
+    fn distance_field_avoiding(
+        &self,
+        sid: SnakeId,
+        avoid: &[CellIndex<T>],
+        mode: EvaluateMode,
+    ) -> [Option<u16>; BOARD_SIZE] {
+        let mut distances = [None; BOARD_SIZE];
+        if self.healths[sid.as_usize()] == 0 {
+            return distances;
+        }
+
+        let head = self.heads[sid.as_usize()];
+        distances[head.as_usize()] = Some(0);
+        let mut frontier = vec![head];
+        let mut next_frontier: Vec<CellIndex<T>> = Vec::new();
+        let mut distance = 0;
+        while !frontier.is_empty() {
+            distance += 1;
+            for idx in frontier.drain(..) {
+                for (_, neighbor) in self.neighbor_cells(idx, mode) {
+                    let cell = self.get_cell(neighbor);
+                    if cell.is_body()
+                        || cell.is_head()
+                        || distances[neighbor.as_usize()].is_some()
+                        || (distance == 1 && avoid.contains(&neighbor))
+                    {
+                        continue;
+                    }
+
+                    distances[neighbor.as_usize()] = Some(distance);
+                    next_frontier.push(neighbor);
+                }
+            }
+            std::mem::swap(&mut frontier, &mut next_frontier);
+        }
+
+        distances
+    }
+
+    /// An estimate of the longest simple path of open cells `sid` can walk starting with a move
+    /// out of its head, found with a depth first search. Like `distance_field`, heads and bodies
+    /// are walls. Unlike a flood fill this punishes being boxed in to a one wide corridor, even
+    /// when the corridor opens up further on.
+    ///
+    /// Longest paths are exponential to find exactly, so the search stops at paths of
+    /// `ESCAPE_CORRIDOR_MAX_DEPTH` cells and after expanding `ESCAPE_CORRIDOR_BUDGET` cells,
+    /// returning the longest path seen so far. The result is a lower bound, capped at the max
+    /// depth. A dead snake has no corridor
+    pub fn longest_escape_corridor(&self, sid: SnakeId, mode: EvaluateMode) -> u16 {
+        if self.healths[sid.as_usize()] == 0 {
+            return 0;
+        }
+
+        let head = self.heads[sid.as_usize()];
+        let mut visited = [false; BOARD_SIZE];
+        visited[head.as_usize()] = true;
+        let mut budget = ESCAPE_CORRIDOR_BUDGET;
+        self.corridor_from(head, 0, &mut visited, &mut budget, mode)
+    }
+
+    /// the longest path out of `idx`, which is `depth` cells from the head, that avoids
+    /// `visited`
+    fn corridor_from(
+        &self,
+        idx: CellIndex<T>,
+        depth: u16,
+        visited: &mut [bool; BOARD_SIZE],
+        budget: &mut u32,
+        mode: EvaluateMode,
+    ) -> u16 {
+        if depth == ESCAPE_CORRIDOR_MAX_DEPTH || *budget == 0 {
+            return depth;
+        }
+        *budget -= 1;
+
+        let mut longest = depth;
+        for (_, neighbor) in self.neighbor_cells(idx, mode) {
+            let cell = self.get_cell(neighbor);
+            if cell.is_body() || cell.is_head() || visited[neighbor.as_usize()] {
+                continue;
+            }
+
+            visited[neighbor.as_usize()] = true;
+            longest = longest.max(self.corridor_from(neighbor, depth + 1, visited, budget, mode));
+            visited[neighbor.as_usize()] = false;
+            if longest == ESCAPE_CORRIDOR_MAX_DEPTH {
+                break;
+            }
+        }
+
+        longest
+    }
+
+    /// A soft alternative to the hard voronoi split of `reachable_all`. Every living snake's
+    /// head radiates an influence of `decay` to the power of its `distance_field` distance, so a
+    /// head has an influence of 1 on its own cell. `me`'s influence counts positive and every
+    /// enemy's negative, and each cell holds the net sum. Heads and bodies block the spread like
+    /// they do in `distance_field`, so bodies read 0 and a head only holds its own snake's
+    /// influence. Hazards don't block it
+    pub fn influence_map(&self, me: SnakeId, decay: f32, mode: EvaluateMode) -> [f32; BOARD_SIZE] {
+        let mut influence = [0.0; BOARD_SIZE];
+        for (id, _) in self.alive_heads() {
+            let sign = if id == me { 1.0 } else { -1.0 };
+            for (cell, distance) in influence.iter_mut().zip(self.distance_field(id, mode)) {
+                if let Some(distance) = distance {
+                    *cell += sign * decay.powi(distance as i32);
+                }
+            }
+        }
+
+        influence
+    }
+
+    /// The fewest turns until `me` and `them` could both have their heads on the same cell,
+    /// the smallest over every cell both can reach of the larger of their `distance_field`
+    /// distances to it. Bodies are walls, so this ignores tails moving out of the way. `None`
+    /// if no cell is reachable by both, including when either snake is dead
+    pub fn turns_to_intercept(
+        &self,
+        me: SnakeId,
+        them: SnakeId,
+        mode: EvaluateMode,
+    ) -> Option<u16> {
+        let mine = self.distance_field(me, mode);
+        let theirs = self.distance_field(them, mode);
+        mine.iter()
+            .zip(theirs.iter())
+            .filter_map(|(a, b)| Some((*a)?.max((*b)?)))
+            .min()
+    }
+
+    /// Every food on the board with each snake's `distance_field` distance to it, indexed by
+    /// `SnakeId`. Food is listed in increasing index order, and uses one search per living snake
+    pub fn food_distance_matrix(
+        &self,
+        mode: EvaluateMode,
+    ) -> Vec<(CellIndex<T>, [Option<u16>; MAX_SNAKES])> {
+        let fields = (0..MAX_SNAKES)
+            .map(|id| self.distance_field(SnakeId(id as u8), mode))
+            .collect::<Vec<_>>();
+
+        let area = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        self.cells[..area]
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_food())
+            .map(|(idx, _)| {
+                let mut row = [None; MAX_SNAKES];
+                for (distance, field) in row.iter_mut().zip(fields.iter()) {
+                    *distance = field[idx];
+                }
+                (CellIndex::from_usize(idx), row)
+            })
+            .collect()
+    }
+}
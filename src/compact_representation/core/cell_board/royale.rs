@@ -0,0 +1,85 @@
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::SnakeId,
+    wire_representation::RoyaleSettings,
+};
+
+use super::{CellBoard, CellIndex, EvaluateMode};
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// The fewest turns until this cell could be a hazard under the royale shrink schedule.
+    ///
+    /// Royale shrinks the safe area by one row or column from a random side every
+    /// `shrink_every_n_turns` turns. The board doesn't know the current turn or which sides will
+    /// be picked, so this is pessimistic: the next shrink happens next turn and every shrink
+    /// comes from the side nearest this cell. Hazards are `Some(0)`, a cell on the edge of the
+    /// safe area is `Some(1)`, and `None` means royale never shrinks
+    pub fn turns_until_hazard(&self, idx: CellIndex<T>, royale: &RoyaleSettings) -> Option<u16> {
+        self.turns_until_hazard_within(idx, royale, self.safe_area())
+    }
+
+    /// How many cells `sid` can reach (see `distance_field`) that can't become a hazard within
+    /// the next `horizon` turns according to `turns_until_hazard`. Unlike a plain flood fill this
+    /// doesn't count space the royale shrink is about to take away
+    pub fn safe_reachable_squares(
+        &self,
+        sid: SnakeId,
+        horizon: u16,
+        royale: &RoyaleSettings,
+        mode: EvaluateMode,
+    ) -> u16 {
+        let safe_area = self.safe_area();
+        self.distance_field(sid, mode)
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| matches!(distance, Some(d) if *d > 0))
+            .filter(|(idx, _)| {
+                self.turns_until_hazard_within(CellIndex::from_usize(*idx), royale, safe_area)
+                    .is_none_or(|turns| turns > horizon)
+            })
+            .count() as u16
+    }
+
+    /// the smallest rectangle holding every cell that isn't a hazard, as min x, min y, max x
+    /// and max y
+    fn safe_area(&self) -> (i32, i32, i32, i32) {
+        let width = self.get_actual_width();
+        let area = width as usize * self.get_actual_height() as usize;
+        let mut bounds = (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+        for (idx, cell) in self.cells[..area].iter().enumerate() {
+            if !cell.is_hazard() {
+                let pos = CellIndex::<T>::from_usize(idx).into_position(width);
+                bounds.0 = bounds.0.min(pos.x);
+                bounds.1 = bounds.1.min(pos.y);
+                bounds.2 = bounds.2.max(pos.x);
+                bounds.3 = bounds.3.max(pos.y);
+            }
+        }
+        bounds
+    }
+
+    fn turns_until_hazard_within(
+        &self,
+        idx: CellIndex<T>,
+        royale: &RoyaleSettings,
+        (min_x, min_y, max_x, max_y): (i32, i32, i32, i32),
+    ) -> Option<u16> {
+        if self.get_cell(idx).is_hazard() {
+            return Some(0);
+        }
+        if royale.shrink_every_n_turns <= 0 {
+            return None;
+        }
+
+        let pos = idx.into_position(self.get_actual_width());
+        let shrinks = (pos.x - min_x)
+            .min(max_x - pos.x)
+            .min(pos.y - min_y)
+            .min(max_y - pos.y) as u32
+            + 1;
+        let turns = 1 + (shrinks - 1) * royale.shrink_every_n_turns as u32;
+        Some(turns.min(u16::MAX as u32) as u16)
+    }
+}
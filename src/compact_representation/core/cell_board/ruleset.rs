@@ -0,0 +1,73 @@
+use crate::wire_representation::{RoyaleSettings, Settings};
+
+/// The ruleset settings a game was started with, beyond the hazard damage every board keeps.
+/// Simulation helpers like food spawning read these instead of assuming the official defaults.
+/// Settings the server leaves out take the values from `Default`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RulesetSettings {
+    /// the percent chance that a food spawns on a turn where the minimum food is on the board,
+    /// anything over 100 spawns food as if it were 100
+    pub food_spawn_chance: u8,
+    /// food is spawned until at least this many are on the board
+    pub minimum_food: u8,
+    /// royale shrinks the safe area by a row or column every this many turns, 0 if it never does
+    pub shrink_every_n_turns: u16,
+}
+
+impl Default for RulesetSettings {
+    fn default() -> Self {
+        Self {
+            food_spawn_chance: 15,
+            minimum_food: 1,
+            shrink_every_n_turns: 25,
+        }
+    }
+}
+
+impl RulesetSettings {
+    /// the number of bytes `to_bytes` writes
+    pub(super) const BYTES_LEN: usize = 4;
+
+    /// reads the settings sent by the battlesnake server, clamping values that don't fit
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            food_spawn_chance: settings.food_spawn_chance.clamp(0, 100) as u8,
+            minimum_food: settings.minimum_food.clamp(0, u8::MAX as i32) as u8,
+            shrink_every_n_turns: settings
+                .royale
+                .map_or(Self::default().shrink_every_n_turns, |royale| {
+                    royale.shrink_every_n_turns.clamp(0, u16::MAX as i32) as u16
+                }),
+        }
+    }
+
+    /// the royale part of these settings, as taken by `turns_until_hazard`
+    pub fn royale(&self) -> RoyaleSettings {
+        RoyaleSettings {
+            shrink_every_n_turns: self.shrink_every_n_turns as i32,
+        }
+    }
+
+    pub(super) fn to_bytes(self) -> [u8; Self::BYTES_LEN] {
+        let shrink = self.shrink_every_n_turns.to_le_bytes();
+        [
+            self.food_spawn_chance,
+            self.minimum_food,
+            shrink[0],
+            shrink[1],
+        ]
+    }
+
+    /// the settings `to_bytes` wrote, or `None` for a spawn chance `to_bytes` never writes
+    pub(super) fn from_bytes(bytes: [u8; Self::BYTES_LEN]) -> Option<Self> {
+        if bytes[0] > 100 {
+            return None;
+        }
+
+        Some(Self {
+            food_spawn_chance: bytes[0],
+            minimum_food: bytes[1],
+            shrink_every_n_turns: u16::from_le_bytes([bytes[2], bytes[3]]),
+        })
+    }
+}
@@ -0,0 +1,96 @@
+use crate::compact_representation::{core::dimensions::Dimensions, CellNum};
+
+use super::{CellBoard, CellIndex};
+
+/// When the hazard pit of the sinkholes map grows. The pit starts as a single hazard on
+/// `start_turn` and every `grow_every_n_turns` turns after that another ring is laid over the
+/// whole pit, up to `max_rings` rings. Every ring adds a hazard to every cell it covers, so the
+/// middle of the pit stacks the most damage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SinkholeSchedule {
+    /// the turn the first hazard appears on
+    pub start_turn: u16,
+    /// how many turns pass between rings
+    pub grow_every_n_turns: u16,
+    /// the pit stops growing after this many rings
+    pub max_rings: u8,
+}
+
+impl Default for SinkholeSchedule {
+    fn default() -> Self {
+        Self {
+            start_turn: 1,
+            grow_every_n_turns: 10,
+            max_rings: 5,
+        }
+    }
+}
+
+impl SinkholeSchedule {
+    /// the schedule the sinkholes map uses for a board this wide, the pit has fewer rings on
+    /// small boards and more on large ones
+    pub fn for_width(width: u8) -> Self {
+        let max_rings = match width {
+            7 => 3,
+            19 => 7,
+            _ => 5,
+        };
+        Self {
+            max_rings,
+            ..Self::default()
+        }
+    }
+
+    /// how many rings have been laid by `turn`, not counting the first hazard
+    fn rings_at(&self, turn: u16) -> u8 {
+        let grown = (turn - self.start_turn) / self.grow_every_n_turns.max(1);
+        grown.min(self.max_rings as u16) as u8
+    }
+}
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// The hazards of a sinkhole pit around `center` on `turn`, as each hazard cell with how
+    /// many hazards are stacked on it, in index order. This only depends on the board size, not
+    /// on the hazards already on the board, so it can forecast the pit for any turn.
+    ///
+    /// Ring `n` covers the square of cells within `n` of `center` except its four corners,
+    /// which rounds the pit off. Cells off the board are left out, and nothing is returned
+    /// before `schedule.start_turn`
+    pub fn hazard_sinkhole(
+        &self,
+        center: CellIndex<T>,
+        turn: u16,
+        schedule: &SinkholeSchedule,
+    ) -> Vec<(CellIndex<T>, u8)> {
+        if turn < schedule.start_turn {
+            return Vec::new();
+        }
+
+        let width = self.get_actual_width();
+        let height = self.get_actual_height();
+        let rings = schedule.rings_at(turn) as i32;
+        let center = center.into_position(width);
+        let mut hazards = Vec::new();
+        for y in (center.y - rings).max(0)..=(center.y + rings).min(height as i32 - 1) {
+            for x in (center.x - rings).max(0)..=(center.x + rings).min(width as i32 - 1) {
+                let (dx, dy) = ((x - center.x).abs(), (y - center.y).abs());
+                let distance = dx.max(dy);
+                // every ring from this cell's distance outwards covers it, except the ring it
+                // is a corner of
+                let mut stack = rings - distance.max(1) + 1;
+                if distance == 0 {
+                    stack += 1;
+                } else if dx == dy {
+                    stack -= 1;
+                }
+                if stack > 0 {
+                    hazards.push((CellIndex::from_xy(x as u8, y as u8, width), stack as u8));
+                }
+            }
+        }
+
+        hazards
+    }
+}
@@ -1,9 +1,9 @@
 use crate::{
-    compact_representation::{core::dimensions::Dimensions, CellNum},
+    compact_representation::{core::dimensions::Dimensions, CellIndex, CellNum},
     types::SizeDeterminableGame,
 };
 
-use super::CellBoard;
+use super::{CellBoard, EvaluateMode};
 
 impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     SizeDeterminableGame for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
@@ -16,3 +16,52 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
         self.get_actual_height() as u32
     }
 }
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// the number of cells on the playable board
+    pub fn area(&self) -> u32 {
+        self.get_actual_width() as u32 * self.get_actual_height() as u32
+    }
+
+    /// the cells on the edge of the board, in index order. Wrapped boards have no edge, so
+    /// nothing is yielded for them
+    pub fn perimeter_cells(&self, mode: EvaluateMode) -> impl Iterator<Item = CellIndex<T>> {
+        let width = self.get_actual_width();
+        let height = self.get_actual_height();
+        let area = if mode != EvaluateMode::Wrapped {
+            self.area() as usize
+        } else {
+            0
+        };
+
+        (0..area)
+            .map(CellIndex::<T>::from_usize)
+            .filter(move |idx| {
+                let pos = idx.into_position(width);
+                pos.x == 0 || pos.y == 0 || pos.x == width as i32 - 1 || pos.y == height as i32 - 1
+            })
+    }
+
+    /// How many steps each cell is from the nearest edge of the board, so edge cells are 0 and
+    /// the center of an 11x11 board is 5. Chebyshev and manhattan distance agree here since the
+    /// nearest edge is always straight along one axis. This only depends on the board size, so
+    /// it can be computed once per game. Wrapped boards have no edge, so every playable cell is
+    /// `u8::MAX` for them. Cells outside the playable board are 0
+    pub fn wall_distance_map(&self, mode: EvaluateMode) -> [u8; BOARD_SIZE] {
+        let width = self.get_actual_width();
+        let height = self.get_actual_height();
+        let mut distances = [0; BOARD_SIZE];
+        for (idx, distance) in distances.iter_mut().enumerate().take(self.area() as usize) {
+            let (x, y) = CellIndex::<T>::from_usize(idx).to_xy(width);
+            *distance = if mode != EvaluateMode::Wrapped {
+                x.min(y).min(width - 1 - x).min(height - 1 - y)
+            } else {
+                u8::MAX
+            };
+        }
+
+        distances
+    }
+}
@@ -9,8 +9,7 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
     SnakeBodyGettableGame for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
 {
     fn get_snake_body_vec(&self, snake_id: &Self::SnakeIDType) -> Vec<Self::NativePositionType> {
-        let mut body = vec![];
-        body.reserve(self.get_length(*snake_id).into());
+        let mut body = Vec::with_capacity(self.get_length(*snake_id).into());
         let head = self.get_head_as_native_position(snake_id);
 
         let mut cur = Some(self.get_cell(head).get_tail_position(head).unwrap());
@@ -0,0 +1,223 @@
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::{Move, SnakeId},
+    wire_representation::Position,
+};
+
+use super::{Cell, CellBoard, CellIndex, EvaluateMode, HeadToHeadPolicy, Occupancy};
+
+/// A symmetry of a rectangular board, mapping every cell to another cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// leaves the board unchanged
+    Identity,
+    /// rotates the board a quarter turn counter clockwise, only valid for square boards
+    Rotate90,
+    /// rotates the board a half turn
+    Rotate180,
+    /// rotates the board three quarter turns counter clockwise, only valid for square boards
+    Rotate270,
+    /// mirrors the board left to right
+    FlipHorizontal,
+    /// mirrors the board top to bottom
+    FlipVertical,
+    /// mirrors the board along the `x = y` diagonal, only valid for square boards
+    FlipDiagonal,
+    /// mirrors the board along the other diagonal, only valid for square boards
+    FlipAntiDiagonal,
+}
+
+impl Symmetry {
+    /// all eight symmetries of a square board
+    pub fn all() -> [Symmetry; 8] {
+        [
+            Symmetry::Identity,
+            Symmetry::Rotate90,
+            Symmetry::Rotate180,
+            Symmetry::Rotate270,
+            Symmetry::FlipHorizontal,
+            Symmetry::FlipVertical,
+            Symmetry::FlipDiagonal,
+            Symmetry::FlipAntiDiagonal,
+        ]
+    }
+
+    /// whether this symmetry swaps the axes, and so only applies to square boards
+    pub fn requires_square(&self) -> bool {
+        matches!(
+            self,
+            Symmetry::Rotate90
+                | Symmetry::Rotate270
+                | Symmetry::FlipDiagonal
+                | Symmetry::FlipAntiDiagonal
+        )
+    }
+
+    /// maps a position on a board of the given size
+    pub fn apply(&self, pos: Position, width: u8, height: u8) -> Position {
+        let max_x = width as i32 - 1;
+        let max_y = height as i32 - 1;
+        let (x, y) = (pos.x, pos.y);
+        let (x, y) = match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (max_y - y, x),
+            Symmetry::Rotate180 => (max_x - x, max_y - y),
+            Symmetry::Rotate270 => (y, max_x - x),
+            Symmetry::FlipHorizontal => (max_x - x, y),
+            Symmetry::FlipVertical => (x, max_y - y),
+            Symmetry::FlipDiagonal => (y, x),
+            Symmetry::FlipAntiDiagonal => (max_y - y, max_x - x),
+        };
+
+        Position { x, y }
+    }
+}
+
+/// The symmetries that leave a particular board unchanged, see `symmetry_group`. Every board
+/// is invariant under `Symmetry::Identity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymmetryGroup {
+    /// bit `s as u8` is set for every symmetry `s` in the group
+    members: u8,
+}
+
+impl SymmetryGroup {
+    /// whether `symmetry` leaves the board unchanged
+    pub fn contains(&self, symmetry: Symmetry) -> bool {
+        self.members & (1 << symmetry as u8) != 0
+    }
+
+    /// the symmetries in the group, in `Symmetry::all` order
+    pub fn iter(&self) -> impl Iterator<Item = Symmetry> {
+        let group = *self;
+        IntoIterator::into_iter(Symmetry::all()).filter(move |s| group.contains(*s))
+    }
+
+    /// the number of symmetries in the group, between 1 and 8
+    pub fn order(&self) -> usize {
+        self.members.count_ones() as usize
+    }
+
+    /// whether only the identity leaves the board unchanged, in which case canonicalizing it
+    /// can't merge any of its successors
+    pub fn is_trivial(&self) -> bool {
+        self.order() == 1
+    }
+}
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// the symmetries that apply to this board's dimensions
+    pub fn valid_symmetries(&self) -> impl Iterator<Item = Symmetry> {
+        let square = self.get_actual_width() == self.get_actual_height();
+        IntoIterator::into_iter(Symmetry::all()).filter(move |s| square || !s.requires_square())
+    }
+
+    /// transforms every cell of the board, returns None if the symmetry doesn't apply to this
+    /// board's dimensions
+    pub fn apply_symmetry(&self, symmetry: Symmetry) -> Option<Self> {
+        let width = self.get_actual_width();
+        let height = self.get_actual_height();
+        if symmetry.requires_square() && width != height {
+            return None;
+        }
+        let map = |idx: CellIndex<T>| {
+            CellIndex::new(
+                symmetry.apply(idx.into_position(width), width, height),
+                width,
+            )
+        };
+
+        let mut new = *self;
+        new.cells = [Cell::empty(); BOARD_SIZE];
+        for i in 0..(width as usize * height as usize) {
+            let idx = CellIndex::from_usize(i);
+            let mut cell = self.get_cell(idx);
+            // every snake cell except a triple stack points at another cell
+            if (cell.is_body() || cell.is_head()) && !cell.is_triple_stacked_piece() {
+                cell.idx = map(cell.idx);
+            }
+            new.cells[map(idx).as_usize()] = cell;
+        }
+        new.occupancy = Occupancy::from_cells(&new.cells);
+        for (id, health) in self.healths.iter().enumerate() {
+            if *health > 0 {
+                new.heads[id] = map(self.heads[id]);
+            }
+        }
+
+        Some(new)
+    }
+
+    /// the symmetries that map this board on to itself, found by applying each valid symmetry
+    /// and comparing. Snakes must land on cells of the same `SnakeId`, so only boards whose
+    /// snakes are themselves symmetric have a group larger than the identity
+    pub fn symmetry_group(&self) -> SymmetryGroup {
+        let area = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        let unchanged = |board: &Self| {
+            board.cells[..area]
+                .iter()
+                .zip(self.cells[..area].iter())
+                .all(|(a, b)| a.pack_as_u32() == b.pack_as_u32())
+        };
+        let members = self
+            .valid_symmetries()
+            .filter(|s| {
+                self.apply_symmetry(*s)
+                    .is_some_and(|board| unchanged(&board))
+            })
+            .fold(0, |members, s| members | (1 << s as u8));
+
+        SymmetryGroup { members }
+    }
+
+    /// the representative of this board among all its symmetric images. Two boards that are
+    /// rotations or reflections of each other have the same canonical form
+    pub fn canonical_form(&self) -> Self {
+        let width = self.get_actual_width() as usize;
+        let height = self.get_actual_height() as usize;
+        let key = |board: &Self| {
+            board.cells[..width * height]
+                .iter()
+                .map(|c| c.pack_as_u32())
+                .collect::<Vec<_>>()
+        };
+
+        self.valid_symmetries()
+            .filter_map(|s| self.apply_symmetry(s))
+            .min_by_key(key)
+            .unwrap_or(*self)
+    }
+
+    /// the moves for `me` that lead to distinct positions, when every other snake holds still.
+    /// Moves whose resulting boards have the same canonical form are equivalent, and only the
+    /// first in `Move::all` order is kept. Moves that are certain death are omitted
+    pub fn dedup_symmetric_moves(&self, me: SnakeId, mode: EvaluateMode) -> Vec<Move> {
+        let all_moves = [(me, Move::all())];
+        let states = self.generate_state(all_moves.iter(), mode);
+
+        let mut seen = Vec::with_capacity(4);
+        let mut moves = Vec::with_capacity(4);
+        for mv in Move::all() {
+            if states[me.as_usize()][mv.as_index()].is_dead() {
+                continue;
+            }
+            let next = self
+                .evaluate_moves_with_state(
+                    [(me, mv)].iter(),
+                    &states,
+                    HeadToHeadPolicy::Standard,
+                    mode,
+                )
+                .canonical_form();
+            let next = (next.cells, next.healths, next.lengths);
+            if !seen.contains(&next) {
+                seen.push(next);
+                moves.push(mv);
+            }
+        }
+
+        moves
+    }
+}
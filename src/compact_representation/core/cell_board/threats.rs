@@ -0,0 +1,337 @@
+use itertools::Itertools;
+
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::{Move, SnakeId},
+};
+
+use super::{CellBoard, CellIndex, EvaluateMode};
+
+/// Weights for the factors combined by `danger_score`. Every factor is normalized to be between
+/// 0 and 1 before it is weighted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DangerWeights {
+    /// weight for the fraction of your 4 moves that are immediately fatal
+    pub fatal_moves: f32,
+    /// weight for being trapped, 1 if you own fewer cells than your length and 0 otherwise
+    pub trapped: f32,
+    /// weight for how many fewer cells you own than the best opponent, as a fraction of the board
+    pub area_deficit: f32,
+    /// weight for the fraction of your moves that an enemy at least as long as you can also reach
+    pub longer_enemy_heads: f32,
+}
+
+impl Default for DangerWeights {
+    fn default() -> Self {
+        Self {
+            fatal_moves: 1.0,
+            trapped: 1.0,
+            area_deficit: 1.0,
+            longer_enemy_heads: 1.0,
+        }
+    }
+}
+
+/// Why a move was or wasn't safe, as reported by `move_analysis`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveLegality {
+    /// nothing is certain to kill the snake on this move
+    Legal,
+    /// the move leaves the board
+    OffBoard,
+    /// the move runs in to the snake's own body, including its neck
+    SelfCollision,
+    /// the move runs in to a cell of this other snake that won't be vacated next turn
+    BodyCollision(SnakeId),
+    /// this snake is at least as long and could move its head to the same cell, so the head to
+    /// head collision would be lost or tied
+    LosingHeadToHead(SnakeId),
+}
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// Why each of `me`'s moves is or isn't safe, in `Move::all` order. Leaving the board
+    /// is checked first, then running in to a body that won't be vacated, then head to head
+    /// collisions with an enemy at least as long as `me`. When several enemies threaten the
+    /// same cell the lowest `SnakeId` is reported. Dead snakes have every move reported as
+    /// `OffBoard`
+    pub fn move_analysis(&self, me: SnakeId, mode: EvaluateMode) -> [(Move, MoveLegality); 4] {
+        let mut analysis = Move::all().map(|mv| (mv, MoveLegality::OffBoard));
+        if self.healths[me.as_usize()] == 0 {
+            return analysis;
+        }
+
+        let my_length = self.lengths[me.as_usize()];
+        for (mv, target) in self.neighbor_cells(self.heads[me.as_usize()], mode) {
+            let cell = self.get_cell(target);
+            let legality = match cell.get_snake_id() {
+                Some(id) if !self.will_be_free_next_turn(target) => {
+                    if id == me {
+                        MoveLegality::SelfCollision
+                    } else {
+                        MoveLegality::BodyCollision(id)
+                    }
+                }
+                _ => self
+                    .alive_heads()
+                    .find(|(id, head)| {
+                        *id != me
+                            && self.lengths[id.as_usize()] >= my_length
+                            && self
+                                .neighbor_cells(*head, mode)
+                                .any(|(_, cell)| cell == target)
+                    })
+                    .map_or(MoveLegality::Legal, |(id, _)| {
+                        MoveLegality::LosingHeadToHead(id)
+                    }),
+            };
+            analysis[mv.as_index()].1 = legality;
+        }
+
+        analysis
+    }
+
+    /// For every cell `me` can move in to, lists each living enemy whose head could also move
+    /// there next turn. Results are ordered by `Move::all` for `me`, then by enemy `SnakeId`.
+    /// A cell appears once per enemy that contests it
+    pub fn contested_cells(&self, me: SnakeId, mode: EvaluateMode) -> Vec<(CellIndex<T>, SnakeId)> {
+        let mut contested = Vec::new();
+        if self.healths[me.as_usize()] == 0 {
+            return contested;
+        }
+
+        for (_, cell) in self.neighbor_cells(self.heads[me.as_usize()], mode) {
+            for (id, health) in self.healths.iter().enumerate() {
+                if id == me.as_usize() || *health == 0 {
+                    continue;
+                }
+
+                if self
+                    .neighbor_cells(self.heads[id], mode)
+                    .any(|(_, enemy_cell)| enemy_cell == cell)
+                {
+                    contested.push((cell, SnakeId(id as u8)));
+                }
+            }
+        }
+
+        contested
+    }
+
+    /// Every living snake whose head could move in to `idx` next turn, ordered by `SnakeId`.
+    /// The `contested_cells` check for any cell, not only cells next to one snake's head
+    pub fn threats_to(&self, idx: CellIndex<T>, mode: EvaluateMode) -> Vec<SnakeId> {
+        self.alive_heads()
+            .filter(|(_, head)| {
+                self.neighbor_cells(*head, mode)
+                    .any(|(_, neighbor)| neighbor == idx)
+            })
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Whether `me` would survive a head to head collision at `idx`: `Some(true)` when `me` is
+    /// strictly longer than every enemy in `threats_to`, `Some(false)` when any of them is at
+    /// least as long and `None` when no enemy can reach `idx`. Doesn't check whether `me` can
+    /// reach `idx` itself
+    pub fn wins_head_to_head_at(
+        &self,
+        me: SnakeId,
+        idx: CellIndex<T>,
+        mode: EvaluateMode,
+    ) -> Option<bool> {
+        let my_length = self.lengths[me.as_usize()];
+        let enemies = self
+            .threats_to(idx, mode)
+            .into_iter()
+            .filter(|id| *id != me)
+            .collect_vec();
+        if enemies.is_empty() {
+            return None;
+        }
+
+        Some(
+            enemies
+                .iter()
+                .all(|id| self.lengths[id.as_usize()] < my_length),
+        )
+    }
+
+    /// Whether `me` is caught in a pincer: none of its moves leads to a cell that is free next
+    /// turn and contested by fewer than two enemies. This is also true when `me` has no free
+    /// move at all. Lengths aren't compared, a single longer enemy on the only escape doesn't
+    /// count. Always false for dead snakes
+    pub fn is_double_threatened(&self, me: SnakeId, mode: EvaluateMode) -> bool {
+        if self.healths[me.as_usize()] == 0 {
+            return false;
+        }
+
+        let contested = self.contested_cells(me, mode);
+        !self
+            .neighbor_cells(self.heads[me.as_usize()], mode)
+            .any(|(_, cell)| {
+                self.will_be_free_next_turn(cell)
+                    && contested.iter().filter(|(c, _)| *c == cell).count() < 2
+            })
+    }
+
+    /// Every cell where `me`'s head would have no safe move, in index order. A move is safe when
+    /// its cell is free next turn and either holds food or doesn't have enough hazards to kill
+    /// `me` at its current health. Only cells without a snake are candidates, and the other
+    /// bodies are taken as they are now, so this is a snapshot of the board rather than a
+    /// forecast. It checks the neighbors of every cell on the board, so compute it once per turn
+    /// to find danger zones rather than calling it inside a search. Empty for dead snakes
+    pub fn death_cells(&self, me: SnakeId, mode: EvaluateMode) -> Vec<CellIndex<T>> {
+        let health = self.healths[me.as_usize()];
+        if health == 0 {
+            return Vec::new();
+        }
+
+        let is_safe = |idx: CellIndex<T>| {
+            self.will_be_free_next_turn(idx)
+                && (self.get_cell(idx).is_food()
+                    || health
+                        .saturating_sub(1)
+                        .saturating_sub(self.hazard_damage_at(idx))
+                        > 0)
+        };
+        let area = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        (0..area)
+            .map(CellIndex::from_usize)
+            .filter(|idx| {
+                let cell = self.get_cell(*idx);
+                !(cell.is_body() || cell.is_head())
+            })
+            .filter(|idx| {
+                !self
+                    .neighbor_cells(*idx, mode)
+                    .any(|(_, neighbor)| is_safe(neighbor))
+            })
+            .collect()
+    }
+
+    /// the manhattan distance between two cells. In wrapped mode each axis takes the shorter way
+    /// around the board
+    pub fn manhattan_distance(&self, a: CellIndex<T>, b: CellIndex<T>, mode: EvaluateMode) -> u32 {
+        let width = self.get_actual_width();
+        let (a, b) = (a.into_position(width), b.into_position(width));
+        let dx = (a.x - b.x).unsigned_abs();
+        let dy = (a.y - b.y).unsigned_abs();
+        match mode {
+            EvaluateMode::Standard | EvaluateMode::Solo => dx + dy,
+            EvaluateMode::Wrapped => {
+                dx.min(width as u32 - dx) + dy.min(self.get_actual_height() as u32 - dy)
+            }
+        }
+    }
+
+    /// the living enemy whose head is closest to `me`'s head by `manhattan_distance`, along with
+    /// that distance. Ties go to the lowest id. `None` if `me` is dead or has no living enemies
+    pub fn nearest_enemy_head(&self, me: SnakeId, mode: EvaluateMode) -> Option<(SnakeId, u32)> {
+        if self.healths[me.as_usize()] == 0 {
+            return None;
+        }
+
+        let my_head = self.heads[me.as_usize()];
+        self.alive_heads()
+            .filter(|(id, _)| *id != me)
+            .map(|(id, head)| (id, self.manhattan_distance(my_head, head, mode)))
+            .min_by_key(|(id, distance)| (*distance, id.0))
+    }
+
+    /// Whether `a` and `b` can possibly collide next turn: some pair of their moves puts both
+    /// heads on the same cell, or one of them can move in to a cell of the other that won't be
+    /// vacated. Always false if either snake is dead or they are the same snake
+    pub fn can_collide_next_turn(&self, a: SnakeId, b: SnakeId, mode: EvaluateMode) -> bool {
+        if a == b || self.healths[a.as_usize()] == 0 || self.healths[b.as_usize()] == 0 {
+            return false;
+        }
+
+        let into_body = |mover: SnakeId, other: SnakeId| {
+            self.neighbor_cells(self.heads[mover.as_usize()], mode)
+                .any(|(_, cell_idx)| {
+                    let cell = self.get_cell(cell_idx);
+                    (cell.is_body() || cell.is_head())
+                        && cell.get_snake_id() == Some(other)
+                        && !self.cell_is_single_tail(cell_idx)
+                })
+        };
+        let head_to_head =
+            self.neighbor_cells(self.heads[a.as_usize()], mode)
+                .any(|(_, a_cell)| {
+                    self.neighbor_cells(self.heads[b.as_usize()], mode)
+                        .any(|(_, b_cell)| a_cell == b_cell)
+                });
+
+        head_to_head || into_body(a, b) || into_body(b, a)
+    }
+
+    /// The cells no snake occupies and no living head could move in to next turn, the whole
+    /// board version of `contested_cells`. Food and hazards are included, the cells are in
+    /// increasing index order
+    pub fn globally_safe_cells(&self, mode: EvaluateMode) -> Vec<CellIndex<T>> {
+        let mut claimable = [false; BOARD_SIZE];
+        for (_, head) in self.alive_heads() {
+            for (_, cell) in self.neighbor_cells(head, mode) {
+                claimable[cell.as_usize()] = true;
+            }
+        }
+
+        let area = self.get_actual_width() as usize * self.get_actual_height() as usize;
+        self.cells[..area]
+            .iter()
+            .enumerate()
+            .filter(|(idx, cell)| !claimable[*idx] && !cell.is_body() && !cell.is_head())
+            .map(|(idx, _)| CellIndex::from_usize(idx))
+            .collect()
+    }
+
+    /// a weighted sum of normalized danger factors for `me`, see `DangerWeights`
+    pub fn danger_score(&self, me: SnakeId, weights: &DangerWeights, mode: EvaluateMode) -> f32 {
+        if self.healths[me.as_usize()] == 0 {
+            return weights.fatal_moves
+                + weights.trapped
+                + weights.area_deficit
+                + weights.longer_enemy_heads;
+        }
+
+        let safe_cells = self
+            .neighbor_cells(self.heads[me.as_usize()], mode)
+            .filter(|(_, cell)| self.will_be_free_next_turn(*cell))
+            .count();
+        let fatal_moves = (4 - safe_cells) as f32 / 4.0;
+
+        let areas = self.reachable_all(mode);
+        let my_area = areas[me.as_usize()];
+        let trapped = if my_area < self.lengths[me.as_usize()] {
+            1.0
+        } else {
+            0.0
+        };
+        let best_opponent_area = areas
+            .iter()
+            .enumerate()
+            .filter(|(id, _)| *id != me.as_usize())
+            .map(|(_, area)| *area)
+            .max()
+            .unwrap_or(0);
+        let total_cells = self.get_actual_width() as f32 * self.get_actual_height() as f32;
+        let area_deficit = best_opponent_area.saturating_sub(my_area) as f32 / total_cells;
+
+        let my_length = self.lengths[me.as_usize()];
+        let contested_by_longer = self
+            .contested_cells(me, mode)
+            .into_iter()
+            .filter(|(_, enemy)| self.lengths[enemy.as_usize()] >= my_length)
+            .map(|(cell, _)| cell)
+            .unique()
+            .count();
+        let longer_enemy_heads = contested_by_longer as f32 / 4.0;
+
+        weights.fatal_moves * fatal_moves
+            + weights.trapped * trapped
+            + weights.area_deficit * area_deficit
+            + weights.longer_enemy_heads * longer_enemy_heads
+    }
+}
@@ -5,13 +5,16 @@ use crate::{
     types::{SnakeId, VictorDeterminableGame},
 };
 
-use super::CellBoard;
+use super::{CellBoard, GameMode};
 
 impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     VictorDeterminableGame for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
 {
     fn is_over(&self) -> bool {
-        self.healths[0] == 0 || self.healths.iter().filter(|h| **h != 0).count() <= 1
+        // a solo game isn't won by being the last snake standing, it only ends when the snake dies
+        self.healths[self.you.as_usize()] == 0
+            || (self.game_mode != Some(GameMode::Solo)
+                && self.healths.iter().filter(|h| **h != 0).count() <= 1)
     }
 
     fn get_winner(&self) -> Option<Self::SnakeIDType> {
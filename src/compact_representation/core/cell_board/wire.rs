@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+
+use serde_json::json;
+
+use crate::{
+    compact_representation::{core::dimensions::Dimensions, CellNum},
+    types::{wire_id_for, SnakeBodyGettableGame, SnakeIDMap, SnakeId},
+    wire_representation::{BattleSnake, Board, Game, NestedGame, Position, Ruleset, Settings},
+};
+
+use super::{CellBoard, CellIndex, ConversionError, EvaluateMode, Occupancy};
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+    CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    /// Builds the wire game for this board, the inverse of `convert_from_game`. Snakes get their
+    /// wire ids from `snake_ids`, falling back to `snake_<id>` for ids missing from the map. The
+    /// board doesn't keep the game id or timeout, so those are empty and 500. The ruleset name
+    /// comes from the `GameMode` when it is known and from `mode` otherwise
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_wire_game(&self, snake_ids: &SnakeIDMap, mode: EvaluateMode) -> Game {
+        let width = self.get_actual_width();
+        let area = width as usize * self.get_actual_height() as usize;
+        let cells_where = |keep: fn(&Self, CellIndex<T>) -> bool| {
+            (0..area)
+                .map(CellIndex::from_usize)
+                .filter(|idx| keep(self, *idx))
+                .map(|idx| idx.into_position(width))
+                .collect::<Vec<_>>()
+        };
+
+        let snake = |id: SnakeId| {
+            let wire_id = wire_id_for(snake_ids, id)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("snake_{}", id.0));
+            let body: VecDeque<Position> = if self.healths[id.as_usize()] > 0 {
+                self.get_snake_body_vec(&id)
+                    .into_iter()
+                    .map(|idx| idx.into_position(width))
+                    .collect()
+            } else {
+                VecDeque::new()
+            };
+            BattleSnake {
+                name: wire_id.clone(),
+                id: wire_id,
+                head: body.front().copied().unwrap_or(Position::new(0, 0)),
+                body,
+                health: self.healths[id.as_usize()] as i32,
+                shout: None,
+                actual_length: None,
+            }
+        };
+
+        let ruleset_settings = self.ruleset_settings.unwrap_or_default();
+        let name = self
+            .game_mode
+            .and_then(|game_mode| game_mode.ruleset_name())
+            .unwrap_or(match mode {
+                EvaluateMode::Wrapped => "wrapped",
+                EvaluateMode::Standard => "standard",
+                EvaluateMode::Solo => "solo",
+            });
+
+        Game {
+            you: snake(self.you),
+            board: Board {
+                height: self.get_actual_height() as u32,
+                width: width as u32,
+                food: cells_where(|board, idx| board.get_cell(idx).is_food()),
+                snakes: (0..MAX_SNAKES)
+                    .map(|id| SnakeId(id as u8))
+                    .filter(|id| self.healths[id.as_usize()] > 0)
+                    .map(snake)
+                    .collect(),
+                hazards: (0..area)
+                    .map(CellIndex::<T>::from_usize)
+                    .flat_map(|idx| {
+                        let stack = self.get_cell(idx).hazard_stack() as usize;
+                        std::iter::repeat_n(idx.into_position(width), stack)
+                    })
+                    .collect(),
+            },
+            turn: self.turn as i32,
+            game: NestedGame {
+                id: String::new(),
+                ruleset: Ruleset {
+                    name: name.to_string(),
+                    version: String::new(),
+                    settings: Some(Settings {
+                        food_spawn_chance: ruleset_settings.food_spawn_chance as i32,
+                        minimum_food: ruleset_settings.minimum_food as i32,
+                        hazard_damage_per_turn: self.hazard_damage as i32,
+                        hazard_map: None,
+                        hazard_map_author: None,
+                        royale: Some(ruleset_settings.royale()),
+                    }),
+                },
+                timeout: 500,
+                map: None,
+                source: None,
+            },
+        }
+    }
+
+    /// Renumbers the snakes of a board converted with `old` to the ids `new` gives their wire
+    /// ids, so boards from the same game converted with different maps can be compared. Snakes
+    /// whose wire id isn't in both maps keep their slot when they are dead. A living snake
+    /// missing from either map is a `ConversionError::UnknownSnakeId`, an id in `new` past
+    /// `MAX_SNAKES` is `TooManySnakes` and two snakes mapped to the same id is
+    /// `DuplicateSnakeId`
+    pub fn remap_snake_ids(
+        &self,
+        old: &SnakeIDMap,
+        new: &SnakeIDMap,
+    ) -> Result<Self, ConversionError> {
+        let mut remapped = [None; MAX_SNAKES];
+        for (wire_id, old_id) in old.iter() {
+            if old_id.as_usize() >= MAX_SNAKES {
+                continue;
+            }
+            if let Some(new_id) = new.get(wire_id) {
+                if new_id.as_usize() >= MAX_SNAKES {
+                    return Err(ConversionError::TooManySnakes {
+                        count: new_id.as_usize() + 1,
+                    });
+                }
+                remapped[old_id.as_usize()] = Some(*new_id);
+            }
+        }
+
+        let mut board = *self;
+        board.healths = [0; MAX_SNAKES];
+        board.lengths = [0; MAX_SNAKES];
+        let mut taken = [false; MAX_SNAKES];
+        for old_id in (0..MAX_SNAKES).map(|id| SnakeId(id as u8)) {
+            if self.healths[old_id.as_usize()] == 0 {
+                continue;
+            }
+            let wire_id = || {
+                wire_id_for(old, old_id)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("snake_{}", old_id.0))
+            };
+            let new_id = remapped[old_id.as_usize()]
+                .ok_or_else(|| ConversionError::UnknownSnakeId { id: wire_id() })?;
+            if std::mem::replace(&mut taken[new_id.as_usize()], true) {
+                return Err(ConversionError::DuplicateSnakeId { id: wire_id() });
+            }
+            board.healths[new_id.as_usize()] = self.healths[old_id.as_usize()];
+            board.lengths[new_id.as_usize()] = self.lengths[old_id.as_usize()];
+            board.heads[new_id.as_usize()] = self.heads[old_id.as_usize()];
+        }
+        for cell in board.cells.iter_mut() {
+            if let Some(old_id) = cell.get_snake_id() {
+                cell.set_snake_id(remapped[old_id.as_usize()].unwrap_or(old_id));
+            }
+        }
+        board.you = remapped[self.you.as_usize()].unwrap_or(self.you);
+        board.occupancy = Occupancy::from_cells(&board.cells);
+
+        Ok(board)
+    }
+
+    /// This board as a single frame in the game engine's frame format, which is what the board
+    /// viewer replays. Unlike the request schema of `to_wire_game` keys are capitalized, points
+    /// are `{"X", "Y"}` and snakes carry the display fields the viewer reads, left empty here
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_board_viewer_json(&self, snake_ids: &SnakeIDMap, mode: EvaluateMode) -> String {
+        let game = self.to_wire_game(snake_ids, mode);
+        let points = |positions: &mut dyn Iterator<Item = &Position>| {
+            positions
+                .map(|p| json!({ "X": p.x, "Y": p.y }))
+                .collect::<Vec<_>>()
+        };
+        let snakes = game
+            .board
+            .snakes
+            .iter()
+            .map(|snake| {
+                json!({
+                    "ID": snake.id,
+                    "Name": snake.name,
+                    "Body": points(&mut snake.body.iter()),
+                    "Health": snake.health,
+                    "Death": null,
+                    "Color": "",
+                    "HeadType": "",
+                    "TailType": "",
+                    "Latency": "0",
+                    "Shout": "",
+                    "Squad": "",
+                    "Author": "",
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "Turn": game.turn,
+            "Snakes": snakes,
+            "Food": points(&mut game.board.food.iter()),
+            "Hazards": points(&mut game.board.hazards.iter()),
+        })
+        .to_string()
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     compact_representation::{core::dimensions::Dimensions, CellNum},
-    types::{SnakeId, YouDeterminableGame},
+    types::YouDeterminableGame,
 };
 
 use super::CellBoard;
@@ -9,10 +9,10 @@ impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize
     YouDeterminableGame for CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
 {
     fn is_you(&self, snake_id: &Self::SnakeIDType) -> bool {
-        snake_id.0 == 0
+        *snake_id == self.you
     }
 
     fn you_id(&self) -> &Self::SnakeIDType {
-        &SnakeId(0)
+        &self.you
     }
 }
@@ -10,9 +10,18 @@ pub trait CellNum:
     fn from_i32(i: i32) -> Self;
     /// makes a cellnum from an usize
     fn from_usize(i: usize) -> Self;
+
+    /// the words of a `BitBoard` of cells indexed by this type, enough for the largest board
+    /// that uses it
+    #[cfg(feature = "bitboard")]
+    type BitBoardWords: super::cell_board::BitBoardWords;
 }
 
 impl CellNum for u8 {
+    /// a u8 indexes at most 256 cells
+    #[cfg(feature = "bitboard")]
+    type BitBoardWords = [u64; 4];
+
     fn as_usize(&self) -> usize {
         *self as usize
     }
@@ -26,6 +35,10 @@ impl CellNum for u8 {
     }
 }
 impl CellNum for u16 {
+    /// enough for the 50x50 boards
+    #[cfg(feature = "bitboard")]
+    type BitBoardWords = [u64; 40];
+
     fn as_usize(&self) -> usize {
         *self as usize
     }
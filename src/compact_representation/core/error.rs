@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use crate::types::SnakeId;
+
+/// Errors that can occur when building a compact board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The game is larger than the board can hold, or has a shape its dimensions can't
+    /// describe, like a non square game for a `Square` board
+    BoardTooLarge {
+        /// the width of the game
+        width: u32,
+        /// the height of the game
+        height: u32,
+    },
+    /// The game has more snakes than the board can hold
+    TooManySnakes {
+        /// how many snakes the game needs room for
+        count: usize,
+    },
+    /// The rows of a grid don't all have the same length
+    RaggedGrid,
+    /// A snake's body is not a valid chain of segments
+    MalformedSnake {
+        /// the snake with the bad body
+        id: SnakeId,
+    },
+    /// More than one snake in the game has this id
+    DuplicateSnakeId {
+        /// the repeated wire id
+        id: String,
+    },
+    /// A snake in the game has no entry in the snake id map
+    UnknownSnakeId {
+        /// the wire id that is missing from the map
+        id: String,
+    },
+    /// A snake has 3 segments on one square while also occupying other squares
+    BadBodyStack {
+        /// the wire id of the snake with the bad stack
+        id: String,
+    },
+    /// A snake's head is not the first segment of its body, or its body is empty
+    HeadBodyMismatch {
+        /// the wire id of the snake
+        id: String,
+    },
+    /// Part of a snake's body is outside of the board
+    BodyOffBoard {
+        /// the wire id of the snake
+        id: String,
+    },
+    /// Two living snakes have a body segment on the same square
+    OverlappingBodies {
+        /// the wire id of the snake listed first in the game
+        a: String,
+        /// the wire id of the snake it overlaps
+        b: String,
+    },
+    /// Bytes or a cache key that weren't written by `to_bytes` or `cache_key`
+    MalformedBytes,
+    /// The game's ruleset can't be simulated by this kind of board, like a wrapped game
+    /// converted to a standard board
+    UnsupportedRuleset {
+        /// the ruleset name of the game
+        name: String,
+    },
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::BoardTooLarge { width, height } => write!(
+                f,
+                "game size {}x{} doesn't fit in the given board size",
+                width, height
+            ),
+            ConversionError::TooManySnakes { count } => {
+                write!(f, "too many snakes: {} don't fit in the board", count)
+            }
+            ConversionError::RaggedGrid => write!(f, "grid rows have different lengths"),
+            ConversionError::MalformedSnake { id } => {
+                write!(f, "snake {} has a malformed body", id.0)
+            }
+            ConversionError::DuplicateSnakeId { id } => {
+                write!(f, "snake id {} is used by more than one snake", id)
+            }
+            ConversionError::UnknownSnakeId { id } => {
+                write!(f, "snake {} is missing from the snake id map", id)
+            }
+            ConversionError::BadBodyStack { id } => write!(
+                f,
+                "snake {} has a bad body stack (3 segs on same square and more than one unique position)",
+                id
+            ),
+            ConversionError::HeadBodyMismatch { id } => {
+                write!(f, "snake {} has a head that isn't the start of its body", id)
+            }
+            ConversionError::BodyOffBoard { id } => {
+                write!(f, "snake {} has a body segment off the board", id)
+            }
+            ConversionError::OverlappingBodies { a, b } => {
+                write!(f, "snakes {} and {} have overlapping bodies", a, b)
+            }
+            ConversionError::MalformedBytes => write!(f, "bytes don't encode a valid board"),
+            ConversionError::UnsupportedRuleset { name } => {
+                write!(f, "{} games are not supported by this board", name)
+            }
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+/// Where a simulated game first stopped matching a log of the official game, as reported by
+/// `conformance_check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConformanceError {
+    /// A frame of the log couldn't be converted to a board
+    Conversion {
+        /// the turn of the frame
+        turn: i32,
+        /// why it couldn't be converted
+        error: ConversionError,
+    },
+    /// There are no moves for the turn after this frame
+    MissingMoves {
+        /// the turn of the frame without moves
+        turn: i32,
+    },
+    /// Simulating the logged moves gave a different board than the next frame
+    Diverged {
+        /// the turn of the frame the simulation disagrees with
+        turn: i32,
+        /// every difference between the simulated board and the frame, one per line
+        differences: Vec<String>,
+    },
+}
+
+impl Display for ConformanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConformanceError::Conversion { turn, error } => {
+                write!(
+                    f,
+                    "the frame for turn {} can't be converted: {}",
+                    turn, error
+                )
+            }
+            ConformanceError::MissingMoves { turn } => {
+                write!(f, "there are no moves after turn {}", turn)
+            }
+            ConformanceError::Diverged { turn, differences } => write!(
+                f,
+                "the simulation diverged on turn {}:\n{}",
+                turn,
+                differences.join("\n")
+            ),
+        }
+    }
+}
+
+impl Error for ConformanceError {}
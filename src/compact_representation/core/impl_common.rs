@@ -2,6 +2,664 @@
 #[macro_export]
 macro_rules! impl_common_board_traits {
     ($type:tt) => {
+        impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+            $type<T, D, BOARD_SIZE, MAX_SNAKES>
+        {
+            /// Converts a game like `convert_from_game`, building the snake id map and returning
+            /// it alongside the board. Keep the map to turn the `SnakeId`s the board uses back in
+            /// to the wire ids the server expects
+            pub fn convert_with_map(
+                game: Game,
+            ) -> Result<
+                (Self, $crate::types::SnakeIDMap),
+                $crate::compact_representation::ConversionError,
+            > {
+                let id_map = $crate::types::build_snake_id_map(&game);
+                let board = $type::convert_from_game(game, &id_map)?;
+                Ok((board, id_map))
+            }
+
+            /// Turns this board back in to a wire game, using `snake_ids` for the wire ids. The
+            /// board doesn't keep the game id or timeout, so those are filled with placeholders.
+            /// Handy for handing a simulated position to code that takes wire games
+            #[allow(clippy::wrong_self_convention)]
+            pub fn to_wire_game(&self, snake_ids: &$crate::types::SnakeIDMap) -> Game {
+                self.embedded.to_wire_game(snake_ids, self.evaluate_mode())
+            }
+
+            /// Renumbers the snakes of a board converted with `old` to the ids `new` assigns their
+            /// wire ids. Use this when a snake id map was rebuilt mid game, so that boards from
+            /// different turns agree on which snake is which
+            pub fn remap_snake_ids(
+                &self,
+                old: &$crate::types::SnakeIDMap,
+                new: &$crate::types::SnakeIDMap,
+            ) -> Result<Self, $crate::compact_representation::ConversionError> {
+                Ok(Self {
+                    embedded: self.embedded.remap_snake_ids(old, new)?,
+                })
+            }
+
+            /// This board as JSON for the board viewer, a single frame in the game engine's
+            /// format rather than the request schema `to_wire_game` produces. Paste it in to the
+            /// viewer to eyeball a position without hand editing JSON
+            #[allow(clippy::wrong_self_convention)]
+            pub fn to_board_viewer_json(&self, snake_ids: &$crate::types::SnakeIDMap) -> String {
+                self.embedded
+                    .to_board_viewer_json(snake_ids, self.evaluate_mode())
+            }
+
+            /// the fraction of the board that is occupied by snakes, between 0 and 1. Food and
+            /// hazards do not count towards fullness, as hazards can still be moved through
+            pub fn board_fullness(&self) -> f32 {
+                self.embedded.board_fullness()
+            }
+
+            /// Counts, for every snake, the cells it can reach strictly before all other snakes
+            /// (a voronoi partition computed with a single multi-source flood fill). Bodies and
+            /// heads block the fill, and cells reached by multiple snakes on the same turn are
+            /// excluded from every count. Indexed by `SnakeId`, dead snakes have a count of 0
+            pub fn reachable_all(&self) -> [u16; MAX_SNAKES] {
+                self.embedded.reachable_all(self.evaluate_mode())
+            }
+
+            /// How many moves this snake's head needs to reach every cell, indexed by cell.
+            /// Every snake's head and body is a wall, so those cells and anything walled off
+            /// are `None`. A dead snake reaches nothing
+            pub fn distance_field(&self, id: &SnakeId) -> [Option<u16>; BOARD_SIZE] {
+                self.embedded.distance_field(*id, self.evaluate_mode())
+            }
+
+            /// Every food with each snake's `distance_field` distance to it, indexed by
+            /// `SnakeId`, for building food contest heuristics. Food is in increasing index
+            /// order
+            pub fn food_distance_matrix(&self) -> Vec<(CellIndex<T>, [Option<u16>; MAX_SNAKES])> {
+                self.embedded.food_distance_matrix(self.evaluate_mode())
+            }
+
+            /// how many of this cell's four neighbors are on the board and free of snakes.
+            /// Degree 1 cells are dead ends and degree 2 cells are corridors, which makes this
+            /// the building block for chokepoint and trap detection
+            pub fn open_degree(&self, idx: CellIndex<T>) -> u8 {
+                self.embedded.open_degree(idx, self.evaluate_mode())
+            }
+
+            /// `open_degree` for every cell, indexed by cell. Low degree regions are good
+            /// "avoid unless necessary" zones, and the map is handy for visualizing the topology
+            /// of the board
+            pub fn open_degree_map(&self) -> [u8; BOARD_SIZE] {
+                self.embedded.open_degree_map(self.evaluate_mode())
+            }
+
+            /// what is on the given cell
+            pub fn what_is_at(
+                &self,
+                idx: CellIndex<T>,
+            ) -> $crate::compact_representation::CellContents {
+                self.embedded.what_is_at(idx)
+            }
+
+            /// Exports the board as a row-major grid of cells, for interop with visualizers and
+            /// other tools. This allocates, so it is not meant for hot loops.
+            ///
+            /// The grid is indexed as `grid[y][x]` using battlesnake coordinates: `grid[0]` is the
+            /// row where `y = 0`, which is the *bottom* of the board as shown in the battlesnake
+            /// UI, and `x = 0` is the left edge. Reverse the rows to print the board top down
+            pub fn to_grid(&self) -> Vec<Vec<$crate::compact_representation::CellContents>> {
+                self.embedded.to_grid()
+            }
+
+            /// A one call danger check for a position, combining normalized factors for `me`:
+            /// the fraction of moves that are immediately fatal, whether you are trapped in
+            /// fewer cells than your length, your area deficit against the best opponent and how
+            /// many of your moves an enemy at least as long as you can contest. Each factor is
+            /// between 0 and 1 and is multiplied by its weight, so the score ranges from 0 to the
+            /// sum of the weights, higher is more dangerous. Dead snakes get the maximum score
+            pub fn danger_score(
+                &self,
+                me: &SnakeId,
+                weights: &$crate::compact_representation::DangerWeights,
+            ) -> f32 {
+                self.embedded
+                    .danger_score(*me, weights, self.evaluate_mode())
+            }
+
+            /// why each of `id`'s moves is or isn't safe, in `Move::all` order: leaving the board,
+            /// hitting its own or another snake's body, or a head to head collision it would lose
+            /// or tie. Handy to show next to a rendered board when debugging move choices
+            pub fn move_analysis(
+                &self,
+                id: &SnakeId,
+            ) -> [(
+                $crate::types::Move,
+                $crate::compact_representation::MoveLegality,
+            ); 4] {
+                self.embedded.move_analysis(*id, self.evaluate_mode())
+            }
+
+            /// transforms the board by a rotation or reflection, returns None if the symmetry
+            /// only applies to square boards and this board isn't square
+            pub fn apply_symmetry(
+                &self,
+                symmetry: $crate::compact_representation::Symmetry,
+            ) -> Option<Self> {
+                Some(Self {
+                    embedded: self.embedded.apply_symmetry(symmetry)?,
+                })
+            }
+
+            /// the representative of this board among all of its rotations and reflections, so
+            /// boards that are symmetric images of each other have equal canonical forms
+            pub fn canonical_form(&self) -> Self {
+                Self {
+                    embedded: self.embedded.canonical_form(),
+                }
+            }
+
+            /// The rotations and reflections that leave this exact board unchanged. A search
+            /// can skip canonicalizing boards whose group is trivial, and at the root only needs
+            /// one move from each set of moves the group maps on to each other
+            pub fn symmetry_group(&self) -> $crate::compact_representation::SymmetryGroup {
+                self.embedded.symmetry_group()
+            }
+
+            /// The moves for `me` that lead to positions which are not symmetric images of each
+            /// other, while every other snake holds still. For equivalent moves only the first in
+            /// `Move::all` order is kept, and moves that are certain death are left out. On a
+            /// symmetric opening board this reduces the root branching factor
+            pub fn dedup_symmetric_moves(&self, me: &SnakeId) -> Vec<Move> {
+                self.embedded
+                    .dedup_symmetric_moves(*me, self.evaluate_mode())
+            }
+
+            /// How many turns until this snake starves if it eats nothing, assuming its head
+            /// stays in the same kind of cell it is in now. Health decays by 1 per turn, plus the
+            /// board's hazard damage while the head is in a hazard. Dead snakes return 0
+            pub fn turns_until_starve(&self, id: &SnakeId) -> u16 {
+                self.embedded.turns_until_starve(*id)
+            }
+
+            /// The game turn this snake starves on if it eats nothing, the current turn plus
+            /// `turns_until_starve`. Absolute turns can be compared directly with other
+            /// milestones, like the turn royale next shrinks
+            pub fn projected_starve_turn(&self, id: &SnakeId) -> u16 {
+                self.embedded.projected_starve_turn(*id)
+            }
+
+            /// The fewest turns until both snakes' heads could reach a common cell, the minimum
+            /// over shared reachable cells of the larger of the two distances. Useful for timing
+            /// an attack or an escape. `None` if the snakes can't reach any common cell
+            pub fn turns_to_intercept(&self, me: &SnakeId, them: &SnakeId) -> Option<u16> {
+                self.embedded
+                    .turns_to_intercept(*me, *them, self.evaluate_mode())
+            }
+
+            /// The net influence of every snake on each cell, for evaluations that want a
+            /// softer split of the board than a voronoi partition. Each head radiates `decay` to
+            /// the power of the cell's distance, positive for `me` and negative for enemies.
+            /// Bodies block the spread, hazards don't
+            pub fn influence_map(&self, me: &SnakeId, decay: f32) -> [f32; BOARD_SIZE] {
+                self.embedded
+                    .influence_map(*me, decay, self.evaluate_mode())
+            }
+
+            /// An estimate of the longest simple path of open cells this snake can walk out of
+            /// its head, where bodies and heads are walls. Snakes trapped in short corridors
+            /// score low even when reachable area looks fine. The depth first search is bounded,
+            /// so this is a lower bound capped at 32 cells. Dead snakes return 0
+            pub fn longest_escape_corridor(&self, id: &SnakeId) -> u16 {
+                self.embedded
+                    .longest_escape_corridor(*id, self.evaluate_mode())
+            }
+
+            /// This snake's health minus the hazard damage it takes next turn if its head stays
+            /// in a hazard. A snake at 10 health in a hazard dealing 15 damage is effectively
+            /// already dead, so this goes negative. Dead snakes return 0
+            pub fn effective_health(&self, id: &SnakeId) -> i32 {
+                self.embedded.effective_health(*id)
+            }
+
+            /// whether this board is the start of a game: turn 0, with every snake at full health
+            /// and stacked on one of the official spawn points. Lets a bot look up precomputed
+            /// opening moves only when they apply
+            pub fn is_standard_start(&self) -> bool {
+                self.embedded.is_standard_start()
+            }
+
+            /// this snake's length minus the longest other living snake's length. Positive means
+            /// it wins head to heads against every enemy, the usual gate for aggressive play
+            pub fn length_advantage(&self, me: &SnakeId) -> i32 {
+                self.embedded.length_advantage(*me)
+            }
+
+            /// this snake's remaining health, the same as `get_health` but named for its use as a
+            /// tie break when lengths are equal
+            pub fn total_health(&self, id: &SnakeId) -> u8 {
+                self.embedded.total_health(*id)
+            }
+
+            /// Ranks two snakes by length, then health, then `SnakeId`, `Ordering::Greater` means
+            /// `a` is ahead. A longer snake is always ahead, among equally long snakes the one
+            /// with more health is ahead and a full tie goes to the lower `SnakeId`. Dead snakes
+            /// rank behind all living ones. Sort with it in reverse to get the standings
+            pub fn compare_snakes(&self, a: &SnakeId, b: &SnakeId) -> std::cmp::Ordering {
+                self.embedded.compare_snakes(*a, *b)
+            }
+
+            /// The cells from which a food can be reached in one move, skipping cells that will
+            /// still hold a snake next turn. Useful for reasoning about who controls the
+            /// approaches to a food. Sorted by index without duplicates
+            pub fn food_approach_cells(&self) -> Vec<CellIndex<T>> {
+                self.embedded.food_approach_cells(self.evaluate_mode())
+            }
+
+            /// whether any food is within `radius` of this snake's head by manhattan distance,
+            /// wrapping around the edges on wrapped boards. A cheap early out before running a
+            /// search for the nearest food
+            pub fn food_within(&self, id: &SnakeId, radius: u32) -> bool {
+                self.embedded.food_within(*id, radius, self.evaluate_mode())
+            }
+
+            /// determines if this cell is guaranteed to not hold a snake next turn, either
+            /// because it holds no snake now or because it is a tail that will move away.
+            /// Stacked tails (snakes that just ate) stay in place and are not free
+            pub fn will_be_free_next_turn(&self, idx: CellIndex<T>) -> bool {
+                self.embedded.will_be_free_next_turn(idx)
+            }
+
+            /// Checks whether a game fits in this board type, reporting every problem at once
+            /// (oversized board, too many snakes, duplicate ids and malformed or off board
+            /// bodies) without building a board. Handy for finding out why a fixture won't
+            /// convert
+            pub fn validate_game(
+                game: &Game,
+            ) -> Vec<$crate::compact_representation::ConversionError> {
+                CCB::<T, D, BOARD_SIZE, MAX_SNAKES>::validate_game(game)
+            }
+
+            /// Encodes this board as bytes, see `from_bytes` for the inverse. The encoding only
+            /// depends on the board's contents, so it is stable across runs
+            pub fn to_bytes(&self) -> Vec<u8> {
+                self.embedded.to_bytes()
+            }
+
+            /// Decodes a board written by `to_bytes`
+            pub fn from_bytes(
+                bytes: &[u8],
+            ) -> Result<Self, $crate::compact_representation::ConversionError> {
+                Ok(Self {
+                    embedded: CCB::from_bytes(bytes)?,
+                })
+            }
+
+            /// A deterministic, human readable key for this board (the hex encoding of
+            /// `to_bytes`), for correlating debug logs with cached evaluations
+            pub fn cache_key(&self) -> String {
+                self.embedded.cache_key()
+            }
+
+            /// Decodes a board from a key produced by `cache_key`
+            pub fn from_cache_key(
+                key: &str,
+            ) -> Result<Self, $crate::compact_representation::ConversionError> {
+                Ok(Self {
+                    embedded: CCB::from_cache_key(key)?,
+                })
+            }
+
+            /// Encodes a whole game, one board per turn, for replay archives. Each frame only
+            /// stores the bytes that changed since the previous frame, which is much smaller
+            /// than calling `to_bytes` on every board. See `deserialize_game` for the inverse
+            pub fn serialize_game(boards: &[Self]) -> Vec<u8> {
+                CCB::serialize_game(boards.iter().map(|board| &board.embedded))
+            }
+
+            /// Decodes every board written by `serialize_game`, in order
+            pub fn deserialize_game(
+                bytes: &[u8],
+            ) -> Result<Vec<Self>, $crate::compact_representation::ConversionError> {
+                Ok(CCB::deserialize_game(bytes)?
+                    .into_iter()
+                    .map(|embedded| Self { embedded })
+                    .collect())
+            }
+
+            /// Replays a log of an official game through the simulator and reports the first
+            /// turn where the simulated board differs from the logged one. `moves[i]` holds every
+            /// snake's move from `frames[i]` to `frames[i + 1]`, indexed by the `SnakeId`s that
+            /// `build_snake_id_map` assigns in the first frame. Food spawns and hazards aren't
+            /// simulated, so they aren't compared
+            pub fn conformance_check(
+                frames: &[Game],
+                moves: &[[Move; MAX_SNAKES]],
+            ) -> Result<(), $crate::compact_representation::ConformanceError> {
+                CCB::<T, D, BOARD_SIZE, MAX_SNAKES>::conformance_check(frames, moves)
+            }
+
+            /// The ruleset this board was converted from, or `None` for boards built from a
+            /// grid. Simulating a board in a mode that doesn't match its origin, e.g. a wrapped
+            /// game converted with `convert_from_game_unchecked_mode`, fails a debug assertion
+            pub fn game_mode(&self) -> Option<$crate::compact_representation::GameMode> {
+                self.embedded.game_mode()
+            }
+
+            /// The ruleset settings of the game this board was converted from, or `None` if the
+            /// server didn't send any. Food spawning follows these, falling back to the
+            /// official defaults
+            pub fn ruleset_settings(
+                &self,
+            ) -> Option<$crate::compact_representation::RulesetSettings> {
+                self.embedded.ruleset_settings()
+            }
+
+            /// the number of cells on the board
+            pub fn area(&self) -> u32 {
+                self.embedded.area()
+            }
+
+            /// the cells along the edge of the board, in index order. Empty for wrapped boards,
+            /// which have no edge
+            pub fn perimeter_cells(&self) -> impl Iterator<Item = CellIndex<T>> {
+                self.embedded.perimeter_cells(self.evaluate_mode())
+            }
+
+            /// how many steps each cell is from the nearest edge of the board, 0 on the edge.
+            /// Useful for biasing towards the center. Wrapped boards have no edge, so every
+            /// cell is `u8::MAX` for them
+            pub fn wall_distance_map(&self) -> [u8; BOARD_SIZE] {
+                self.embedded.wall_distance_map(self.evaluate_mode())
+            }
+
+            /// the cells holding any snake's head or body. This is maintained during
+            /// simulation, so it is free to get
+            #[cfg(feature = "bitboard")]
+            pub fn all_bodies_mask(&self) -> $crate::compact_representation::BitBoard<T> {
+                self.embedded.all_bodies_mask()
+            }
+
+            /// the cells holding this snake's head or body, empty for dead snakes. Combine it
+            /// with other masks using the bitwise operators, e.g. to find which candidate cells
+            /// touch enemy bodies
+            #[cfg(feature = "bitboard")]
+            pub fn body_mask(&self, id: &SnakeId) -> $crate::compact_representation::BitBoard<T> {
+                self.embedded.body_mask(*id)
+            }
+
+            /// the cells without a snake that touch any snake's head or body. A large frontier
+            /// means a tactically busy board, and it shows where collisions could happen next
+            #[cfg(feature = "bitboard")]
+            pub fn body_frontier(&self) -> $crate::compact_representation::BitBoard<T> {
+                self.embedded.body_frontier(self.evaluate_mode())
+            }
+
+            /// whether moving `id` in direction `mv` is certain death: off the edge of the
+            /// board, or in to a snake cell that won't be vacated next turn. Head to head
+            /// collisions aren't certain so they aren't considered. With the `bitboard` feature
+            /// the snake check is a single bit test
+            pub fn is_move_immediately_fatal(&self, id: &SnakeId, mv: Move) -> bool {
+                self.embedded
+                    .is_move_immediately_fatal(*id, mv, self.evaluate_mode())
+            }
+
+            /// the id and head of every living snake, ordered by `SnakeId`
+            pub fn alive_heads(&self) -> impl Iterator<Item = (SnakeId, CellIndex<T>)> + '_ {
+                self.embedded.alive_heads()
+            }
+
+            /// whether this snake ate on the previous turn, i.e. its tail is double stacked and
+            /// will stay in place next turn. This is also true on the first turn of a game, when
+            /// the starting stack hasn't unwound yet
+            pub fn just_ate(&self, id: &SnakeId) -> bool {
+                self.embedded.just_ate(*id)
+            }
+
+            /// The health of every snake, where index `i` is the health of `SnakeId(i)`. Dead
+            /// snakes and unused slots read 0, the same as `get_health`
+            pub fn healths(&self) -> [u8; MAX_SNAKES] {
+                self.embedded.healths()
+            }
+
+            /// The length of every snake, where index `i` is the length of `SnakeId(i)`. Dead
+            /// snakes and unused slots always read 0, they don't keep their last length
+            pub fn lengths(&self) -> [u16; MAX_SNAKES] {
+                self.embedded.lengths()
+            }
+
+            /// the most body segments stacked on any one of this snake's cells, between 1 and 3,
+            /// or 0 if the snake is dead. Only the tail should ever be stacked, so this shows
+            /// whether the snake is growing
+            pub fn max_stack(&self, id: &SnakeId) -> u8 {
+                self.embedded.max_stack(*id)
+            }
+
+            /// The average of this snake's body cell coordinates, a coarse signal for whether
+            /// it is central or cornered. On wrapped boards this is just the head position, as
+            /// a body can wrap around the edges. `None` for dead snakes
+            pub fn snake_centroid(
+                &self,
+                id: &SnakeId,
+            ) -> Option<$crate::wire_representation::Position> {
+                self.embedded.snake_centroid(*id, self.evaluate_mode())
+            }
+
+            /// For every cell `me` can move in to, lists each living enemy whose head could also
+            /// move there next turn, so that contested cells can be weighed rather than avoided.
+            /// Ordered by `Move::all` for `me`, then by enemy id. Comparing lengths is left to
+            /// the caller
+            pub fn contested_cells(&self, me: &SnakeId) -> Vec<(CellIndex<T>, SnakeId)> {
+                self.embedded.contested_cells(*me, self.evaluate_mode())
+            }
+
+            /// The fewest turns until this cell could be a hazard in royale, assuming the next
+            /// shrink is next turn and every shrink comes from the nearest side. `Some(0)` for
+            /// hazards, `None` if royale never shrinks
+            pub fn turns_until_hazard(
+                &self,
+                idx: CellIndex<T>,
+                royale: &$crate::wire_representation::RoyaleSettings,
+            ) -> Option<u16> {
+                self.embedded.turns_until_hazard(idx, royale)
+            }
+
+            /// The hazards of a sinkhole pit around `center` on `turn`, as each hazard cell with
+            /// the number of hazards stacked on it. The pit grows by a ring on the schedule, so
+            /// this forecasts where it will be and how deep
+            pub fn hazard_sinkhole(
+                &self,
+                center: CellIndex<T>,
+                turn: u16,
+                schedule: &$crate::compact_representation::SinkholeSchedule,
+            ) -> Vec<(CellIndex<T>, u8)> {
+                self.embedded.hazard_sinkhole(center, turn, schedule)
+            }
+
+            /// Overlays hazards from a pattern generator like `hazard_sinkhole`, given as cells
+            /// with how many hazards to stack on them. Stacks add to the hazards already on the
+            /// board, up to `MAX_HAZARD_STACK`, so patterns can be combined
+            pub fn apply_hazard_map(
+                &mut self,
+                hazards: impl IntoIterator<Item = (CellIndex<T>, u8)>,
+            ) {
+                self.embedded.apply_hazard_map(hazards)
+            }
+
+            /// how many hazards are stacked on this cell, 0 if it isn't a hazard. A head on the
+            /// cell takes the hazard damage once per hazard
+            pub fn hazard_stack(&self, idx: CellIndex<T>) -> u8 {
+                self.embedded.hazard_stack(idx)
+            }
+
+            /// The cells this snake can reach that won't become a hazard within `horizon` turns
+            /// by `turns_until_hazard`. In late game royale a plain flood fill overcounts space
+            /// that is about to be engulfed, this is the realistic space metric
+            pub fn safe_reachable_squares(
+                &self,
+                id: &SnakeId,
+                horizon: u16,
+                royale: &$crate::wire_representation::RoyaleSettings,
+            ) -> u16 {
+                self.embedded
+                    .safe_reachable_squares(*id, horizon, royale, self.evaluate_mode())
+            }
+
+            /// The cells this snake can flood fill to without first stepping on a cell that a
+            /// living enemy at least as long could also move in to next turn. A more honest
+            /// space estimate than a plain flood fill for a snake shorter than a nearby rival
+            pub fn head_to_head_safe_reachable_squares(&self, id: &SnakeId) -> u16 {
+                self.embedded
+                    .head_to_head_safe_reachable_squares(*id, self.evaluate_mode())
+            }
+
+            /// Every living snake whose head is next to `idx` and so could move there next
+            /// turn, ordered by id. Handy for scoring food and key cells by how many snakes
+            /// contest them. This crate doesn't depend on arrayvec, so this is a `Vec`
+            pub fn threats_to(&self, idx: CellIndex<T>) -> Vec<SnakeId> {
+                self.embedded.threats_to(idx, self.evaluate_mode())
+            }
+
+            /// Whether `me` survives if it and an enemy both move in to `idx`: `Some(true)`
+            /// when `me` is longer than every enemy that can reach it, `Some(false)` when one
+            /// is as long or longer and `None` when no enemy can reach it
+            pub fn wins_head_to_head_at(&self, me: &SnakeId, idx: CellIndex<T>) -> Option<bool> {
+                self.embedded
+                    .wins_head_to_head_at(*me, idx, self.evaluate_mode())
+            }
+
+            /// Whether two enemy heads pin `me` down: every move is either blocked or lands on
+            /// a cell that at least two enemies can also move in to. Built from
+            /// `will_be_free_next_turn` and `contested_cells` to catch this losing pattern early
+            pub fn is_double_threatened(&self, me: &SnakeId) -> bool {
+                self.embedded
+                    .is_double_threatened(*me, self.evaluate_mode())
+            }
+
+            /// the cells where this snake's head would have no safe move, given the bodies and
+            /// hazards on the board now and its current health. Sweeps the whole board, so
+            /// precompute it once per turn to route around death traps
+            pub fn death_cells(&self, id: &SnakeId) -> Vec<CellIndex<T>> {
+                self.embedded.death_cells(*id, self.evaluate_mode())
+            }
+
+            /// Like `SimulableGame::simulate_with_moves`, but head to head collisions are
+            /// resolved with `policy` instead of the standard rule, for experimenting with
+            /// custom rule sets. `HeadToHeadPolicy::Standard` gives exactly the same results as
+            /// `simulate_with_moves`
+            #[allow(clippy::type_complexity)]
+            pub fn simulate_with_head_to_head_policy<'a, I, S>(
+                &'a self,
+                instruments: &I,
+                snake_ids_and_moves: impl IntoIterator<Item = (SnakeId, S)>,
+                policy: $crate::compact_representation::HeadToHeadPolicy,
+            ) -> Box<dyn Iterator<Item = ($crate::types::Action<MAX_SNAKES>, Self)> + 'a>
+            where
+                I: $crate::types::SimulatorInstruments,
+                S: std::borrow::Borrow<[$crate::types::Move]>,
+            {
+                Box::new(
+                    $crate::compact_representation::core::simulate_with_moves_and_policy(
+                        &self.embedded,
+                        instruments,
+                        snake_ids_and_moves,
+                        self.evaluate_mode(),
+                        policy,
+                    )
+                    .map(|(action, embedded)| (action, Self { embedded })),
+                )
+            }
+
+            /// Like `SimulableGame::simulate_with_moves`, but with the rule variants in
+            /// `settings`, like food adding a fixed amount of health instead of restoring it.
+            /// `SimSettings::default()` gives exactly the same results as `simulate_with_moves`
+            #[allow(clippy::type_complexity)]
+            pub fn simulate_with_settings<'a, I, S>(
+                &'a self,
+                instruments: &I,
+                snake_ids_and_moves: impl IntoIterator<Item = (SnakeId, S)>,
+                settings: $crate::compact_representation::SimSettings,
+            ) -> Box<dyn Iterator<Item = ($crate::types::Action<MAX_SNAKES>, Self)> + 'a>
+            where
+                I: $crate::types::SimulatorInstruments,
+                S: std::borrow::Borrow<[$crate::types::Move]>,
+            {
+                Box::new(
+                    $crate::compact_representation::core::simulate_with_settings(
+                        &self.embedded,
+                        instruments,
+                        snake_ids_and_moves,
+                        self.evaluate_mode(),
+                        settings,
+                    )
+                    .map(|(action, embedded)| (action, Self { embedded })),
+                )
+            }
+
+            /// Advances the board by one turn where every living snake makes the move at its
+            /// `SnakeId` in `moves`, entries for dead snakes are ignored. Equivalent to the only
+            /// result of `simulate_with_moves` with one move per snake, but it doesn't allocate,
+            /// making it the fastest path for search once the moves are decided
+            pub fn step_once(&self, moves: [$crate::types::Move; MAX_SNAKES]) -> Self {
+                Self {
+                    embedded: self.embedded.step_once(moves, self.evaluate_mode()),
+                }
+            }
+
+            /// Simulates one turn with a single move per snake, like `simulate_with_moves`, but
+            /// also returns the board after each phase of the turn. Useful for stepping through
+            /// a rules discrepancy. It copies the board for every phase, so don't use it in
+            /// search
+            pub fn simulate_traced(
+                &self,
+                moves: &[(SnakeId, $crate::types::Move)],
+            ) -> $crate::compact_representation::SimTrace<Self> {
+                let trace = self.embedded.simulate_traced(moves, self.evaluate_mode());
+                $crate::compact_representation::SimTrace {
+                    before: *self,
+                    after_tails_and_food: Self {
+                        embedded: trace.after_tails_and_food,
+                    },
+                    after_head_to_head: Self {
+                        embedded: trace.after_head_to_head,
+                    },
+                    result: Self {
+                        embedded: trace.result,
+                    },
+                }
+            }
+
+            /// Simulates one turn with a single move per snake and also returns a log of every
+            /// move, meal and elimination in it, for commentary and debugging views. The log is
+            /// allocated every call, so keep it out of search
+            pub fn simulate_logged(
+                &self,
+                moves: &[(SnakeId, $crate::types::Move)],
+            ) -> (Self, Vec<$crate::compact_representation::SimEvent>) {
+                let (embedded, events) = self.embedded.simulate_logged(moves, self.evaluate_mode());
+                (Self { embedded }, events)
+            }
+
+            /// the living enemy whose head is closest to ours by manhattan distance, and that
+            /// distance. Distances wrap around the edges on wrapped boards, and ties go to the
+            /// lowest id. `None` if `me` is dead or alone
+            pub fn nearest_enemy_head(&self, me: &SnakeId) -> Option<(SnakeId, u32)> {
+                self.embedded.nearest_enemy_head(*me, self.evaluate_mode())
+            }
+
+            /// Whether `a` and `b` can possibly collide next turn, either head to head or one
+            /// moving in to the other's body. When this is false the two snakes can't interact
+            /// this turn and can be analysed independently
+            pub fn can_collide_next_turn(&self, a: &SnakeId, b: &SnakeId) -> bool {
+                self.embedded
+                    .can_collide_next_turn(*a, *b, self.evaluate_mode())
+            }
+
+            /// The cells no snake occupies and no living head could move in to next turn, i.e.
+            /// territory that is free for everyone rather than contested. In increasing index
+            /// order
+            pub fn globally_safe_cells(&self) -> Vec<CellIndex<T>> {
+                self.embedded.globally_safe_cells(self.evaluate_mode())
+            }
+        }
+
         impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
             LengthGettableGame for $type<T, D, BOARD_SIZE, MAX_SNAKES>
         {
@@ -31,6 +689,14 @@ macro_rules! impl_common_board_traits {
             }
         }
 
+        impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+            TurnDeterminableGame for $type<T, D, BOARD_SIZE, MAX_SNAKES>
+        {
+            fn turn(&self) -> u64 {
+                self.embedded.turn()
+            }
+        }
+
         impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
             VictorDeterminableGame for $type<T, D, BOARD_SIZE, MAX_SNAKES>
         {
@@ -159,6 +825,27 @@ macro_rules! impl_common_board_traits {
             }
         }
 
+        /// Serialized as the bytes of `to_bytes`, so boards can be persisted, e.g. as the keys of a
+        /// saved `TranspositionTable`
+        impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+            serde::Serialize for $type<T, D, BOARD_SIZE, MAX_SNAKES>
+        {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(&self.to_bytes())
+            }
+        }
+
+        impl<'de, T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+            serde::Deserialize<'de> for $type<T, D, BOARD_SIZE, MAX_SNAKES>
+        {
+            fn deserialize<De: serde::Deserializer<'de>>(
+                deserializer: De,
+            ) -> Result<Self, De::Error> {
+                let bytes = <Vec<u8> as serde::Deserialize>::deserialize(deserializer)?;
+                Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+            }
+        }
+
         impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> Display
             for $type<T, D, BOARD_SIZE, MAX_SNAKES>
         {
@@ -198,12 +885,16 @@ macro_rules! impl_common_board_traits {
             }
         }
 
+        /// Converts a game without building the snake id map yourself, e.g.
+        /// `let board: CellBoard4Snakes11x11 = game.try_into()?;`. The id map is built with
+        /// `build_snake_id_map` and then dropped, so use `convert_from_game` if you need it to
+        /// map `SnakeId`s back to the wire ids
         impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
             std::convert::TryFrom<Game> for $type<T, D, BOARD_SIZE, MAX_SNAKES>
         {
-            type Error = Box<dyn Error>;
+            type Error = $crate::compact_representation::ConversionError;
 
-            fn try_from(game: Game) -> Result<Self, Box<dyn Error>> {
+            fn try_from(game: Game) -> Result<Self, Self::Error> {
                 let id_map = $crate::types::build_snake_id_map(&game);
 
                 $type::convert_from_game(game, &id_map)
@@ -230,5 +921,163 @@ macro_rules! impl_common_board_traits {
                 self.embedded.place_food(rng)
             }
         }
+
+        impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+            $crate::types::FoodPlacerGame for $type<T, D, BOARD_SIZE, MAX_SNAKES>
+        {
+            fn place_food_with(
+                &mut self,
+                rng: &mut impl rand::Rng,
+                placer: &mut impl $crate::types::FoodPlacer<Self>,
+            ) {
+                self.embedded.place_food_from(rng, |embedded| {
+                    placer.next_food(&Self {
+                        embedded: *embedded,
+                    })
+                })
+            }
+        }
+
+        impl<T, D, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
+            $crate::compact_representation::DynCellBoard for $type<T, D, BOARD_SIZE, MAX_SNAKES>
+        where
+            T: CN + Send + Sync,
+            D: Dimensions + Send + Sync + 'static,
+        {
+            fn width(&self) -> u32 {
+                self.get_width()
+            }
+
+            fn height(&self) -> u32 {
+                self.get_height()
+            }
+
+            fn snake_ids(&self) -> Vec<SnakeId> {
+                self.get_snake_ids()
+            }
+
+            fn you_id(&self) -> SnakeId {
+                *YouDeterminableGame::you_id(self)
+            }
+
+            fn health(&self, id: SnakeId) -> u8 {
+                self.get_health(&id)
+            }
+
+            fn length(&self, id: SnakeId) -> u16 {
+                self.get_length(&id)
+            }
+
+            fn head(&self, id: SnakeId) -> Position {
+                self.get_head_as_position(&id)
+            }
+
+            fn body(&self, id: SnakeId) -> Vec<Position> {
+                self.get_snake_body_iter(&id)
+                    .map(|idx| self.position_from_native(idx))
+                    .collect()
+            }
+
+            fn food(&self) -> Vec<Position> {
+                self.get_all_food_as_positions()
+            }
+
+            fn is_hazard(&self, pos: Position) -> bool {
+                !self.off_board(pos)
+                    && HazardQueryableGame::is_hazard(self, &self.native_from_position(pos))
+            }
+
+            fn is_over(&self) -> bool {
+                VictorDeterminableGame::is_over(self)
+            }
+
+            fn winner(&self) -> Option<SnakeId> {
+                self.get_winner()
+            }
+
+            fn reasonable_moves(&self) -> Vec<(SnakeId, Vec<Move>)> {
+                self.reasonable_moves_for_each_snake().collect()
+            }
+
+            fn simulate_with_moves(
+                &self,
+                instruments: &dyn SimulatorInstruments,
+                moves: &[(SnakeId, Vec<Move>)],
+            ) -> Vec<(
+                Vec<(SnakeId, Move)>,
+                Box<dyn $crate::compact_representation::DynCellBoard>,
+            )> {
+                let instruments = $crate::compact_representation::DynInstruments(instruments);
+                let moves = moves.iter().map(|(sid, mvs)| (*sid, mvs.as_slice()));
+                SimulableGame::simulate_with_moves(self, &instruments, moves)
+                    .map(|(action, board)| {
+                        let moves = action
+                            .into_inner()
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, mv)| mv.map(|mv| (SnakeId(i as u8), mv)))
+                            .collect();
+                        (
+                            moves,
+                            Box::new(board)
+                                as Box<dyn $crate::compact_representation::DynCellBoard>,
+                        )
+                    })
+                    .collect()
+            }
+
+            fn clone_box(&self) -> Box<dyn $crate::compact_representation::DynCellBoard> {
+                Box::new(*self)
+            }
+        }
+    };
+}
+
+/// Very internal, implements dispatch to the inner board for a `BestCellBoard` enum
+#[macro_export]
+macro_rules! impl_best_cell_board_dispatch {
+    ($type:tt) => {
+        impl $type {
+            /// Applies `f` to whichever board this holds, so common operations don't need a match
+            /// over every variant.
+            ///
+            /// The board is passed as a `&dyn DynCellBoard`, which gives access to the dimensions,
+            /// snake ids, healths, lengths, heads, bodies, food and hazards, the victor
+            /// (`is_over` and `winner`), reasonable moves and simulation. Rendering is available
+            /// through the `Display` and `Debug` supertraits
+            pub fn with_board<R>(
+                &self,
+                f: impl FnOnce(&dyn $crate::compact_representation::DynCellBoard) -> R,
+            ) -> R {
+                match self {
+                    $type::Tiny(b) => f(b.as_ref()),
+                    $type::SmallExact(b) => f(b.as_ref()),
+                    $type::Standard(b) => f(b.as_ref()),
+                    $type::MediumExact(b) => f(b.as_ref()),
+                    $type::LargestU8(b) => f(b.as_ref()),
+                    $type::LargeExact(b) => f(b.as_ref()),
+                    $type::ArcadeMaze(b) => f(b.as_ref()),
+                    $type::ArcadeMaze8Snake(b) => f(b.as_ref()),
+                    $type::Large(b) => f(b.as_ref()),
+                    $type::Silly(b) => f(b.as_ref()),
+                }
+            }
+
+            /// Erases the size of the held board, see `with_board` for what is available
+            pub fn into_dyn(self) -> Box<dyn $crate::compact_representation::DynCellBoard> {
+                match self {
+                    $type::Tiny(b) => b,
+                    $type::SmallExact(b) => b,
+                    $type::Standard(b) => b,
+                    $type::MediumExact(b) => b,
+                    $type::LargestU8(b) => b,
+                    $type::LargeExact(b) => b,
+                    $type::ArcadeMaze(b) => b,
+                    $type::ArcadeMaze8Snake(b) => b,
+                    $type::Large(b) => b,
+                    $type::Silly(b) => b,
+                }
+            }
+        }
     };
 }
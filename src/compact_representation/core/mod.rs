@@ -1,5 +1,6 @@
 mod cell_board;
 mod cell_num;
+mod error;
 mod impl_common;
 mod simulate;
 
@@ -8,9 +9,16 @@ use crate::{
     wire_representation::Position,
 };
 
-pub use cell_board::{CellBoard, EvaluateMode};
+#[cfg(feature = "bitboard")]
+pub use cell_board::{BitBoard, BitBoardWords};
+pub use cell_board::{
+    CellBoard, CellContents, DangerWeights, EliminationCause, EvaluateMode, FoodHealthMode,
+    GameMode, HeadToHeadPolicy, MoveLegality, RulesetSettings, SimEvent, SimSettings, SimTrace,
+    SinkholeSchedule, Symmetry, SymmetryGroup,
+};
 pub use cell_num::CellNum;
-pub use simulate::simulate_with_moves;
+pub use error::{ConformanceError, ConversionError};
+pub use simulate::{simulate_with_moves, simulate_with_moves_and_policy, simulate_with_settings};
 
 /// wrapper type for an index in to the board
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -23,6 +31,17 @@ impl<T: CellNum> CellIndex<T> {
         Self(T::from_i32(pos.y * width as i32 + pos.x))
     }
 
+    /// makes a new cell index from raw coordinates, needs to know the width of the board
+    pub fn from_xy(x: u8, y: u8, width: u8) -> Self {
+        Self::from_usize(y as usize * width as usize + x as usize)
+    }
+
+    /// the coordinates of this cell index as `(x, y)`, the inverse of `from_xy`
+    pub fn to_xy(&self, width: u8) -> (u8, u8) {
+        let idx = self.0.as_usize();
+        ((idx % width as usize) as u8, (idx / width as usize) as u8)
+    }
+
     /// build a CellIndex from a usize
     pub fn from_usize(u: usize) -> Self {
         Self(T::from_usize(u))
@@ -65,6 +84,11 @@ const EMPTY: u8 = 0x05;
 const KIND_MASK: u8 = 0x07;
 
 const IS_HAZARD: u8 = 0x10;
+/// the top 3 bits of a hazard cell hold how many hazards are stacked on it, minus one
+const HAZARD_STACK_MASK: u8 = 0xe0;
+const HAZARD_STACK_SHIFT: u8 = 5;
+/// the most hazards a cell can have stacked on it
+pub const MAX_HAZARD_STACK: u8 = 8;
 
 pub const TRIPLE_STACK: usize = 3;
 pub const DOUBLE_STACK: usize = 2;
@@ -130,13 +154,31 @@ impl<T: CellNum> Cell<T> {
     }
 
     pub fn clear_hazard(&mut self) {
-        self.flags &= !IS_HAZARD
+        self.flags &= !(IS_HAZARD | HAZARD_STACK_MASK)
     }
 
     pub fn is_hazard(&self) -> bool {
         self.flags & IS_HAZARD != 0
     }
 
+    /// how many hazards are stacked on this cell, 0 if it isn't a hazard
+    pub fn hazard_stack(&self) -> u8 {
+        if self.is_hazard() {
+            1 + ((self.flags & HAZARD_STACK_MASK) >> HAZARD_STACK_SHIFT)
+        } else {
+            0
+        }
+    }
+
+    /// sets how many hazards are stacked on this cell, capped at `MAX_HAZARD_STACK`. 0 clears
+    /// the hazard
+    pub fn set_hazard_stack(&mut self, stack: u8) {
+        self.clear_hazard();
+        if stack > 0 {
+            self.flags |= IS_HAZARD | ((stack.min(MAX_HAZARD_STACK) - 1) << HAZARD_STACK_SHIFT);
+        }
+    }
+
     pub fn is_body_segment(&self) -> bool {
         self.is_snake_body_piece()
             || self.is_double_stacked_piece()
@@ -239,6 +281,12 @@ impl<T: CellNum> Cell<T> {
         self.idx = next_pos;
     }
 
+    /// changes which snake a head or body cell belongs to, leaving its links alone
+    pub fn set_snake_id(&mut self, sid: SnakeId) {
+        debug_assert!(self.is_body_segment() || self.is_head());
+        self.id = sid;
+    }
+
     pub fn get_snake_id(&self) -> Option<SnakeId> {
         if self.is_body_segment() || self.is_head() {
             Some(self.id)
@@ -5,7 +5,11 @@ use tracing::instrument;
 
 use crate::types::{Action, Move, SimulatorInstruments, SnakeId, N_MOVES};
 
-use super::{cell_board::EvaluateMode, dimensions::Dimensions, CellBoard, CellNum};
+use super::{
+    cell_board::{EvaluateMode, HeadToHeadPolicy, SimSettings},
+    dimensions::Dimensions,
+    CellBoard, CellNum,
+};
 
 #[instrument(level = "trace", skip_all)]
 pub fn simulate_with_moves<
@@ -22,6 +26,69 @@ pub fn simulate_with_moves<
     snake_ids_and_moves: impl IntoIterator<Item = (SnakeId, S)>,
     evaluate_mode: EvaluateMode,
 ) -> Box<dyn Iterator<Item = (Action<MAX_SNAKES>, CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>)> + 'a>
+where
+    S: Borrow<[Move]>,
+{
+    simulate_with_moves_and_policy(
+        board,
+        instruments,
+        snake_ids_and_moves,
+        evaluate_mode,
+        HeadToHeadPolicy::Standard,
+    )
+}
+
+/// Like `simulate_with_moves`, but head to head collisions are resolved with `policy` instead of
+/// the standard rule
+#[instrument(level = "trace", skip_all)]
+pub fn simulate_with_moves_and_policy<
+    'a,
+    S,
+    I: SimulatorInstruments,
+    T: CellNum,
+    D: Dimensions,
+    const BOARD_SIZE: usize,
+    const MAX_SNAKES: usize,
+>(
+    board: &'a CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>,
+    instruments: &I,
+    snake_ids_and_moves: impl IntoIterator<Item = (SnakeId, S)>,
+    evaluate_mode: EvaluateMode,
+    policy: HeadToHeadPolicy,
+) -> Box<dyn Iterator<Item = (Action<MAX_SNAKES>, CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>)> + 'a>
+where
+    S: Borrow<[Move]>,
+{
+    simulate_with_settings(
+        board,
+        instruments,
+        snake_ids_and_moves,
+        evaluate_mode,
+        SimSettings {
+            head_to_head: policy,
+            ..SimSettings::default()
+        },
+    )
+}
+
+/// Like `simulate_with_moves`, but with the rule variants in `settings` instead of the official
+/// rules
+#[instrument(level = "trace", skip_all)]
+pub fn simulate_with_settings<
+    'a,
+    S,
+    I: SimulatorInstruments,
+    T: CellNum,
+    D: Dimensions,
+    const BOARD_SIZE: usize,
+    const MAX_SNAKES: usize,
+>(
+    board: &'a CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>,
+    instruments: &I,
+    snake_ids_and_moves: impl IntoIterator<Item = (SnakeId, S)>,
+    evaluate_mode: EvaluateMode,
+    settings: SimSettings,
+) -> Box<dyn Iterator<Item = (Action<MAX_SNAKES>, CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>)> + 'a>
 where
     S: Borrow<[Move]>,
 {
@@ -37,7 +104,11 @@ where
     // sid major, move minor
     // [ some_reulst_struct, some_dead_struct ]
     // [ some_dead_struct, some_dead_struct ] // snake we didn't simulate
-    let states = board.generate_state(snake_ids_and_moves.iter(), evaluate_mode);
+    let states = board.generate_state_with_food_health(
+        snake_ids_and_moves.iter(),
+        evaluate_mode,
+        settings.food_health,
+    );
     let mut dead_snakes_table = [[false; N_MOVES]; MAX_SNAKES];
 
     for (sid, result_row) in states.iter().enumerate() {
@@ -66,7 +137,12 @@ where
     let results = ids_and_moves_product.into_iter().map(move |m| {
         let action = Action::collect_from(m.iter());
 
-        let game = board.evaluate_moves_with_state(m.iter(), &states);
+        let game = board.evaluate_moves_with_state(
+            m.iter(),
+            &states,
+            settings.head_to_head,
+            evaluate_mode,
+        );
         if !game.assert_consistency() {
             panic!(
                 "caught an inconsistent simulate, moves: {:?} orig: {}, new: {}",
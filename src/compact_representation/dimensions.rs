@@ -9,6 +9,19 @@ use std::hash::Hash;
 
 /// Trait that all different Dimensions must implement
 pub trait Dimensions: Debug + Copy + Hash {
+    /// The width and height every board with these dimensions has, if they are known at compile
+    /// time. Boards check this against their storage size, and every conversion checks it
+    /// against the size of the game, grid or bytes it is given, see [Fixed]
+    const FIXED: Option<(u8, u8)> = None;
+
+    /// Whether these dimensions can describe a board of the given width and height, regardless
+    /// of how much storage the board has
+    fn can_hold(width: u32, height: u32) -> bool {
+        Self::FIXED
+            .map(|(w, h)| (w as u32, h as u32) == (width, height))
+            .unwrap_or(true)
+    }
+
     /// Convert from a width and a height to this dimension
     fn from_dimensions(width: u8, height: u8) -> Self;
 
@@ -36,6 +49,10 @@ impl Dimensions for Square {
         self.width
     }
 
+    fn can_hold(width: u32, height: u32) -> bool {
+        width == height
+    }
+
     fn from_dimensions(width: u8, height: u8) -> Self {
         debug_assert!(width == height);
 
@@ -45,9 +62,15 @@ impl Dimensions for Square {
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 /// A fixed size board
+///
+/// A board using these dimensions must have a `BOARD_SIZE` of exactly `W * H`, anything else
+/// fails to compile as soon as the board is built. Converting a game, grid or bytes of any other
+/// size fails with `ConversionError::BoardTooLarge`
 pub struct Fixed<const W: u8, const H: u8>;
 
 impl<const W: u8, const H: u8> Dimensions for Fixed<W, H> {
+    const FIXED: Option<(u8, u8)> = Some((W, H));
+
     fn width(&self) -> u8 {
         W
     }
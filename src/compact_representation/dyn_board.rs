@@ -0,0 +1,82 @@
+//! An object safe view of compact boards, which erases their size and number of snakes
+//!
+//! The compact boards are generic over `BOARD_SIZE` and `MAX_SNAKES`, which makes it hard to keep
+//! boards of different sizes in one collection. Every compact board implements [DynCellBoard], so
+//! they can all be held as `Box<dyn DynCellBoard>`. Positions are exposed as wire
+//! [Position]s because the native `CellIndex` type depends on the board size.
+
+use std::fmt::{Debug, Display};
+use std::time::Duration;
+
+use crate::types::{Move, SimulatorInstruments, SnakeId};
+use crate::wire_representation::Position;
+
+/// A compact board of any size, behind an object safe interface
+pub trait DynCellBoard: Debug + Display + Send + Sync {
+    /// the width of the board
+    fn width(&self) -> u32;
+
+    /// the height of the board
+    fn height(&self) -> u32;
+
+    /// the ids of every living snake
+    fn snake_ids(&self) -> Vec<SnakeId>;
+
+    /// the id of your snake
+    fn you_id(&self) -> SnakeId;
+
+    /// the health of the given snake, 0 if it is dead
+    fn health(&self, id: SnakeId) -> u8;
+
+    /// the length of the given snake, 0 if it is dead
+    fn length(&self, id: SnakeId) -> u16;
+
+    /// the head of the given snake
+    fn head(&self, id: SnakeId) -> Position;
+
+    /// the body of the given snake, head first, with stacked segments repeated
+    fn body(&self, id: SnakeId) -> Vec<Position>;
+
+    /// every food on the board
+    fn food(&self) -> Vec<Position>;
+
+    /// whether the given position is a hazard
+    fn is_hazard(&self, pos: Position) -> bool;
+
+    /// whether the game is over
+    fn is_over(&self) -> bool;
+
+    /// the winner of the game, None for a draw or if the game isn't over
+    fn winner(&self) -> Option<SnakeId>;
+
+    /// the reasonable moves for every living snake
+    fn reasonable_moves(&self) -> Vec<(SnakeId, Vec<Move>)>;
+
+    /// simulates every combination of the given moves, see `SimulableGame::simulate_with_moves`.
+    /// Each result holds the move every simulated snake made, and the resulting board
+    #[allow(clippy::type_complexity)]
+    fn simulate_with_moves(
+        &self,
+        instruments: &dyn SimulatorInstruments,
+        moves: &[(SnakeId, Vec<Move>)],
+    ) -> Vec<(Vec<(SnakeId, Move)>, Box<dyn DynCellBoard>)>;
+
+    /// copies this board in to a new box
+    fn clone_box(&self) -> Box<dyn DynCellBoard>;
+}
+
+impl Clone for Box<dyn DynCellBoard> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Adapts a `&dyn SimulatorInstruments` to the sized instruments the simulator expects
+#[derive(Debug)]
+pub(crate) struct DynInstruments<'a>(pub(crate) &'a dyn SimulatorInstruments);
+
+impl SimulatorInstruments for DynInstruments<'_> {
+    fn observe_simulation(&self, duration: Duration) {
+        self.0.observe_simulation(duration)
+    }
+}
@@ -0,0 +1,123 @@
+//! Tracks which cells snakes have occupied over the course of a game
+//!
+//! A compact board only knows the current state. [BoardWithHistory] is fed every board as the
+//! game progresses and remembers how many turns ago each cell last held a snake, which is useful
+//! for avoiding oscillation or for exploration heuristics.
+
+use crate::types::{
+    HealthGettableGame, PositionGettableGame, SnakeBodyGettableGame, SnakeIDGettableGame, SnakeId,
+};
+
+use super::{CellIndex, CellNum};
+
+/// A board along with how long ago every cell was last occupied by any snake
+#[derive(Debug, Clone)]
+pub struct BoardWithHistory<B> {
+    board: B,
+    /// turns since a snake was last on each cell, indexed by cell. Grows as cells are visited
+    ages: Vec<Option<u16>>,
+}
+
+impl<B, T> BoardWithHistory<B>
+where
+    T: CellNum,
+    B: SnakeIDGettableGame<SnakeIDType = SnakeId>
+        + SnakeBodyGettableGame
+        + HealthGettableGame
+        + PositionGettableGame<NativePositionType = CellIndex<T>>,
+{
+    /// starts tracking from `board`, every snake cell on it has an age of 0
+    pub fn new(board: B) -> Self {
+        let mut history = Self {
+            board,
+            ages: Vec::new(),
+        };
+        history.record();
+        history
+    }
+
+    /// Moves on to the next board. Every cell a snake was seen on gets a turn older, then the
+    /// cells of every living snake on `board` are reset to an age of 0
+    pub fn push(&mut self, board: B) {
+        for age in self.ages.iter_mut().flatten() {
+            *age = age.saturating_add(1);
+        }
+        self.board = board;
+        self.record();
+    }
+
+    /// how many boards ago a snake was last on this cell: 0 if one is on it in the current
+    /// board, `None` if no snake has been on it since tracking started
+    pub fn visit_age(&self, idx: CellIndex<T>) -> Option<u16> {
+        self.ages.get(idx.as_usize()).copied().flatten()
+    }
+
+    /// the most recently pushed board
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+
+    fn record(&mut self) {
+        for sid in self.board.get_snake_ids() {
+            if !self.board.is_alive(&sid) {
+                continue;
+            }
+            for idx in self.board.get_snake_body_iter(&sid) {
+                let idx = idx.as_usize();
+                if idx >= self.ages.len() {
+                    self.ages.resize(idx + 1, None);
+                }
+                self.ages[idx] = Some(0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoardWithHistory;
+    use crate::{
+        compact_representation::{standard::CellBoard4Snakes11x11, CellIndex},
+        game_fixture,
+        types::{
+            build_snake_id_map, HeadGettableGame, Move, SimulableGame, SimulatorInstruments,
+            SnakeId,
+        },
+        wire_representation::Position,
+    };
+
+    #[derive(Debug)]
+    struct Instruments;
+    impl SimulatorInstruments for Instruments {
+        fn observe_simulation(&self, _: std::time::Duration) {}
+    }
+
+    #[test]
+    fn test_visit_ages() {
+        let g = game_fixture(include_str!("../../fixtures/start_of_game.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let board: CellBoard4Snakes11x11 = g.as_cell_board(&snake_ids).unwrap();
+        let start = board.get_head_as_native_position(&SnakeId(0));
+        let mut history = BoardWithHistory::new(board);
+        assert_eq!(history.visit_age(start), Some(0));
+        assert_eq!(history.visit_age(CellIndex(5 * 11 + 5)), None);
+
+        // we start at (5, 8) and head left, eating the food at (4, 8) on the way
+        for turn in 1..=4 {
+            let (_, next) = history
+                .board()
+                .simulate_with_moves(&Instruments, vec![(SnakeId(0), [Move::Left])])
+                .next()
+                .unwrap();
+            history.push(next);
+            let head = history.board().get_head_as_position(&SnakeId(0));
+            assert_eq!(head, Position { x: 5 - turn, y: 8 });
+        }
+        // having eaten, the tail only left the starting cell on the last turn
+        assert_eq!(history.visit_age(start), Some(1));
+        assert_eq!(
+            history.visit_age(history.board().get_head_as_native_position(&SnakeId(0))),
+            Some(0)
+        );
+    }
+}
@@ -1,10 +1,36 @@
 //! A compact board representation that is efficient for simulation
 mod core;
+mod dyn_board;
+mod history;
 pub mod standard;
 pub mod wrapped;
 
+#[cfg(feature = "bitboard")]
+pub use self::core::BitBoard;
+#[cfg(feature = "bitboard")]
+pub use self::core::BitBoardWords;
+pub use self::core::CellContents;
 pub use self::core::CellIndex;
 pub use self::core::CellNum;
+pub use self::core::ConformanceError;
+pub use self::core::ConversionError;
+pub use self::core::DangerWeights;
+pub use self::core::EliminationCause;
+pub use self::core::FoodHealthMode;
+pub use self::core::GameMode;
+pub use self::core::HeadToHeadPolicy;
+pub use self::core::MoveLegality;
+pub use self::core::RulesetSettings;
+pub use self::core::SimEvent;
+pub use self::core::SimSettings;
+pub use self::core::SimTrace;
+pub use self::core::SinkholeSchedule;
+pub use self::core::Symmetry;
+pub use self::core::SymmetryGroup;
+pub use self::core::MAX_HAZARD_STACK;
+pub use self::dyn_board::DynCellBoard;
+pub(crate) use self::dyn_board::DynInstruments;
+pub use self::history::BoardWithHistory;
 
 use self::dimensions::Square;
 
@@ -1,5 +1,6 @@
 //! A compact board representation that is efficient for simulation
 use crate::compact_representation::core::CellNum as CN;
+use crate::impl_best_cell_board_dispatch;
 use crate::impl_common_board_traits;
 use crate::types::*;
 /// you almost certainly want to use the `convert_from_game` method to
@@ -21,7 +22,7 @@ use crate::{
 
 use super::core::CellBoard as CCB;
 use super::core::CellIndex;
-use super::core::{simulate_with_moves, EvaluateMode};
+use super::core::{simulate_with_moves, CellContents, ConversionError, EvaluateMode, GameMode};
 use super::dimensions::{ArcadeMaze, Custom, Dimensions, Fixed, Square};
 
 /// A compact board representation that is significantly faster for simulation than
@@ -46,7 +47,12 @@ pub type CellBoard8Snakes15x15 = CellBoard<u8, Square, { 15 * 15 }, 8>;
 /// Used to represent the largest UI Selectable board with 8 snakes.
 pub type CellBoard8Snakes25x25 = CellBoard<u16, Custom, { 25 * 25 }, 8>;
 
-/// Used to represent an absolutely silly game board
+/// Used to represent an absolutely silly game board.
+///
+/// Boards store their cells inline so they can be copied cheaply while simulating, so this one
+/// is about 10KiB and conversion and every simulated move put a copy of it on the stack. That is
+/// fine for the default thread stacks, but keep it in mind for deep recursive searches on threads
+/// with small stacks. `BestCellBoard` boxes the board once it is built
 pub type CellBoard16Snakes50x50 = CellBoard<u16, Custom, { 50 * 50 }, 16>;
 
 impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
@@ -54,16 +60,104 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
 {
     /// Builds a cellboard from a given game, will return an error if the game doesn't match
     /// the provided BOARD_SIZE or MAX_SNAKES. You are encouraged to use `CellBoard4Snakes11x11`
-    /// for the common game layout
-    pub fn convert_from_game(game: Game, snake_ids: &SnakeIDMap) -> Result<Self, Box<dyn Error>> {
-        if game.game.ruleset.name == "wrapped" {
-            return Err("Wrapped games are not supported".into());
+    /// for the common game layout. If you don't need the id map afterwards, `TryFrom<Game>`
+    /// builds it for you. Wrapped games are rejected with `ConversionError::UnsupportedRuleset`
+    pub fn convert_from_game(game: Game, snake_ids: &SnakeIDMap) -> Result<Self, ConversionError> {
+        if game.is_wrapped() {
+            return Err(ConversionError::UnsupportedRuleset {
+                name: game.game.ruleset.name,
+            });
         }
 
         let embedded = CCB::convert_from_game(game, snake_ids)?;
         Ok(CellBoard { embedded })
     }
 
+    /// Builds a cellboard like `convert_from_game`, but without rejecting wrapped games. The
+    /// board treats its edges as walls, so simulation semantics may be wrong for wrapped games.
+    /// Only use this for read-only tooling, like rendering a wrapped replay or reading its food
+    pub fn convert_from_game_unchecked_mode(
+        game: Game,
+        snake_ids: &SnakeIDMap,
+    ) -> Result<Self, ConversionError> {
+        let embedded = CCB::convert_from_game(game, snake_ids)?;
+        Ok(CellBoard { embedded })
+    }
+
+    /// Builds a board from rows of cells indexed as `grid[y][x]`, the inverse of `to_grid`.
+    ///
+    /// Every snake gets 100 health and `SnakeId(0)` is you. Each snake's cells must form a chain
+    /// ordered by `segment`: the head is segment 0, every following cell's segment is the previous
+    /// cell's segment plus its `stack`, and consecutive cells must be adjacent. Only the tail may be
+    /// stacked, and a stack of 3 is only valid for a snake that occupies a single cell. Anything
+    /// else is rejected with `ConversionError::MalformedSnake`
+    pub fn from_grid(
+        grid: &[Vec<CellContents>],
+        hazard_damage: u8,
+    ) -> Result<Self, ConversionError> {
+        let embedded = CCB::from_grid(grid, hazard_damage, EvaluateMode::Standard)?;
+        Ok(CellBoard { embedded })
+    }
+
+    /// Cuts out the `width` by `height` region whose top left cell, as the board is drawn, is
+    /// `top_left`, so the region covers `top_left.y` and the `height - 1` rows below it. The
+    /// region is shifted to start at `(0, 0)` and put in the smallest board it fits, so a search
+    /// focused on one corner of a large board can run on a much cheaper one.
+    ///
+    /// Snakes partly outside the region are dropped along with the ones entirely outside it.
+    /// `None` if you aren't entirely inside the region or the region doesn't fit on this board.
+    /// Snake ids are renumbered in the cropped board, with you as `SnakeId(0)`
+    pub fn crop(&self, top_left: Position, width: u8, height: u8) -> Option<BestCellBoard> {
+        let (left, top) = (top_left.x, top_left.y);
+        let bottom = top - height as i32 + 1;
+        if width == 0
+            || height == 0
+            || left < 0
+            || bottom < 0
+            || left + width as i32 > self.embedded.get_actual_width() as i32
+            || top >= self.embedded.get_actual_height() as i32
+        {
+            return None;
+        }
+        let inside = |pos: &Position| {
+            (left..left + width as i32).contains(&pos.x) && (bottom..=top).contains(&pos.y)
+        };
+        let shift = |pos: &Position| Position::new(pos.x - left, pos.y - bottom);
+        let crop_positions = |positions: &[Position]| {
+            positions
+                .iter()
+                .filter(|pos| inside(pos))
+                .map(shift)
+                .collect_vec()
+        };
+
+        let mut game = self.to_wire_game(&SnakeIDMap::new());
+        if !game.you.body.iter().all(inside) {
+            return None;
+        }
+        game.board
+            .snakes
+            .retain(|snake| snake.body.iter().all(inside));
+        for snake in game.board.snakes.iter_mut().chain(Some(&mut game.you)) {
+            snake.body = snake.body.iter().map(shift).collect();
+            snake.head = shift(&snake.head);
+        }
+        game.board.food = crop_positions(&game.board.food);
+        game.board.hazards = crop_positions(&game.board.hazards);
+        game.board.width = width as u32;
+        game.board.height = height as u32;
+
+        game.to_best_cell_board().ok()
+    }
+
+    fn evaluate_mode(&self) -> EvaluateMode {
+        if self.embedded.game_mode() == Some(GameMode::Solo) {
+            EvaluateMode::Solo
+        } else {
+            EvaluateMode::Standard
+        }
+    }
+
     fn off_board(&self, new_head: Position) -> bool {
         new_head.x < 0
             || new_head.x >= self.embedded.get_actual_width() as i32
@@ -98,30 +192,27 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> Rea
         &self,
     ) -> Box<dyn std::iter::Iterator<Item = (SnakeId, Vec<Move>)> + '_> {
         let width = self.embedded.get_actual_width();
-        Box::new(
-            self.embedded
-                .iter_healths()
-                .enumerate()
-                .filter(|(_, health)| **health > 0)
-                .map(move |(idx, _)| {
-                    let head_pos = self.get_head_as_position(&SnakeId(idx as u8));
-
-                    let mvs = IntoIterator::into_iter(Move::all())
-                        .filter(|mv| {
-                            let new_head = head_pos.add_vec(mv.to_vector());
-                            let ci = CellIndex::new(new_head, width);
-
-                            !self.off_board(new_head)
-                                && (!self.embedded.cell_is_body(ci)
-                                    || self.embedded.cell_is_single_tail(ci))
-                                && !self.embedded.cell_is_snake_head(ci)
-                        })
-                        .collect_vec();
-                    let mvs = if mvs.is_empty() { vec![Move::Up] } else { mvs };
-
-                    (SnakeId(idx as u8), mvs)
-                }),
-        )
+        Box::new(self.embedded.alive_heads().map(move |(sid, head)| {
+            let head_pos = head.into_position(width);
+
+            let mvs = IntoIterator::into_iter(Move::all())
+                .filter(|mv| {
+                    let new_head = head_pos.add_vec(mv.to_vector());
+                    let ci = CellIndex::new(new_head, width);
+
+                    !self.off_board(new_head) && self.embedded.will_be_free_next_turn(ci)
+                })
+                .collect_vec();
+            // with no reasonable move every move is fatal, so offer all of them rather than
+            // always picking the same one
+            let mvs = if mvs.is_empty() {
+                Move::all().to_vec()
+            } else {
+                mvs
+            };
+
+            (sid, mvs)
+        }))
     }
 }
 
@@ -164,7 +255,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     fn possible_moves<'a>(
         &'a self,
         pos: &Self::NativePositionType,
-    ) -> Box<(dyn std::iter::Iterator<Item = (Move, CellIndex<T>)> + 'a)> {
+    ) -> Box<dyn std::iter::Iterator<Item = (Move, CellIndex<T>)> + 'a> {
         let width = self.embedded.get_actual_width();
         let head_pos = pos.into_position(width);
 
@@ -184,7 +275,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     fn neighbors<'a>(
         &'a self,
         pos: &Self::NativePositionType,
-    ) -> Box<(dyn Iterator<Item = CellIndex<T>> + 'a)> {
+    ) -> Box<dyn Iterator<Item = CellIndex<T>> + 'a> {
         let width = self.embedded.get_actual_width();
         let head_pos = pos.into_position(width);
 
@@ -227,41 +318,89 @@ pub enum BestCellBoard {
     Silly(Box<CellBoard16Snakes50x50>),
 }
 
+impl_best_cell_board_dispatch!(BestCellBoard);
+
 /// Trait to get the best sized cellboard for the given game. It returns the smallest Compact board
 /// that has enough room to fit the given Wire game. If the game can't fit in any of our Compact
 /// boards we panic. However the largest board available is MUCH larger than the biggest selectable
 /// board in the Battlesnake UI
+///
+/// The boards are tried in this order and the first one the game fits in is used. If you want
+/// your own ladder, e.g. to keep your enum small, these are the thresholds to copy
+///
+/// | variant | width x height | snakes | cells |
+/// |---|---|---|---|
+/// | `SmallExact` | exactly 7x7 | up to 4 | u8 |
+/// | `Tiny` | square, up to 7x7 | up to 4 | u8 |
+/// | `MediumExact` | exactly 11x11 | up to 4 | u8 |
+/// | `Standard` | square, up to 11x11 | up to 4 | u8 |
+/// | `LargestU8` | square, up to 15x15 | up to 8 | u8 |
+/// | `LargeExact` | exactly 19x19 | up to 4 | u16 |
+/// | `ArcadeMaze` | exactly 19x21 | up to 4 | u16 |
+/// | `ArcadeMaze8Snake` | exactly 19x21 | up to 8 | u16 |
+/// | `Large` | up to 25 wide and 24 high | up to 8 | u16 |
+/// | `Silly` | up to 50x50 | up to 16 | u16 |
 pub trait ToBestCellBoard {
     #[allow(missing_docs)]
     fn to_best_cell_board(self) -> Result<BestCellBoard, Box<dyn Error>>;
+
+    /// Like `to_best_cell_board`, but skips every board with more than `MAX_CELLS` cells. A game
+    /// that only fits in a bigger board returns `ConversionError::BoardTooLarge`, so
+    /// `to_best_cell_board_up_to::<{ 15 * 15 }>()` only ever gives the u8 boards
+    fn to_best_cell_board_up_to<const MAX_CELLS: usize>(
+        self,
+    ) -> Result<BestCellBoard, Box<dyn Error>>;
 }
 
 impl ToBestCellBoard for Game {
     fn to_best_cell_board(self) -> Result<BestCellBoard, Box<dyn Error>> {
+        self.to_best_cell_board_up_to::<{ usize::MAX }>()
+    }
+
+    fn to_best_cell_board_up_to<const MAX_CELLS: usize>(
+        self,
+    ) -> Result<BestCellBoard, Box<dyn Error>> {
         let width = self.board.width;
         let height = self.board.height;
         let num_snakes = self.board.snakes.len();
         let id_map = build_snake_id_map(&self);
+        let fits = |cells: usize| {
+            if cells > MAX_CELLS {
+                Err(ConversionError::BoardTooLarge { width, height })
+            } else {
+                Ok(())
+            }
+        };
 
         let best_board = if width == 7 && height == 7 && num_snakes <= 4 {
+            fits(7 * 7)?;
             BestCellBoard::SmallExact(Box::new(CellBoard::convert_from_game(self, &id_map)?))
-        } else if width <= 7 && height <= 7 && num_snakes <= 4 {
+        } else if width == height && width <= 7 && num_snakes <= 4 {
+            fits(7 * 7)?;
             BestCellBoard::Tiny(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 11 && height == 11 && num_snakes <= 4 {
+            fits(11 * 11)?;
             BestCellBoard::MediumExact(Box::new(CellBoard::convert_from_game(self, &id_map)?))
-        } else if width <= 11 && height <= 11 && num_snakes <= 4 {
+        } else if width == height && width <= 11 && num_snakes <= 4 {
+            fits(11 * 11)?;
             BestCellBoard::Standard(Box::new(CellBoard::convert_from_game(self, &id_map)?))
-        } else if width <= 15 && height <= 15 && num_snakes <= 8 {
+        } else if width == height && width <= 15 && num_snakes <= 8 {
+            fits(15 * 15)?;
             BestCellBoard::LargestU8(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 19 && height == 19 && num_snakes <= 4 {
+            fits(19 * 19)?;
             BestCellBoard::LargeExact(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 19 && height == 21 && num_snakes <= 4 {
+            fits(19 * 21)?;
             BestCellBoard::ArcadeMaze(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 19 && height == 21 && num_snakes <= 8 {
+            fits(19 * 21)?;
             BestCellBoard::ArcadeMaze8Snake(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width <= 25 && height < 25 && num_snakes <= 8 {
+            fits(25 * 25)?;
             BestCellBoard::Large(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width <= 50 && height <= 50 && num_snakes <= 16 {
+            fits(50 * 50)?;
             BestCellBoard::Silly(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else {
             panic!("No board was big enough")
@@ -278,7 +417,12 @@ mod test {
 
     use super::*;
     use crate::{
-        compact_representation::core::Cell, game_fixture, types::build_snake_id_map,
+        compact_representation::{
+            core::Cell, CellContents, ConformanceError, DangerWeights, EliminationCause,
+            FoodHealthMode, GameMode, RulesetSettings, SimEvent, SimSettings, Symmetry,
+        },
+        game_fixture,
+        types::build_snake_id_map,
         wire_representation::Game as DEGame,
     };
     #[derive(Debug)]
@@ -287,6 +431,27 @@ mod test {
         fn observe_simulation(&self, _: std::time::Duration) {}
     }
 
+    /// Writes a snake in to a `grid[y][x]` grid, one unstacked segment per `(x, y)`, head first
+    fn place_snake(grid: &mut [Vec<CellContents>], id: u8, body: &[(usize, usize)]) {
+        for (segment, (x, y)) in body.iter().enumerate() {
+            grid[*y][*x] = CellContents::Snake {
+                id: SnakeId(id),
+                segment: segment as u16,
+                stack: 1,
+                hazards: 0,
+            };
+        }
+    }
+
+    /// An empty `size` by `size` grid for `from_grid` holding each snake as `place_snake` would
+    fn grid_with_snakes(size: usize, snakes: &[(u8, &[(usize, usize)])]) -> Vec<Vec<CellContents>> {
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; size]; size];
+        for (id, body) in snakes {
+            place_snake(&mut grid, *id, body);
+        }
+        grid
+    }
+
     #[test]
     fn test_compact_board_conversion() {
         let start_of_game_fixture =
@@ -319,6 +484,265 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_try_from_game() {
+        use std::convert::TryInto;
+
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let expected =
+            CellBoard4Snakes11x11::convert_from_game(g.clone(), &snake_id_mapping).unwrap();
+        let board: CellBoard4Snakes11x11 = g.try_into().unwrap();
+        assert_eq!(board, expected);
+
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let too_small: Result<CellBoard4Snakes7x7, _> = g.try_into();
+        assert!(too_small.is_err());
+    }
+
+    #[test]
+    fn test_convert_with_map() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let expected =
+            CellBoard4Snakes11x11::convert_from_game(g.clone(), &snake_id_mapping).unwrap();
+        let (board, map) = CellBoard4Snakes11x11::convert_with_map(g.clone()).unwrap();
+        assert_eq!(board, expected);
+        assert_eq!(map, snake_id_mapping);
+        assert_eq!(map[&g.you.id], SnakeId(0));
+    }
+
+    #[test]
+    fn test_best_cell_board_up_to() {
+        let standard = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        match standard.clone().to_best_cell_board_up_to::<{ 15 * 15 }>() {
+            Ok(BestCellBoard::MediumExact(_)) => {}
+            _ => panic!("expected standard board"),
+        }
+
+        let err = standard
+            .to_best_cell_board_up_to::<{ 7 * 7 }>()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ConversionError>(),
+            Some(&ConversionError::BoardTooLarge {
+                width: 11,
+                height: 11
+            })
+        );
+    }
+
+    #[test]
+    fn test_convert_silly_board_on_a_small_stack() {
+        let mut g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        g.board.width = 50;
+        g.board.height = 50;
+        // a 50x50 board is about 10KiB, so a few copies fit comfortably in a small stack
+        assert!(std::mem::size_of::<CellBoard16Snakes50x50>() < 11 * 1024);
+
+        let converted = std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(move || match Game::to_best_cell_board(g) {
+                Ok(BestCellBoard::Silly(board)) => board.get_snake_ids().len(),
+                _ => panic!("expected silly board"),
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(converted, 4);
+    }
+
+    #[test]
+    fn test_best_cell_board_with_board() {
+        let tiny = game_fixture(include_str!("../../../fixtures/7x7board.json"));
+        let tiny_ids = build_snake_id_map(&tiny);
+        let expected: CellBoard<u8, Fixed<7, 7>, { 7 * 7 }, 4> =
+            CellBoard::convert_from_game(tiny.clone(), &tiny_ids).unwrap();
+        let best = Game::to_best_cell_board(tiny).unwrap();
+
+        assert_eq!(best.with_board(|b| (b.width(), b.height())), (7, 7));
+        assert_eq!(best.with_board(|b| b.to_string()), expected.to_string());
+        assert_eq!(
+            best.with_board(|b| b.is_over()),
+            VictorDeterminableGame::is_over(&expected)
+        );
+        assert_eq!(best.into_dyn().snake_ids(), expected.get_snake_ids());
+    }
+
+    #[test]
+    fn test_duplicate_snake_ids_are_rejected() {
+        let mut g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let duplicate = g.board.snakes[0].id.clone();
+        g.board.snakes[1].id = duplicate.clone();
+        let snake_id_mapping = build_snake_id_map(&g);
+
+        let err = CellBoard4Snakes11x11::convert_from_game(g, &snake_id_mapping).unwrap_err();
+        assert_eq!(err, ConversionError::DuplicateSnakeId { id: duplicate });
+    }
+
+    #[test]
+    fn test_as_cell_board_rejects_other_rulesets() {
+        let wrapped = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_id_mapping = build_snake_id_map(&wrapped);
+        let err: ConversionError = wrapped
+            .as_cell_board::<u8, Square, { 11 * 11 }, 4>(&snake_id_mapping)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ConversionError::UnsupportedRuleset {
+                name: "wrapped".to_string()
+            }
+        );
+        assert!(wrapped
+            .as_wrapped_cell_board::<u8, Square, { 11 * 11 }, 4>(&snake_id_mapping)
+            .is_ok());
+
+        let standard = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&standard);
+        let err = standard
+            .as_wrapped_cell_board::<u8, Square, { 11 * 11 }, 4>(&snake_id_mapping)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ConversionError::UnsupportedRuleset {
+                name: standard.game.ruleset.name.clone()
+            }
+        );
+    }
+
+    #[test]
+    fn test_head_body_mismatch_is_rejected() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id = g.board.snakes[0].id.clone();
+        let snake_id_mapping = build_snake_id_map(&g);
+
+        let mut moved_head = g.clone();
+        moved_head.board.snakes[0].head.x += 1;
+        let err =
+            CellBoard4Snakes11x11::convert_from_game(moved_head, &snake_id_mapping).unwrap_err();
+        assert_eq!(
+            err,
+            ConversionError::HeadBodyMismatch {
+                id: snake_id.clone()
+            }
+        );
+
+        // a head that matches the body but is off the board is rejected before it is placed
+        let mut off_board = g.clone();
+        off_board.board.snakes[0].head.x = -1;
+        off_board.board.snakes[0].body[0].x = -1;
+        let err =
+            CellBoard4Snakes11x11::convert_from_game(off_board, &snake_id_mapping).unwrap_err();
+        assert_eq!(
+            err,
+            ConversionError::BodyOffBoard {
+                id: snake_id.clone()
+            }
+        );
+
+        let mut doubled_head = g;
+        let head = doubled_head.board.snakes[0].head;
+        doubled_head.board.snakes[0].body.push_front(head);
+        let err =
+            CellBoard4Snakes11x11::convert_from_game(doubled_head, &snake_id_mapping).unwrap_err();
+        assert_eq!(err, ConversionError::BadBodyStack { id: snake_id });
+    }
+
+    #[test]
+    fn test_body_off_board_is_rejected() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id = g.board.snakes[0].id.clone();
+        let snake_id_mapping = build_snake_id_map(&g);
+
+        for tail in [
+            Position::new(-1, 0),
+            Position::new(0, 11),
+            Position::new(11, 3),
+        ] {
+            let mut off_board = g.clone();
+            *off_board.board.snakes[0].body.back_mut().unwrap() = tail;
+            let err =
+                CellBoard4Snakes11x11::convert_from_game(off_board.clone(), &snake_id_mapping)
+                    .unwrap_err();
+            assert_eq!(
+                err,
+                ConversionError::BodyOffBoard {
+                    id: snake_id.clone()
+                }
+            );
+            assert!(Game::to_best_cell_board(off_board).is_err());
+        }
+    }
+
+    #[test]
+    fn test_validate_game_reports_every_problem() {
+        let mut g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        assert!(CellBoard4Snakes11x11::validate_game(&g).is_empty());
+
+        let first = g.board.snakes[0].id.clone();
+        let second = g.board.snakes[1].id.clone();
+        g.board.snakes[0].body.back_mut().unwrap().x = -1;
+        g.board.snakes[1].head.y += 1;
+        g.board.snakes.push(g.board.snakes[1].clone());
+
+        assert_eq!(
+            CellBoard4Snakes7x7::validate_game(&g),
+            vec![
+                ConversionError::BoardTooLarge {
+                    width: 11,
+                    height: 11
+                },
+                ConversionError::DuplicateSnakeId { id: second.clone() },
+                ConversionError::BodyOffBoard { id: first },
+                ConversionError::HeadBodyMismatch { id: second.clone() },
+                ConversionError::HeadBodyMismatch { id: second },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping_bodies_are_rejected() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let first = g.board.snakes[0].id.clone();
+        let second = g.board.snakes[1].id.clone();
+
+        let mut overlapping = g.clone();
+        let shared = overlapping.board.snakes[0].body[2];
+        overlapping.board.snakes[1].body.push_back(shared);
+        let err = CellBoard4Snakes11x11::convert_from_game(overlapping.clone(), &snake_id_mapping)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ConversionError::OverlappingBodies {
+                a: first,
+                b: second
+            }
+        );
+
+        // dead snakes aren't placed, so they can't overlap
+        overlapping.board.snakes[0].health = 0;
+        assert!(CellBoard4Snakes11x11::convert_from_game(overlapping, &snake_id_mapping).is_ok());
+
+        // food and hazards under a body are fine
+        let mut under_body = g;
+        under_body.board.food.push(under_body.you.body[1]);
+        under_body.board.hazards.push(under_body.you.body[1]);
+        assert!(CellBoard4Snakes11x11::validate_game(&under_body).is_empty());
+    }
+
+    #[test]
+    fn test_cell_index_from_xy() {
+        let idx = CellIndex::<u8>::from_xy(4, 6, 11);
+        assert_eq!(idx, CellIndex::new(Position { x: 4, y: 6 }, 11));
+        assert_eq!(idx.to_xy(11), (4, 6));
+        for i in 0..121 {
+            let idx = CellIndex::<u8>::from_usize(i);
+            let (x, y) = idx.to_xy(11);
+            assert_eq!(CellIndex::from_xy(x, y, 11), idx);
+        }
+    }
+
     #[test]
     fn test_head_gettable() {
         let game_fixture = include_str!("../../../fixtures/late_stage.json");
@@ -336,6 +760,165 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_eating_stacks_the_tail_for_the_next_turn() {
+        let mut grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(2, 2), (2, 1), (2, 0)]),
+                (1, &[(3, 0), (4, 0), (4, 1)]),
+            ],
+        );
+        grid[2][1] = CellContents::Food { hazards: 0 };
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        let at = |x, y| CellIndex::new(Position { x, y }, 7);
+
+        // we eat while snake 1 moves in to the cell our tail leaves. Movement happens before
+        // feeding, so our old tail is vacated this turn and the new segment stacks on the cell
+        // in front of it
+        let (_, ate) = compact
+            .simulate_with_moves(
+                &Instruments,
+                vec![(SnakeId(0), [Move::Left]), (SnakeId(1), [Move::Left])],
+            )
+            .next()
+            .unwrap();
+        assert!(ate.is_alive(&SnakeId(1)));
+        assert_eq!(ate.get_head_as_native_position(&SnakeId(1)), at(2, 0));
+        assert_eq!(ate.get_length(&SnakeId(0)), 4);
+        assert!(ate.just_ate(&SnakeId(0)));
+        assert!(matches!(
+            ate.what_is_at(at(2, 1)),
+            CellContents::Snake { stack: 2, .. }
+        ));
+        assert!(!ate.will_be_free_next_turn(at(2, 1)));
+
+        // on the next turn the stacked tail stays put, so moving in to it is fatal
+        let (_, next) = ate
+            .simulate_with_moves(
+                &Instruments,
+                vec![(SnakeId(0), [Move::Up]), (SnakeId(1), [Move::Up])],
+            )
+            .next()
+            .unwrap();
+        assert!(next.is_alive(&SnakeId(0)));
+        assert!(!next.is_alive(&SnakeId(1)));
+        assert!(matches!(
+            next.what_is_at(at(2, 1)),
+            CellContents::Snake {
+                id: SnakeId(0),
+                stack: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_step_once_matches_simulate_with_moves() {
+        for fixture in [
+            include_str!("../../../fixtures/start_of_game.json"),
+            include_str!("../../../fixtures/late_stage.json"),
+        ] {
+            let g = game_fixture(fixture);
+            let snake_id_mapping = build_snake_id_map(&g);
+            let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+            let living = compact.get_snake_ids();
+
+            for joint in living.iter().map(|_| Move::all()).multi_cartesian_product() {
+                let mut moves = [Move::Up; 4];
+                for (id, mv) in living.iter().zip(joint.iter()) {
+                    moves[id.as_usize()] = *mv;
+                }
+                let (_, expected) = compact
+                    .simulate_with_moves(
+                        &Instruments,
+                        living.iter().zip(joint.iter()).map(|(id, mv)| (*id, [*mv])),
+                    )
+                    .next()
+                    .unwrap();
+                assert_eq!(compact.step_once(moves), expected, "{:?}", joint);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulation_is_deterministic() {
+        for fixture in [
+            include_str!("../../../fixtures/start_of_game.json"),
+            include_str!("../../../fixtures/late_stage.json"),
+        ] {
+            let successors = || {
+                let g = game_fixture(fixture);
+                let snake_id_mapping = build_snake_id_map(&g);
+                let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+                compact
+                    .successors(&Instruments)
+                    .into_iter()
+                    .map(|(action, board)| (action, board.to_bytes()))
+                    .collect_vec()
+            };
+
+            let expected = successors();
+            for _ in 0..100 {
+                assert_eq!(successors(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eliminated_snakes_are_removed_after_collisions() {
+        let empty = CellContents::Empty { hazards: 0 };
+        let grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(3, 2), (4, 2), (5, 2), (6, 2)]),
+                (1, &[(2, 3), (2, 4), (2, 5)]),
+                (2, &[(1, 4), (0, 4), (0, 5)]),
+            ],
+        );
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        let at = |x, y| CellIndex::new(Position { x, y }, 7);
+
+        // snake 1 loses a head to head with the longer snake 0 while snake 2 runs in to snake
+        // 1's neck. Snake 1 is only removed once every collision is checked, so snake 2 dies too
+        let (_, next) = compact
+            .simulate_with_moves(
+                &Instruments,
+                vec![
+                    (SnakeId(0), [Move::Left]),
+                    (SnakeId(1), [Move::Down]),
+                    (SnakeId(2), [Move::Right]),
+                ],
+            )
+            .next()
+            .unwrap();
+        assert!(next.is_alive(&SnakeId(0)));
+        assert!(!next.is_alive(&SnakeId(1)));
+        assert!(!next.is_alive(&SnakeId(2)));
+        assert_eq!(next.get_head_as_native_position(&SnakeId(0)), at(2, 2));
+        assert_eq!(next.what_is_at(at(2, 4)), empty);
+        assert_eq!(next.what_is_at(at(0, 4)), empty);
+
+        // the same holds when snake 1 turns back in to its own neck instead
+        let (_, next) = compact
+            .simulate_with_moves(
+                &Instruments,
+                vec![
+                    (SnakeId(0), [Move::Left]),
+                    (SnakeId(1), [Move::Up]),
+                    (SnakeId(2), [Move::Right]),
+                ],
+            )
+            .next()
+            .unwrap();
+        assert!(next.is_alive(&SnakeId(0)));
+        assert!(!next.is_alive(&SnakeId(1)));
+        assert!(!next.is_alive(&SnakeId(2)));
+        assert_eq!(next.get_head_as_native_position(&SnakeId(0)), at(2, 2));
+        assert_eq!(next.what_is_at(at(2, 3)), empty);
+        assert_eq!(next.what_is_at(at(2, 4)), empty);
+    }
+
     #[test]
     fn test_tail_collision() {
         let game_fixture = include_str!("../../../fixtures/start_of_game.json");
@@ -364,129 +947,2409 @@ mod test {
     }
 
     #[test]
-    fn test_set_hazard() {
-        let mut c: Cell<u8> = Cell::empty();
-        c.set_food();
-        assert!(c.is_food());
-        c.set_hazard();
-        assert!(c.is_food());
-        assert!(c.is_hazard());
-        assert!(!c.is_head());
-        assert!(!c.is_body());
-    }
+    fn test_my_successors() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let instruments = Instruments;
 
-    #[test]
-    fn test_clear_hazard() {
-        let mut c: Cell<u8> = Cell::empty();
-        c.set_food();
-        assert!(c.is_food());
-        c.set_hazard();
-        c.clear_hazard();
-        assert!(c.is_food());
-        assert!(!c.is_hazard());
-        assert!(!c.is_head());
-        assert!(!c.is_body());
-        let mut c: Cell<u8> = Cell::make_double_stacked_piece(SnakeId(0), CellIndex(0));
-        c.set_hazard();
-        c.clear_hazard();
-        assert!(c.is_body());
-        assert!(!c.is_hazard());
-    }
+        let mut samples = 0;
+        let next = compact.my_successors(&instruments, SnakeId(0), Move::Up, |_| {
+            samples += 1;
+            [Move::Left, Move::Down, Move::Down, Move::Down]
+        });
+        let expected = compact
+            .simulate_with_moves(
+                &instruments,
+                vec![
+                    (SnakeId(0), [Move::Up]),
+                    (SnakeId(1), [Move::Down]),
+                    (SnakeId(2), [Move::Down]),
+                    (SnakeId(3), [Move::Down]),
+                ],
+            )
+            .next()
+            .unwrap()
+            .1;
 
-    #[test]
-    fn test_remove() {
-        let mut c: Cell<u8> = Cell::make_body_piece(SnakeId(3), CellIndex(17));
-        c.remove();
-        c.set_hazard();
-        assert!(c.is_empty());
-        assert!(c.is_hazard());
-        assert!(c.get_snake_id().is_none());
-        assert!(c.get_idx() == CellIndex(0));
-    }
-    #[test]
-    fn test_set_food() {
-        let mut c: Cell<u8> = Cell::empty();
-        c.set_food();
-        c.set_hazard();
-        assert!(c.is_food());
-        assert!(c.is_hazard());
-        assert!(c.get_snake_id().is_none());
-        assert!(c.get_idx() == CellIndex(0));
+        assert_eq!(samples, 1);
+        assert_eq!(next, expected);
     }
 
     #[test]
-    fn test_set_head() {
-        let mut c: Cell<u8> = Cell::empty();
-        c.set_head(SnakeId(3), CellIndex(17));
-        c.set_hazard();
-        assert!(c.is_head());
-        assert!(c.is_hazard());
-        assert!(c.get_snake_id().unwrap() == SnakeId(3));
-        assert!(c.get_idx() == CellIndex(17));
-    }
+    fn test_simulate_against_model() {
+        use crate::types::AlwaysSafeModel;
 
-    #[test]
-    fn test_food_queryable() {
-        let game_fixture = include_str!("../../../fixtures/late_stage.json");
-        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
-        let g = g.expect("the json literal is valid");
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
         let snake_id_mapping = build_snake_id_map(&g);
         let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let instruments = Instruments;
 
-        assert!(!compact.is_food(&CellIndex(6 * 11 + 4)));
+        let next =
+            compact.simulate_against_model(&instruments, SnakeId(0), Move::Left, &AlwaysSafeModel);
+        let first_reasonable = compact
+            .reasonable_moves_for_each_snake()
+            .map(|(_, moves)| moves[0])
+            .collect_vec();
+        let expected = compact.my_successors(&instruments, SnakeId(0), Move::Left, |_| {
+            [
+                Move::Down,
+                first_reasonable[1],
+                first_reasonable[2],
+                first_reasonable[3],
+            ]
+        });
 
-        assert!(compact.is_food(&CellIndex(2 * 11)));
-        assert!(compact.is_food(&CellIndex(9 * 11)));
-        assert!(compact.is_food(&CellIndex(3 * 11 + 4)));
+        assert_eq!(next, expected);
+        // nobody makes a fatal move
+        assert_eq!(next.get_snake_ids().len(), 4);
     }
 
     #[test]
-    fn test_neighbors_and_possible_moves_start_of_game() {
-        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+    fn test_simulate_surviving() {
+        let game_fixture = include_str!("../../../fixtures/cornered.json");
         let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
         let g = g.expect("the json literal is valid");
         let snake_id_mapping = build_snake_id_map(&g);
         let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let instruments = Instruments;
+        let all_moves = Move::all();
 
-        let head = compact.get_head_as_native_position(&SnakeId(0));
-        assert_eq!(head, CellIndex(8 * 11 + 5));
-
-        let expected_possible_moves = vec![
-            (Move::Up, CellIndex(9 * 11 + 5)),
-            (Move::Down, CellIndex(7 * 11 + 5)),
-            (Move::Left, CellIndex(8 * 11 + 4)),
-            (Move::Right, CellIndex(8 * 11 + 6)),
-        ];
-
-        assert_eq!(
-            compact.possible_moves(&head).collect::<Vec<_>>(),
-            expected_possible_moves
-        );
+        let all_results = compact
+            .simulate_with_moves(&instruments, vec![(SnakeId(0), all_moves.as_slice())])
+            .collect_vec();
+        let surviving = compact
+            .simulate_surviving(
+                &instruments,
+                SnakeId(0),
+                vec![(SnakeId(0), all_moves.as_slice())],
+            )
+            .collect_vec();
 
+        assert!(surviving.len() < all_results.len());
+        assert!(surviving.iter().all(|(_, g)| g.is_alive(&SnakeId(0))));
         assert_eq!(
-            compact.neighbors(&head).collect::<Vec<_>>(),
-            expected_possible_moves
-                .into_iter()
-                .map(|(_, pos)| pos)
-                .collect::<Vec<_>>()
+            surviving.len(),
+            all_results
+                .iter()
+                .filter(|(_, g)| g.is_alive(&SnakeId(0)))
+                .count()
         );
     }
 
     #[test]
-    fn test_neighbors_and_possible_moves_cornered() {
-        let game_fixture = include_str!("../../../fixtures/cornered.json");
+    fn test_board_fullness() {
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
         let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
         let g = g.expect("the json literal is valid");
         let snake_id_mapping = build_snake_id_map(&g);
         let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // 4 snakes with a stacked tail each cover 2 cells
+        assert_eq!(compact.board_fullness(), 8.0 / 121.0);
 
-        let head = compact.get_head_as_native_position(&SnakeId(0));
-        assert_eq!(head, CellIndex(10 * 11));
+        // late_stage has 59 hazards and 11 food, neither of which count
+        let game_fixture = include_str!("../../../fixtures/late_stage.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.board_fullness(), 17.0 / 121.0);
+    }
 
-        let expected_possible_moves = vec![
-            (Move::Down, CellIndex(9 * 11)),
-            (Move::Right, CellIndex(10 * 11 + 1)),
-        ];
+    #[test]
+    fn test_you_is_preserved() {
+        let game_fixture = include_str!("../../../fixtures/late_stage.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let mut snake_id_mapping = build_snake_id_map(&g);
+        let other_id = g
+            .board
+            .snakes
+            .iter()
+            .find(|s| s.id != g.you.id)
+            .unwrap()
+            .id
+            .clone();
+        snake_id_mapping.insert(g.you.id.clone(), SnakeId(1));
+        snake_id_mapping.insert(other_id, SnakeId(0));
+
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.you_id(), &SnakeId(1));
+        assert!(compact.is_you(&SnakeId(1)));
+        assert!(!compact.is_you(&SnakeId(0)));
+        assert_eq!(compact.get_head_as_position(compact.you_id()), g.you.head);
+
+        let packed = compact.embedded.pack_as_hash();
+        let unpacked = CellBoard4Snakes11x11 {
+            embedded: CCB::from_packed_hash(&packed),
+        };
+        assert_eq!(unpacked.you_id(), &SnakeId(1));
+    }
+
+    #[test]
+    fn test_convert_from_game_unchecked_mode() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        assert!(CellBoard4Snakes11x11::convert_from_game(g.clone(), &snake_id_mapping).is_err());
+
+        let compact =
+            CellBoard4Snakes11x11::convert_from_game_unchecked_mode(g.clone(), &snake_id_mapping)
+                .unwrap();
+        assert_eq!(
+            compact.get_all_food_as_positions().len(),
+            g.board.food.len()
+        );
+        assert_eq!(compact.get_head_as_position(&SnakeId(0)), g.you.head);
+    }
+
+    #[test]
+    fn test_game_mode() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.game_mode(), Some(GameMode::Royale));
+        assert_eq!(
+            CellBoard4Snakes11x11::from_bytes(&compact.to_bytes())
+                .unwrap()
+                .game_mode(),
+            Some(GameMode::Royale)
+        );
+
+        let grid = vec![vec![CellContents::Empty { hazards: 0 }; 11]; 11];
+        assert_eq!(
+            CellBoard4Snakes11x11::from_grid(&grid, 0)
+                .unwrap()
+                .game_mode(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_solo_mode() {
+        let mut g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        g.game.ruleset.name = "solo".to_string();
+        let you = g.you.id.clone();
+        g.board.snakes.retain(|s| s.id == you);
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.game_mode(), Some(GameMode::Solo));
+        assert!(!compact.is_over());
+
+        let id = *compact.you_id();
+        let mut board = compact;
+        for _ in 0..5 {
+            board = board.step_once([Move::Left; 4]);
+            assert!(!board.is_over());
+            assert!(board.is_alive(&id));
+        }
+        board = board.step_once([Move::Left; 4]);
+        assert!(board.is_over());
+        assert_eq!(board.get_winner(), None);
+
+        // moving in to your own neck still ends the game
+        assert!(compact.step_once([Move::Up; 4]).is_over());
+        let states = compact
+            .simulate_with_moves(&Instruments, vec![(id, Move::all())])
+            .collect_vec();
+        assert_eq!(states.len(), 3);
+        assert!(states.iter().all(|(_, b)| !b.is_over()));
+    }
+
+    #[test]
+    fn test_ruleset_settings() {
+        use crate::types::StandardFoodPlaceableGame;
+        use rand::SeedableRng;
+
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.ruleset_settings(), None);
+
+        let mut g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let settings = RulesetSettings {
+            food_spawn_chance: 20,
+            minimum_food: 1,
+            shrink_every_n_turns: 25,
+        };
+        assert_eq!(compact.ruleset_settings(), Some(settings));
+        assert_eq!(settings.royale().shrink_every_n_turns, 25);
+        assert_eq!(
+            CellBoard4Snakes11x11::from_bytes(&compact.to_bytes()).unwrap(),
+            compact
+        );
+
+        // food spawning tops the board up to the game's minimum food
+        let food = g.board.food.len();
+        let settings = g.game.ruleset.settings.as_mut().unwrap();
+        settings.minimum_food = food as i32 + 4;
+        settings.royale = None;
+        let mut compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(
+            compact.ruleset_settings().unwrap().shrink_every_n_turns,
+            RulesetSettings::default().shrink_every_n_turns
+        );
+        compact.place_food(&mut rand::rngs::SmallRng::seed_from_u64(0));
+        assert_eq!(compact.get_all_food_as_positions().len(), food + 4);
+
+        // a spawn chance over 100 always spawns a food, wherever the settings came from
+        let mut packed = compact.embedded.pack_as_hash();
+        packed.insert("ruleset_settings".to_string(), vec![200, 0, 25]);
+        let mut over = CellBoard4Snakes11x11 {
+            embedded: CCB::from_packed_hash(&packed),
+        };
+        over.place_food(&mut rand::rngs::SmallRng::seed_from_u64(0));
+        assert_eq!(over.get_all_food_as_positions().len(), food + 5);
+
+        // to_bytes never writes one, so bytes with one are malformed
+        let mut bytes = compact.to_bytes();
+        assert_eq!(bytes[6..8], [1, 20]);
+        bytes[7] = 101;
+        assert_eq!(
+            CellBoard4Snakes11x11::from_bytes(&bytes).unwrap_err(),
+            ConversionError::MalformedBytes
+        );
+    }
+
+    #[test]
+    fn test_place_food_with() {
+        use crate::types::{FoodPlacerGame, RandomFoodPlacer, ScriptedFoodPlacer};
+        use rand::SeedableRng;
+
+        let mut g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let food = g.board.food.len();
+        g.game.ruleset.settings.as_mut().unwrap().minimum_food = food as i32 + 2;
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(0);
+
+        // the second scripted food lands on a head so it is skipped
+        let empty = compact.get_empty_cells().next().unwrap();
+        let head = compact.get_head_as_native_position(&SnakeId(0));
+        let mut scripted = compact;
+        scripted.place_food_with(&mut rng, &mut ScriptedFoodPlacer::new(vec![empty, head]));
+        assert_eq!(scripted.get_all_food_as_native_positions().len(), food + 1);
+        assert!(scripted.is_food(&empty));
+        assert_eq!(scripted.get_head_as_native_position(&SnakeId(0)), head);
+
+        let mut random = compact;
+        let mut placer = RandomFoodPlacer::new(rand::rngs::SmallRng::seed_from_u64(1));
+        random.place_food_with(&mut rng, &mut placer);
+        assert_eq!(random.get_all_food_as_native_positions().len(), food + 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_simulating_in_the_wrong_mode_panics() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact =
+            CellBoard4Snakes11x11::convert_from_game_unchecked_mode(g, &snake_id_mapping).unwrap();
+        assert_eq!(compact.game_mode(), Some(GameMode::Wrapped));
+        let _ = compact
+            .simulate(&Instruments, &compact.get_snake_ids())
+            .count();
+    }
+
+    #[test]
+    fn test_to_wire_game_round_trip() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let wire = compact.to_wire_game(&snake_id_mapping);
+        assert_eq!(wire.you.id, g.you.id);
+        assert_eq!(wire.you.body, g.you.body);
+        assert_eq!(wire.board.snakes.len(), 2);
+        assert_eq!(wire.game.ruleset.name, "royale");
+        let again: CellBoard4Snakes11x11 = wire.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(again.to_bytes(), compact.to_bytes());
+
+        let viewer: serde_json::Value =
+            serde_json::from_str(&compact.to_board_viewer_json(&snake_id_mapping)).unwrap();
+        let you = &viewer["Snakes"][0];
+        assert_eq!(you["ID"], g.you.id.as_str());
+        assert_eq!(you["Body"][0]["X"], g.you.head.x);
+        assert_eq!(you["Body"][0]["Y"], g.you.head.y);
+        assert_eq!(viewer["Food"].as_array().unwrap().len(), g.board.food.len());
+    }
+
+    #[test]
+    fn test_remap_snake_ids() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let old = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&old).unwrap();
+
+        // a map that numbers the enemies the other way around
+        let mut new = old.clone();
+        for (wire_id, id) in new.iter_mut() {
+            if *wire_id != g.you.id {
+                *id = SnakeId(4 - id.0);
+            }
+        }
+        let expected: CellBoard4Snakes11x11 = g.as_cell_board(&new).unwrap();
+        let remapped = compact.remap_snake_ids(&old, &new).unwrap();
+        assert_eq!(remapped.to_bytes(), expected.to_bytes());
+        assert_eq!(
+            remapped.remap_snake_ids(&new, &old).unwrap().to_bytes(),
+            compact.to_bytes()
+        );
+
+        let missing = g.board.snakes[0].id.clone();
+        let mut without = new.clone();
+        without.remove(&missing);
+        assert_eq!(
+            compact.remap_snake_ids(&old, &without).unwrap_err(),
+            ConversionError::UnknownSnakeId { id: missing }
+        );
+
+        let mut colliding = new;
+        colliding.insert(g.board.snakes[0].id.clone(), SnakeId(0));
+        assert!(matches!(
+            compact.remap_snake_ids(&old, &colliding),
+            Err(ConversionError::DuplicateSnakeId { .. })
+        ));
+    }
+
+    #[test]
+    fn test_perft() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let instruments = Instruments;
+
+        assert_eq!(compact.perft(&instruments, 0), 1);
+        assert_eq!(
+            compact.perft(&instruments, 1),
+            compact.successors(&instruments).len() as u64
+        );
+        // pinned counts, a change here means move generation or simulation changed
+        assert_eq!(compact.perft(&instruments, 1), 9);
+        assert_eq!(compact.perft(&instruments, 2), 48);
+        assert_eq!(compact.perft(&instruments, 3), 360);
+    }
+
+    #[test]
+    fn test_successors() {
+        let game_fixture = include_str!("../../../fixtures/late_stage.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let instruments = Instruments;
+
+        let successors = compact.successors(&instruments);
+        assert!(!successors.is_empty());
+        assert!(successors.len() <= 16);
+        assert_eq!(
+            successors,
+            compact
+                .simulate(&instruments, &compact.get_snake_ids())
+                .collect_vec()
+        );
+        // reproducible ordering, my move varies slowest
+        let my_moves = successors
+            .iter()
+            .map(|(a, _)| a.own_move().as_index())
+            .collect_vec();
+        assert!(my_moves.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_food_distance_matrix() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let head = compact.get_head_as_native_position(&SnakeId(0));
+        let field = compact.distance_field(&SnakeId(0));
+        assert_eq!(field[head.as_usize()], Some(0));
+        for body in compact.get_snake_body_vec(&SnakeId(1)) {
+            assert_eq!(field[body.as_usize()], None);
+        }
+
+        let matrix = compact.food_distance_matrix();
+        assert_eq!(
+            matrix.iter().map(|(food, _)| food.as_usize()).collect_vec(),
+            vec![4, 8, 4 * 11, 5 * 11 + 5, 8 * 11 + 4]
+        );
+        for (food, distances) in &matrix {
+            let food = compact.position_from_native(*food);
+            for sid in compact.get_snake_ids() {
+                let head = compact.get_head_as_position(&sid);
+                let manhattan = (head.x - food.x).abs() + (head.y - food.y).abs();
+                assert!(distances[sid.as_usize()].unwrap() >= manhattan as u16);
+            }
+        }
+        // the snake at (5, 8) has to go around the snake sitting on top of the food at (4, 0)
+        let top = compact
+            .get_snake_ids()
+            .into_iter()
+            .find(|sid| compact.get_head_as_position(sid) == Position { x: 5, y: 8 })
+            .unwrap();
+        assert_eq!(matrix[0].1[top.as_usize()], Some(11));
+        assert_eq!(matrix[0].1.iter().flatten().min(), Some(&1));
+    }
+
+    #[test]
+    fn test_reachable_all() {
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // 20 cells are tied and 8 are occupied by snakes
+        assert_eq!(compact.reachable_all(), [42, 16, 19, 16]);
+
+        let game_fixture = include_str!("../../../fixtures/late_stage.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.reachable_all(), [67, 31, 0, 0]);
+    }
+
+    #[test]
+    fn test_simulate_partial_freezes_other_snakes() {
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let instruments = Instruments;
+
+        let moved = compact.simulate_partial(
+            &instruments,
+            &[(SnakeId(0), None), (SnakeId(1), Some(Move::Down))],
+        );
+        assert_eq!(
+            moved.get_head_as_position(&SnakeId(1)),
+            compact
+                .get_head_as_position(&SnakeId(1))
+                .add_vec(Move::Down.to_vector())
+        );
+        assert!(moved.is_alive(&SnakeId(1)));
+        for frozen in [SnakeId(0), SnakeId(2), SnakeId(3)] {
+            assert_eq!(
+                moved.get_snake_body_vec(&frozen),
+                compact.get_snake_body_vec(&frozen)
+            );
+            assert_eq!(moved.get_health(&frozen), compact.get_health(&frozen));
+            assert_eq!(moved.get_length(&frozen), compact.get_length(&frozen));
+        }
+
+        assert_eq!(compact.simulate_partial(&instruments, &[]), compact);
+        assert_eq!(
+            compact.simulate_partial(&instruments, &[(SnakeId(0), None), (SnakeId(1), None)]),
+            compact
+        );
+    }
+
+    #[test]
+    fn test_contested_cells() {
+        let game_fixture = include_str!("../../../fixtures/body_collision.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        assert_eq!(
+            compact.contested_cells(&SnakeId(0)),
+            vec![
+                (CellIndex(4 * 11 + 1), SnakeId(1)),
+                (CellIndex(3 * 11), SnakeId(1)),
+            ]
+        );
+
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert!(compact.contested_cells(&SnakeId(0)).is_empty());
+    }
+
+    #[test]
+    fn test_head_to_head_safe_reachable_squares() {
+        let board = |enemy_length: usize| {
+            let enemy = (0..enemy_length).map(|y| (2, y)).collect_vec();
+            let grid = grid_with_snakes(7, &[(0, &[(0, 0), (0, 1), (0, 2)]), (1, &enemy)]);
+            CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap()
+        };
+        let plain_count = |compact: &CellBoard4Snakes7x7| {
+            compact
+                .distance_field(&SnakeId(0))
+                .iter()
+                .filter(|distance| matches!(distance, Some(d) if *d > 0))
+                .count() as u16
+        };
+
+        // the only way out of the corner is (1, 0), which a longer or equal enemy also reaches
+        for enemy_length in [3, 4] {
+            let compact = board(enemy_length);
+            assert!(plain_count(&compact) > 0);
+            assert_eq!(compact.head_to_head_safe_reachable_squares(&SnakeId(0)), 0);
+        }
+
+        // a shorter enemy loses the head to head, so nothing is excluded
+        let compact = board(2);
+        assert_eq!(
+            compact.head_to_head_safe_reachable_squares(&SnakeId(0)),
+            plain_count(&compact)
+        );
+
+        // a contested cell can still be reached later through another route
+        let grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(0, 3), (0, 2), (0, 1)]),
+                (1, &[(2, 3), (3, 3), (4, 3), (5, 3)]),
+            ],
+        );
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert_eq!(
+            compact.head_to_head_safe_reachable_squares(&SnakeId(0)),
+            plain_count(&compact)
+        );
+    }
+
+    #[test]
+    fn test_can_collide_next_turn() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        for (a, b) in compact.get_snake_ids().into_iter().tuple_combinations() {
+            assert!(!compact.can_collide_next_turn(&a, &b));
+        }
+
+        let g = game_fixture(include_str!("../../../fixtures/body_collision.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert!(compact.can_collide_next_turn(&SnakeId(0), &SnakeId(1)));
+        assert!(compact.can_collide_next_turn(&SnakeId(1), &SnakeId(0)));
+        assert!(!compact.can_collide_next_turn(&SnakeId(0), &SnakeId(0)));
+
+        // no head to head is possible, but 1 can move down in to the middle of 0's body
+        let grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(0, 0), (1, 0), (2, 0), (3, 0)]),
+                (1, &[(2, 1), (2, 2), (2, 3)]),
+            ],
+        );
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert!(compact.can_collide_next_turn(&SnakeId(0), &SnakeId(1)));
+        assert!(compact.contested_cells(&SnakeId(0)).is_empty());
+    }
+
+    #[test]
+    fn test_move_analysis() {
+        use crate::compact_representation::MoveLegality;
+
+        let grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(2, 2), (1, 2), (0, 2)]),
+                (1, &[(4, 2), (5, 2), (6, 2)]),
+                (2, &[(3, 3), (2, 3), (2, 4), (2, 5)]),
+            ],
+        );
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert_eq!(
+            compact.move_analysis(&SnakeId(0)),
+            [
+                (Move::Up, MoveLegality::BodyCollision(SnakeId(2))),
+                (Move::Down, MoveLegality::Legal),
+                (Move::Left, MoveLegality::SelfCollision),
+                (Move::Right, MoveLegality::LosingHeadToHead(SnakeId(1))),
+            ]
+        );
+        // both 0 and 2 can contest moving left, the lowest id is reported
+        assert_eq!(
+            compact.move_analysis(&SnakeId(1)),
+            [
+                (Move::Up, MoveLegality::LosingHeadToHead(SnakeId(2))),
+                (Move::Down, MoveLegality::Legal),
+                (Move::Left, MoveLegality::LosingHeadToHead(SnakeId(0))),
+                (Move::Right, MoveLegality::SelfCollision),
+            ]
+        );
+
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let edge = snake_id_mapping["gs_YkwKKSmYwqFFgDk9BycMvWf8"];
+        assert_eq!(
+            compact.move_analysis(&edge)[Move::Left.as_index()],
+            (Move::Left, MoveLegality::OffBoard)
+        );
+    }
+
+    #[test]
+    fn test_head_to_head_policy() {
+        use crate::compact_representation::HeadToHeadPolicy;
+
+        fn official(snakes: &[(SnakeId, u16)]) -> Option<SnakeId> {
+            let longest = snakes.iter().map(|(_, length)| *length).max().unwrap();
+            let winners = snakes
+                .iter()
+                .filter(|(_, length)| *length == longest)
+                .collect_vec();
+            if winners.len() == 1 {
+                Some(winners[0].0)
+            } else {
+                None
+            }
+        }
+
+        let instruments = Instruments;
+        for fixture in [
+            include_str!("../../../fixtures/body_collision.json"),
+            include_str!("../../../fixtures/late_stage.json"),
+            include_str!("../../../fixtures/start_of_game.json"),
+            include_str!("../../../fixtures/tree_search_collision.json"),
+        ] {
+            let g = game_fixture(fixture);
+            let snake_id_mapping = build_snake_id_map(&g);
+            let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+            let moves = compact
+                .get_snake_ids()
+                .into_iter()
+                .map(|sid| (sid, Move::all()))
+                .collect_vec();
+
+            let expected = compact
+                .simulate_with_moves(&instruments, moves.clone())
+                .collect_vec();
+            for policy in [
+                HeadToHeadPolicy::default(),
+                HeadToHeadPolicy::Custom(official),
+            ] {
+                let got = compact
+                    .simulate_with_head_to_head_policy(&instruments, moves.clone(), policy)
+                    .collect_vec();
+                assert_eq!(got, expected);
+            }
+        }
+
+        // 0 and 1 are the same length, so moving on to the same cell normally kills both
+        let g = game_fixture(include_str!("../../../fixtures/body_collision.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let (cell, _) = compact.contested_cells(&SnakeId(0))[0];
+        let towards = |sid| {
+            compact
+                .possible_moves(&compact.get_head_as_native_position(&sid))
+                .find(|(_, c)| *c == cell)
+                .unwrap()
+                .0
+        };
+        let moves = [
+            (SnakeId(0), [towards(SnakeId(0))]),
+            (SnakeId(1), [towards(SnakeId(1))]),
+        ];
+        let (_, standard) = compact
+            .simulate_with_moves(&instruments, moves)
+            .next()
+            .unwrap();
+        assert!(!standard.is_alive(&SnakeId(0)));
+        assert!(!standard.is_alive(&SnakeId(1)));
+
+        let first_wins = HeadToHeadPolicy::Custom(|snakes| {
+            snakes.iter().map(|(id, _)| *id).min_by_key(|id| id.0)
+        });
+        let (_, custom) = compact
+            .simulate_with_head_to_head_policy(&instruments, moves, first_wins)
+            .next()
+            .unwrap();
+        assert!(custom.is_alive(&SnakeId(0)));
+        assert!(!custom.is_alive(&SnakeId(1)));
+        assert_eq!(custom.get_head_as_native_position(&SnakeId(0)), cell);
+    }
+
+    #[test]
+    fn test_simulate_traced() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // this snake is right on top of the food at (4, 0)
+        let eater = compact
+            .get_snake_ids()
+            .into_iter()
+            .find(|sid| compact.get_head_as_position(sid) == Position { x: 4, y: 1 })
+            .unwrap();
+        let moves = compact
+            .reasonable_moves_for_each_snake()
+            .map(|(sid, mvs)| (sid, if sid == eater { Move::Down } else { mvs[0] }))
+            .collect_vec();
+
+        let trace = compact.simulate_traced(&moves);
+        assert_eq!(trace.before, compact);
+
+        let eaten = trace.after_tails_and_food;
+        assert_eq!(eaten.get_health(&eater), 100);
+        assert_eq!(eaten.get_length(&eater), 4);
+        assert_eq!(
+            eaten.get_head_as_native_position(&eater),
+            compact.get_head_as_native_position(&eater)
+        );
+
+        let (_, expected) = compact
+            .simulate_with_moves(
+                &Instruments,
+                moves.iter().map(|(sid, mv)| (*sid, [*mv])).collect_vec(),
+            )
+            .next()
+            .unwrap();
+        assert_eq!(trace.result, expected);
+        assert_eq!(
+            trace.result.get_head_as_position(&eater),
+            Position { x: 4, y: 0 }
+        );
+    }
+
+    #[test]
+    fn test_conformance_check() {
+        let first = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&first);
+        let compact: CellBoard4Snakes11x11 =
+            CellBoard4Snakes11x11::convert_from_game(first.clone(), &snake_id_mapping).unwrap();
+        let mut moves = [Move::Up; 4];
+        for (sid, mvs) in compact.reasonable_moves_for_each_snake() {
+            moves[sid.as_usize()] = mvs[0];
+        }
+        let mut second = compact.step_once(moves).to_wire_game(&snake_id_mapping);
+
+        let frames = [first.clone(), second.clone()];
+        assert_eq!(
+            CellBoard4Snakes11x11::conformance_check(&frames, &[moves]),
+            Ok(())
+        );
+        assert_eq!(
+            CellBoard4Snakes11x11::conformance_check(&frames, &[]),
+            Err(ConformanceError::MissingMoves { turn: first.turn })
+        );
+
+        let wire_id = snake_id_mapping
+            .iter()
+            .find(|(_, sid)| **sid == SnakeId(1))
+            .map(|(id, _)| id.clone())
+            .unwrap();
+        let snake = second
+            .board
+            .snakes
+            .iter_mut()
+            .find(|snake| snake.id == wire_id)
+            .unwrap();
+        let simulated_health = snake.health;
+        snake.health -= 1;
+        assert_eq!(
+            CellBoard4Snakes11x11::conformance_check(&[first, second.clone()], &[moves]),
+            Err(ConformanceError::Diverged {
+                turn: second.turn,
+                differences: vec![format!(
+                    "snake 1 has {} health, expected {}",
+                    simulated_health,
+                    simulated_health - 1
+                )],
+            })
+        );
+    }
+
+    #[test]
+    fn test_simulate_logged() {
+        let grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(3, 2), (4, 2), (5, 2), (6, 2)]),
+                (1, &[(2, 3), (2, 4), (2, 5)]),
+                (2, &[(1, 4), (0, 4), (0, 5)]),
+                (3, &[(6, 6), (5, 6), (4, 6)]),
+            ],
+        );
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+
+        // snake 1 loses a head to head with snake 0, snake 2 runs in to snake 1's neck and
+        // snake 3 runs off the board
+        let moves = [
+            (SnakeId(0), Move::Left),
+            (SnakeId(1), Move::Down),
+            (SnakeId(2), Move::Right),
+            (SnakeId(3), Move::Right),
+        ];
+        let (next, events) = compact.simulate_logged(&moves);
+        assert_eq!(next, compact.simulate_traced(&moves).result);
+        assert_eq!(
+            events,
+            vec![
+                SimEvent::Moved {
+                    id: SnakeId(0),
+                    mv: Move::Left,
+                    to: Position { x: 2, y: 2 },
+                },
+                SimEvent::Moved {
+                    id: SnakeId(1),
+                    mv: Move::Down,
+                    to: Position { x: 2, y: 2 },
+                },
+                SimEvent::Moved {
+                    id: SnakeId(2),
+                    mv: Move::Right,
+                    to: Position { x: 2, y: 4 },
+                },
+                SimEvent::Moved {
+                    id: SnakeId(3),
+                    mv: Move::Right,
+                    to: Position { x: 7, y: 6 },
+                },
+                SimEvent::Died {
+                    id: SnakeId(1),
+                    cause: EliminationCause::HeadToHead(SnakeId(0)),
+                },
+                SimEvent::Died {
+                    id: SnakeId(2),
+                    cause: EliminationCause::BodyCollision(SnakeId(1)),
+                },
+                SimEvent::Died {
+                    id: SnakeId(3),
+                    cause: EliminationCause::WallCollision,
+                },
+            ]
+        );
+
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let eater = compact
+            .get_snake_ids()
+            .into_iter()
+            .find(|sid| compact.get_head_as_position(sid) == Position { x: 4, y: 1 })
+            .unwrap();
+        let moves = compact
+            .reasonable_moves_for_each_snake()
+            .map(|(sid, mvs)| (sid, if sid == eater { Move::Down } else { mvs[0] }))
+            .collect_vec();
+        let (_, events) = compact.simulate_logged(&moves);
+        assert!(events.contains(&SimEvent::Ate {
+            id: eater,
+            at: Position { x: 4, y: 0 },
+        }));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, SimEvent::Died { .. })));
+    }
+
+    #[test]
+    fn test_open_degree() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // corners only have two neighbors
+        assert_eq!(compact.open_degree(CellIndex(10 * 11)), 2);
+        assert_eq!(compact.open_degree(CellIndex(5 * 11 + 5)), 4);
+        // (0, 4) is boxed in by the edge and the head at (0, 5)
+        assert_eq!(compact.open_degree(CellIndex(4 * 11)), 2);
+        // (10, 0) is next to the head at (9, 0)
+        assert_eq!(compact.open_degree(CellIndex(10)), 1);
+    }
+
+    #[test]
+    fn test_open_degree_map() {
+        let g = game_fixture(include_str!("../../../fixtures/8x8board.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let map = compact.open_degree_map();
+        for (idx, degree) in map.iter().enumerate() {
+            if idx < 8 * 8 {
+                assert_eq!(*degree, compact.open_degree(CellIndex(idx as u8)));
+            } else {
+                assert_eq!(*degree, 0);
+            }
+        }
+        assert_eq!(map[0], 2);
+    }
+
+    #[test]
+    fn test_nearest_enemy_head() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // we are at (5, 8), (0, 5) and (4, 1) are both 8 away
+        assert_eq!(
+            compact.nearest_enemy_head(&SnakeId(0)),
+            Some((SnakeId(1), 8))
+        );
+        assert_eq!(
+            compact.nearest_enemy_head(&SnakeId(3)),
+            Some((SnakeId(2), 6))
+        );
+
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 7]; 7];
+        grid[3][3] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let alone = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert_eq!(alone.nearest_enemy_head(&SnakeId(0)), None);
+        assert_eq!(alone.nearest_enemy_head(&SnakeId(1)), None);
+    }
+
+    #[test]
+    fn test_globally_safe_cells() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let safe = compact.globally_safe_cells();
+        // 8 cells hold snakes and 10 more are next to a head
+        assert_eq!(safe.len(), 11 * 11 - 8 - 10);
+        assert!(!safe.contains(&CellIndex(6 * 11)));
+        assert!(safe.contains(&CellIndex(5 * 11 + 5)));
+
+        let g = game_fixture(include_str!("../../../fixtures/body_collision.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let safe = compact.globally_safe_cells();
+        for sid in compact.get_snake_ids() {
+            for (cell, _) in compact.contested_cells(&sid) {
+                assert!(!safe.contains(&cell));
+            }
+            for (_, cell) in compact.possible_moves(&compact.get_head_as_native_position(&sid)) {
+                assert!(!safe.contains(&cell));
+            }
+            for cell in compact.get_snake_body_vec(&sid) {
+                assert!(!safe.contains(&cell));
+            }
+        }
+    }
+
+    #[test]
+    fn test_will_be_free_next_turn() {
+        let game_fixture = include_str!("../../../fixtures/tail_chase.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        // head, body, body, tail
+        assert!(!compact.will_be_free_next_turn(CellIndex(0)));
+        assert!(!compact.will_be_free_next_turn(CellIndex(1)));
+        assert!(!compact.will_be_free_next_turn(CellIndex(11 + 1)));
+        assert!(compact.will_be_free_next_turn(CellIndex(11)));
+        assert!(compact.will_be_free_next_turn(CellIndex(5 * 11 + 5)));
+
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // a stacked tail stays put
+        assert!(!compact.will_be_free_next_turn(CellIndex(9 * 11 + 5)));
+    }
+
+    #[test]
+    fn test_alive_heads() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let alive_heads = compact.alive_heads().collect_vec();
+        assert_eq!(
+            alive_heads.iter().map(|(sid, _)| *sid).collect_vec(),
+            compact.get_snake_ids()
+        );
+        for (sid, head) in alive_heads {
+            assert_eq!(head, compact.get_head_as_native_position(&sid));
+        }
+    }
+
+    #[test]
+    fn test_just_ate() {
+        let g = game_fixture(include_str!("../../../fixtures/tail_chase.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert!(!compact.just_ate(&SnakeId(0)));
+
+        let g = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes7x7 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert!(!compact.just_ate(&SnakeId(0)));
+        // the food is directly to our left
+        let (_, ate) = compact
+            .simulate_with_moves(&Instruments, vec![(SnakeId(0), [Move::Left])])
+            .next()
+            .unwrap();
+        assert!(ate.just_ate(&SnakeId(0)));
+        let (_, digested) = ate
+            .simulate_with_moves(&Instruments, vec![(SnakeId(0), [Move::Down])])
+            .next()
+            .unwrap();
+        assert!(!digested.just_ate(&SnakeId(0)));
+
+        // on turn 1 the starting stack hasn't unwound yet, which looks like a meal
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert!(compact.just_ate(&SnakeId(0)));
+        let (_, second_turn) = compact
+            .simulate_with_moves(&Instruments, vec![(SnakeId(0), [Move::Up])])
+            .next()
+            .unwrap();
+        assert!(!second_turn.just_ate(&SnakeId(0)));
+    }
+
+    #[test]
+    fn test_healths() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        for sid in compact.get_snake_ids() {
+            assert_eq!(compact.healths()[sid.as_usize()], compact.get_health(&sid));
+        }
+
+        // SnakeId(1) is on the left edge at (0, 5)
+        let (_, next) = compact
+            .simulate_with_moves(&Instruments, vec![(SnakeId(1), [Move::Left])])
+            .next()
+            .unwrap();
+        assert_eq!(next.healths()[1], 0);
+        assert_eq!(next.healths()[0], compact.healths()[0]);
+    }
+
+    #[test]
+    fn test_lengths() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.lengths(), [3, 3, 3, 3]);
+
+        // SnakeId(1) runs off the left edge, SnakeId(0) eats the food at (4, 8)
+        let (_, next) = compact
+            .simulate_with_moves(
+                &Instruments,
+                vec![(SnakeId(0), [Move::Left]), (SnakeId(1), [Move::Left])],
+            )
+            .next()
+            .unwrap();
+        assert_eq!(next.lengths(), [4, 0, 3, 3]);
+        for sid in 0..4 {
+            assert_eq!(next.lengths()[sid], next.get_length(&SnakeId(sid as u8)));
+        }
+    }
+
+    #[test]
+    fn test_is_double_threatened() {
+        // we are at (3, 3) facing up, enemy bodies block left and right and both enemy heads
+        // can reach (3, 4)
+        let mut grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(3, 3), (3, 2), (3, 1)]),
+                (1, &[(2, 4), (2, 3), (2, 2)]),
+            ],
+        );
+        let mut pincer = grid.clone();
+        place_snake(&mut pincer, 2, &[(4, 4), (4, 3), (4, 2)]);
+        let compact = CellBoard4Snakes7x7::from_grid(&pincer, 0).unwrap();
+        assert!(compact.is_double_threatened(&SnakeId(0)));
+        assert!(!compact.is_double_threatened(&SnakeId(3)));
+
+        // with the second enemy out of the way we can escape right
+        place_snake(&mut grid, 2, &[(5, 5), (5, 4), (5, 3)]);
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert!(!compact.is_double_threatened(&SnakeId(0)));
+    }
+
+    #[test]
+    fn test_wins_head_to_head_at() {
+        let grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(3, 3), (3, 2), (3, 1), (3, 0)]),
+                // shorter, contests (2, 3) and (3, 4)
+                (1, &[(2, 4), (1, 4), (0, 4)]),
+                // as long, contests (4, 3)
+                (2, &[(5, 3), (6, 3), (6, 2), (6, 1)]),
+            ],
+        );
+        let cell = |x, y| CellIndex::<u8>::from_xy(x, y, 7);
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+
+        assert_eq!(
+            compact.wins_head_to_head_at(&SnakeId(0), cell(2, 3)),
+            Some(true)
+        );
+        assert_eq!(
+            compact.wins_head_to_head_at(&SnakeId(0), cell(3, 4)),
+            Some(true)
+        );
+        assert_eq!(
+            compact.wins_head_to_head_at(&SnakeId(0), cell(4, 3)),
+            Some(false)
+        );
+        assert_eq!(compact.wins_head_to_head_at(&SnakeId(0), cell(0, 0)), None);
+        // snake 0's own head doesn't count as a threat
+        assert_eq!(compact.wins_head_to_head_at(&SnakeId(0), cell(3, 2)), None);
+        assert_eq!(
+            compact.wins_head_to_head_at(&SnakeId(1), cell(2, 3)),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_death_cells() {
+        let mut grid = grid_with_snakes(
+            7,
+            &[
+                (0, &[(3, 3), (3, 2), (3, 1)]),
+                // the corner at (0, 0) is walled in by snake 1, whose tail moves away next turn
+                (1, &[(0, 1), (1, 1), (1, 0)]),
+            ],
+        );
+        // the opposite corner is only reachable through deadly hazards
+        grid[6][5] = CellContents::Empty { hazards: 1 };
+        grid[5][6] = CellContents::Empty { hazards: 1 };
+        let cell = |x, y| CellIndex::<u8>::from_xy(x, y, 7);
+
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 100).unwrap();
+        assert_eq!(compact.death_cells(&SnakeId(0)), vec![cell(6, 6)]);
+        assert!(compact.death_cells(&SnakeId(3)).is_empty());
+
+        // once the tail is stacked it stays, so the corner is a trap too
+        grid[0][1] = CellContents::Snake {
+            id: SnakeId(1),
+            segment: 2,
+            stack: 2,
+            hazards: 0,
+        };
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 100).unwrap();
+        assert_eq!(
+            compact.death_cells(&SnakeId(0)),
+            vec![cell(0, 0), cell(6, 6)]
+        );
+
+        // with weak hazards the far corner is survivable
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 14).unwrap();
+        assert_eq!(compact.death_cells(&SnakeId(0)), vec![cell(0, 0)]);
+    }
+
+    #[test]
+    fn test_small_board_in_large_storage_moves_vertically() {
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 7]; 7];
+        grid[3][3] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        // a 7x7 board stored in room for 11x11, rows are 7 cells apart and not 11
+        let compact = CellBoard4Snakes11x11::from_grid(&grid, 0).unwrap();
+        for (mv, expected) in [
+            (Move::Up, Position { x: 3, y: 4 }),
+            (Move::Down, Position { x: 3, y: 2 }),
+        ] {
+            let (_, next) = compact
+                .simulate_with_moves(&Instruments, vec![(SnakeId(0), [mv])])
+                .next()
+                .unwrap();
+            assert_eq!(next.get_head_as_position(&SnakeId(0)), expected);
+        }
+    }
+
+    #[test]
+    fn test_hazard_sinkhole() {
+        use crate::compact_representation::SinkholeSchedule;
+
+        let grid = vec![vec![CellContents::Empty { hazards: 0 }; 11]; 11];
+        let compact = CellBoard4Snakes11x11::from_grid(&grid, 0).unwrap();
+        let schedule = SinkholeSchedule::default();
+        let cell = |x, y| CellIndex::<u8>::from_xy(x, y, 11);
+        let stack_at = |hazards: &[(CellIndex<u8>, u8)], x, y| {
+            hazards
+                .iter()
+                .find(|(idx, _)| *idx == cell(x, y))
+                .map_or(0, |(_, stack)| *stack)
+        };
+
+        assert!(compact.hazard_sinkhole(cell(5, 5), 0, &schedule).is_empty());
+        assert_eq!(
+            compact.hazard_sinkhole(cell(5, 5), 1, &schedule),
+            vec![(cell(5, 5), 1)]
+        );
+        assert_eq!(
+            compact.hazard_sinkhole(cell(5, 5), 11, &schedule),
+            vec![
+                (cell(5, 4), 1),
+                (cell(4, 5), 1),
+                (cell(5, 5), 2),
+                (cell(6, 5), 1),
+                (cell(5, 6), 1),
+            ]
+        );
+
+        let hazards = compact.hazard_sinkhole(cell(5, 5), 21, &schedule);
+        assert_eq!(hazards.len(), 21);
+        assert_eq!(stack_at(&hazards, 5, 5), 3);
+        assert_eq!(stack_at(&hazards, 5, 6), 2);
+        assert_eq!(stack_at(&hazards, 6, 6), 1);
+        assert_eq!(stack_at(&hazards, 7, 6), 1);
+        assert_eq!(stack_at(&hazards, 7, 7), 0);
+
+        // the pit stops growing and cells off the board are dropped
+        let hazards = compact.hazard_sinkhole(cell(0, 0), 1000, &schedule);
+        assert_eq!(stack_at(&hazards, 0, 0), 6);
+        assert!(hazards.iter().all(|(idx, _)| idx.to_xy(11).0 <= 5));
+        assert_eq!(SinkholeSchedule::for_width(7).max_rings, 3);
+    }
+
+    #[test]
+    fn test_apply_hazard_map() {
+        use crate::compact_representation::{SinkholeSchedule, MAX_HAZARD_STACK};
+
+        let grid = vec![vec![CellContents::Empty { hazards: 0 }; 11]; 11];
+        let mut compact = CellBoard4Snakes11x11::from_grid(&grid, 0).unwrap();
+        let cell = |x, y| CellIndex::<u8>::from_xy(x, y, 11);
+
+        // a sinkhole with one ring, overlaid with a hazard line through its middle
+        let sinkhole = compact.hazard_sinkhole(cell(5, 5), 11, &SinkholeSchedule::default());
+        compact.apply_hazard_map(sinkhole);
+        compact.apply_hazard_map((0..11).map(|y| (cell(5, y), 1)));
+        assert_eq!(compact.hazard_stack(cell(5, 5)), 3);
+        assert_eq!(compact.hazard_stack(cell(5, 4)), 2);
+        assert_eq!(compact.hazard_stack(cell(4, 5)), 1);
+        assert_eq!(compact.hazard_stack(cell(5, 0)), 1);
+        assert_eq!(compact.hazard_stack(cell(0, 0)), 0);
+        assert!(compact.is_hazard(&cell(5, 4)));
+
+        compact.apply_hazard_map([(cell(5, 5), u8::MAX)]);
+        assert_eq!(compact.hazard_stack(cell(5, 5)), MAX_HAZARD_STACK);
+
+        // stacks survive a round trip through the wire format and multiply the damage
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let mut compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let me = SnakeId(0);
+        let mv = compact
+            .reasonable_moves_for_each_snake()
+            .find(|(sid, _)| *sid == me)
+            .unwrap()
+            .1[0];
+        let target = compact
+            .get_head_as_native_position(&me)
+            .in_direction(&mv, 11);
+        assert!(!compact.is_food(&target));
+        compact.apply_hazard_map([(target, 2)]);
+        let stack = compact.hazard_stack(target);
+        assert!(stack >= 2);
+
+        let round_trip = CellBoard4Snakes11x11::convert_from_game(
+            compact.to_wire_game(&snake_id_mapping),
+            &snake_id_mapping,
+        )
+        .unwrap();
+        assert_eq!(round_trip.hazard_stack(target), stack);
+
+        let mut moves = [Move::Up; 4];
+        for (sid, mvs) in compact.reasonable_moves_for_each_snake() {
+            moves[sid.as_usize()] = mvs[0];
+        }
+        let next = compact.step_once(moves);
+        let damage = 1 + compact.get_hazard_damage() as i32 * stack as i32;
+        assert_eq!(
+            next.get_health(&me) as i32,
+            (compact.get_health(&me) as i32 - damage).max(0)
+        );
+    }
+
+    #[test]
+    fn test_safe_reachable_squares() {
+        use crate::wire_representation::RoyaleSettings;
+
+        // the left column is already hazard, leaving a 6x7 safe area
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 7]; 7];
+        for row in grid.iter_mut() {
+            row[0] = CellContents::Empty { hazards: 1 };
+        }
+        grid[3][3] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 14).unwrap();
+        let royale = RoyaleSettings {
+            shrink_every_n_turns: 5,
+        };
+        let cell = |x, y| CellIndex::new(Position { x, y }, 7);
+
+        assert_eq!(compact.turns_until_hazard(cell(0, 0), &royale), Some(0));
+        assert_eq!(compact.turns_until_hazard(cell(1, 3), &royale), Some(1));
+        assert_eq!(compact.turns_until_hazard(cell(2, 3), &royale), Some(6));
+        assert_eq!(compact.turns_until_hazard(cell(3, 3), &royale), Some(11));
+        let never = RoyaleSettings {
+            shrink_every_n_turns: 0,
+        };
+        assert_eq!(compact.turns_until_hazard(cell(3, 3), &never), None);
+
+        // everything but the hazards and our head, then the 4x5 inside of the safe area, then
+        // its 2x3 inside
+        assert_eq!(compact.safe_reachable_squares(&SnakeId(0), 0, &royale), 41);
+        assert_eq!(compact.safe_reachable_squares(&SnakeId(0), 1, &royale), 19);
+        assert_eq!(compact.safe_reachable_squares(&SnakeId(0), 6, &royale), 5);
+        assert_eq!(compact.safe_reachable_squares(&SnakeId(0), 6, &never), 41);
+    }
+
+    #[test]
+    fn test_threats_to() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let cell = |x, y| CellIndex::new(Position { x, y }, 11);
+
+        // the food at (4, 8) is right next to our head at (5, 8)
+        assert_eq!(compact.threats_to(cell(4, 8)), vec![SnakeId(0)]);
+        assert_eq!(compact.threats_to(cell(5, 5)), vec![]);
+        // a head can't move on to itself
+        assert_eq!(compact.threats_to(cell(5, 8)), vec![]);
+
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 7]; 7];
+        for (id, (x, y)) in [(2, 3), (4, 3), (3, 4)].iter().enumerate() {
+            grid[*y][*x] = CellContents::Snake {
+                id: SnakeId(id as u8),
+                segment: 0,
+                stack: 3,
+                hazards: 0,
+            };
+        }
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert_eq!(
+            compact.threats_to(CellIndex::new(Position { x: 3, y: 3 }, 7)),
+            vec![SnakeId(0), SnakeId(1), SnakeId(2)]
+        );
+    }
+
+    #[test]
+    fn test_max_stack() {
+        let g = game_fixture(include_str!("../../../fixtures/tail_chase.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.max_stack(&SnakeId(0)), 1);
+
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        for sid in compact.get_snake_ids() {
+            assert_eq!(compact.max_stack(&sid), 2);
+        }
+
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 7]; 7];
+        grid[3][3] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert_eq!(compact.max_stack(&SnakeId(0)), 3);
+        assert_eq!(compact.max_stack(&SnakeId(1)), 0);
+    }
+
+    #[test]
+    fn test_snake_centroid() {
+        let mut grid = grid_with_snakes(7, &[(0, &[(1, 1), (2, 1), (3, 1), (3, 2)])]);
+        grid[5][5] = CellContents::Snake {
+            id: SnakeId(1),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        // SnakeId(2) isn't on the board, so it is dead with its head reset to cell 0, where
+        // SnakeId(3) is
+        grid[0][0] = CellContents::Snake {
+            id: SnakeId(3),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        // x averages 9 / 4 and y averages 5 / 4
+        assert_eq!(
+            compact.snake_centroid(&SnakeId(0)),
+            Some(Position { x: 2, y: 1 })
+        );
+        assert_eq!(
+            compact.snake_centroid(&SnakeId(1)),
+            Some(Position { x: 5, y: 5 })
+        );
+        assert_eq!(compact.snake_centroid(&SnakeId(2)), None);
+    }
+
+    #[test]
+    fn test_to_grid() {
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let grid = compact.to_grid();
+        assert_eq!(grid.len(), 11);
+        assert!(grid.iter().all(|row| row.len() == 11));
+
+        // you are at (5, 8) with a stacked tail at (5, 9)
+        assert_eq!(
+            grid[8][5],
+            CellContents::Snake {
+                id: SnakeId(0),
+                segment: 0,
+                stack: 1,
+                hazards: 0
+            }
+        );
+        assert_eq!(
+            grid[9][5],
+            CellContents::Snake {
+                id: SnakeId(0),
+                segment: 1,
+                stack: 2,
+                hazards: 0
+            }
+        );
+        assert_eq!(grid[4][0], CellContents::Food { hazards: 0 });
+        assert_eq!(grid[0][0], CellContents::Empty { hazards: 0 });
+
+        for (y, row) in grid.iter().enumerate() {
+            for (x, contents) in row.iter().enumerate() {
+                let idx = CellIndex::new(Position::new(x as i32, y as i32), 11);
+                assert_eq!(*contents, compact.what_is_at(idx));
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_grid_round_trips() {
+        use crate::compact_representation::MAX_HAZARD_STACK;
+
+        for fixture in [
+            include_str!("../../../fixtures/start_of_game.json"),
+            include_str!("../../../fixtures/late_stage.json"),
+        ] {
+            let g: Result<DEGame, _> = serde_json::from_slice(fixture.as_bytes());
+            let g = g.expect("the json literal is valid");
+            let snake_id_mapping = build_snake_id_map(&g);
+            let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+            let grid = compact.to_grid();
+            let imported =
+                CellBoard4Snakes11x11::from_grid(&grid, compact.get_hazard_damage()).unwrap();
+            assert!(imported.embedded.assert_consistency());
+            assert_eq!(imported.to_grid(), grid);
+            for id in compact.get_snake_ids() {
+                assert_eq!(
+                    imported.get_snake_body_vec(&id),
+                    compact.get_snake_body_vec(&id)
+                );
+                assert_eq!(imported.get_length(&id), compact.get_length(&id));
+            }
+        }
+
+        // stacked hazards keep their count, including under a snake
+        let mut grid = grid_with_snakes(7, &[(0, &[(1, 1), (1, 2), (1, 3)])]);
+        grid[5][5] = CellContents::Empty {
+            hazards: MAX_HAZARD_STACK,
+        };
+        grid[4][4] = CellContents::Food { hazards: 2 };
+        if let CellContents::Snake { hazards, .. } = &mut grid[1][1] {
+            *hazards = 3;
+        }
+        let imported = CellBoard4Snakes7x7::from_grid(&grid, 14).unwrap();
+        let cell = |x, y| CellIndex::<u8>::from_xy(x, y, 7);
+        assert_eq!(imported.hazard_stack(cell(5, 5)), MAX_HAZARD_STACK);
+        assert_eq!(imported.hazard_stack(cell(1, 1)), 3);
+        assert_eq!(
+            imported.what_is_at(cell(4, 4)),
+            CellContents::Food { hazards: 2 }
+        );
+        assert_eq!(imported.to_grid(), grid);
+    }
+
+    #[test]
+    fn test_from_grid_rejects_bad_grids() {
+        let empty = CellContents::Empty { hazards: 0 };
+        let snake = |segment, stack| CellContents::Snake {
+            id: SnakeId(0),
+            segment,
+            stack,
+            hazards: 0,
+        };
+
+        let mut grid = vec![vec![empty; 5]; 5];
+        grid[0][0] = snake(0, 1);
+        grid[0][1] = snake(1, 2);
+        assert!(CellBoard4Snakes7x7::from_grid(&grid, 0).is_ok());
+
+        // a gap between the head and the tail
+        let mut gap = grid.clone();
+        gap[0][1] = empty;
+        gap[0][2] = snake(1, 2);
+        assert_eq!(
+            CellBoard4Snakes7x7::from_grid(&gap, 0).unwrap_err(),
+            ConversionError::MalformedSnake { id: SnakeId(0) }
+        );
+
+        // a stacked head
+        let mut stacked_head = grid.clone();
+        stacked_head[0][0] = snake(0, 2);
+        assert!(CellBoard4Snakes7x7::from_grid(&stacked_head, 0).is_err());
+
+        let mut ragged = grid.clone();
+        ragged[3].pop();
+        assert_eq!(
+            CellBoard4Snakes7x7::from_grid(&ragged, 0).unwrap_err(),
+            ConversionError::RaggedGrid
+        );
+
+        let too_big = vec![vec![empty; 8]; 8];
+        assert!(matches!(
+            CellBoard4Snakes7x7::from_grid(&too_big, 0),
+            Err(ConversionError::BoardTooLarge {
+                width: 8,
+                height: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn test_grid_must_match_dimensions() {
+        let tall = grid_with_snakes(5, &[(0, &[(1, 1), (1, 2), (1, 3)])])
+            .into_iter()
+            .chain(vec![vec![CellContents::Empty { hazards: 0 }; 5]; 2])
+            .collect_vec();
+        let too_large = |width, height| ConversionError::BoardTooLarge { width, height };
+
+        // a square board can't describe a 5 wide and 7 tall grid, even though it fits in storage
+        assert_eq!(
+            CellBoard4Snakes11x11::from_grid(&tall, 0).unwrap_err(),
+            too_large(5, 7)
+        );
+        let custom = CellBoard8Snakes25x25::from_grid(&tall, 0).unwrap();
+        assert_eq!(custom.to_grid(), tall);
+        assert_eq!(
+            CellBoard4Snakes11x11::from_bytes(&custom.to_bytes()).unwrap_err(),
+            too_large(5, 7)
+        );
+
+        // a fixed board only takes a grid of exactly its size
+        type Fixed7x7 = CellBoard<u8, Fixed<7, 7>, { 7 * 7 }, 4>;
+        let small = grid_with_snakes(5, &[(0, &[(1, 1), (1, 2), (1, 3)])]);
+        assert_eq!(Fixed7x7::from_grid(&small, 0).unwrap_err(), too_large(5, 5));
+        assert!(Fixed7x7::from_grid(&grid_with_snakes(7, &[]), 0).is_ok());
+
+        // games are checked the same way, and the best board for a non square game is one with
+        // custom dimensions
+        let mut g = game_fixture(include_str!("../../../fixtures/7x7board.json"));
+        g.board.width = 8;
+        assert_eq!(
+            CellBoard4Snakes11x11::validate_game(&g),
+            vec![ConversionError::BoardTooLarge {
+                width: 8,
+                height: 7
+            }]
+        );
+        assert!(matches!(
+            g.to_best_cell_board(),
+            Ok(BestCellBoard::Large(_))
+        ));
+    }
+
+    #[test]
+    fn test_danger_score() {
+        let game_fixture = include_str!("../../../fixtures/cornered.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // every move is fatal and we are trapped, but there are no opponents
+        assert_eq!(
+            compact.danger_score(&SnakeId(0), &DangerWeights::default()),
+            2.0
+        );
+
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let only_fatal_moves = DangerWeights {
+            fatal_moves: 1.0,
+            trapped: 0.0,
+            area_deficit: 0.0,
+            longer_enemy_heads: 0.0,
+        };
+        // only moving back on to our stacked tail is fatal
+        assert_eq!(compact.danger_score(&SnakeId(0), &only_fatal_moves), 0.25);
+        assert!(compact.danger_score(&SnakeId(0), &DangerWeights::default()) < 2.0);
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let game_fixture = include_str!("../../../fixtures/late_stage.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        for symmetry in Symmetry::all() {
+            let transformed = compact.apply_symmetry(symmetry).unwrap();
+            assert!(transformed.embedded.assert_consistency());
+            assert_eq!(
+                transformed.get_head_as_position(&SnakeId(0)),
+                symmetry.apply(compact.get_head_as_position(&SnakeId(0)), 11, 11)
+            );
+            assert_eq!(transformed.canonical_form(), compact.canonical_form());
+        }
+
+        let mut rotated = compact;
+        for _ in 0..4 {
+            rotated = rotated.apply_symmetry(Symmetry::Rotate90).unwrap();
+        }
+        assert_eq!(rotated, compact);
+    }
+
+    #[test]
+    fn test_dedup_symmetric_moves() {
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 11]; 11];
+        grid[5][5] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 0,
+            stack: 1,
+            hazards: 0,
+        };
+        grid[4][5] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 1,
+            stack: 2,
+            hazards: 0,
+        };
+        let compact = CellBoard4Snakes11x11::from_grid(&grid, 0).unwrap();
+
+        // down is our neck, and left and right are mirror images
+        assert_eq!(
+            compact.dedup_symmetric_moves(&SnakeId(0)),
+            vec![Move::Up, Move::Left]
+        );
+    }
+
+    #[test]
+    fn test_turns_until_starve() {
+        let game_fixture = include_str!("../../../fixtures/late_stage.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let mut compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        assert_eq!(compact.turns_until_starve(&SnakeId(0)), 58);
+        assert_eq!(compact.turns_until_starve(&SnakeId(1)), 81);
+        assert_eq!(compact.turns_until_starve(&SnakeId(2)), 0);
+
+        // 58 health at 1 + 15 damage per turn lasts 4 turns
+        let head = compact.get_head_as_native_position(&SnakeId(0));
+        compact.set_hazard(head);
+        assert_eq!(compact.turns_until_starve(&SnakeId(0)), 4);
+    }
+
+    #[test]
+    fn test_projected_starve_turn() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let mut compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.turn(), 150);
+        assert_eq!(compact.projected_starve_turn(&SnakeId(0)), 208);
+        assert_eq!(compact.projected_starve_turn(&SnakeId(1)), 231);
+        assert_eq!(compact.projected_starve_turn(&SnakeId(2)), 150);
+
+        // the turn advances with the simulation and survives encoding
+        let next = compact.step_once([Move::Left, Move::Up, Move::Up, Move::Up]);
+        assert_eq!(next.turn(), 151);
+        assert_eq!(
+            CellBoard4Snakes11x11::from_bytes(&next.to_bytes())
+                .unwrap()
+                .turn(),
+            151
+        );
+        assert_eq!(next.to_wire_game(&snake_id_mapping).turn, 151);
+
+        let head = compact.get_head_as_native_position(&SnakeId(0));
+        compact.set_hazard(head);
+        assert_eq!(compact.projected_starve_turn(&SnakeId(0)), 154);
+    }
+
+    #[test]
+    fn test_symmetry_group() {
+        let empty = CellContents::Empty { hazards: 0 };
+        let mut grid = vec![vec![empty; 7]; 7];
+        grid[3][3] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert_eq!(compact.symmetry_group().order(), 8);
+
+        // food on one diagonal only survives the symmetries that keep that diagonal
+        grid[0][0] = CellContents::Food { hazards: 0 };
+        grid[6][6] = CellContents::Food { hazards: 0 };
+        let group = CellBoard4Snakes7x7::from_grid(&grid, 0)
+            .unwrap()
+            .symmetry_group();
+        assert_eq!(
+            group.iter().collect_vec(),
+            vec![
+                Symmetry::Identity,
+                Symmetry::Rotate180,
+                Symmetry::FlipDiagonal,
+                Symmetry::FlipAntiDiagonal
+            ]
+        );
+        assert!(!group.contains(Symmetry::Rotate90));
+
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert!(compact.symmetry_group().is_trivial());
+    }
+
+    #[test]
+    fn test_influence_map() {
+        let empty = CellContents::Empty { hazards: 0 };
+        let stacked = |id| CellContents::Snake {
+            id: SnakeId(id),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let mut grid = vec![vec![empty; 7]; 7];
+        grid[3][1] = stacked(0);
+        grid[3][5] = stacked(1);
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        let at = |x, y| CellIndex::<u8>::new(Position { x, y }, 7).as_usize();
+
+        let influence = compact.influence_map(&SnakeId(0), 0.5);
+        assert_eq!(influence[at(1, 3)], 1.0);
+        assert_eq!(influence[at(5, 3)], -1.0);
+        assert_eq!(influence[at(2, 3)], 0.5 - 0.5f32.powi(3));
+        assert_eq!(influence[at(3, 3)], 0.0);
+        assert!(influence[at(4, 3)] < 0.0);
+        assert_eq!(influence[at(0, 0)], 0.5f32.powi(4) - 0.5f32.powi(8));
+    }
+
+    #[test]
+    fn test_turns_to_intercept() {
+        let empty = CellContents::Empty { hazards: 0 };
+        let stacked = |id| CellContents::Snake {
+            id: SnakeId(id),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let mut grid = vec![vec![empty; 7]; 7];
+        grid[3][1] = stacked(0);
+        grid[3][5] = stacked(1);
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        // both heads reach (3, 3) in 2 moves
+        assert_eq!(
+            compact.turns_to_intercept(&SnakeId(0), &SnakeId(1)),
+            Some(2)
+        );
+        assert_eq!(compact.turns_to_intercept(&SnakeId(0), &SnakeId(2)), None);
+
+        // snake 2 walls the board in two, leaving no common cell
+        let wall = (0..7).map(|y| (3, y)).collect_vec();
+        place_snake(&mut grid, 2, &wall);
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        assert_eq!(compact.turns_to_intercept(&SnakeId(0), &SnakeId(1)), None);
+    }
+
+    #[test]
+    fn test_longest_escape_corridor() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // the open board has paths far longer than the search looks
+        assert_eq!(compact.longest_escape_corridor(&SnakeId(0)), 32);
+
+        let wall = (1..7).rev().map(|x| (x, 1)).collect_vec();
+        let grid = grid_with_snakes(7, &[(0, &[(0, 0), (0, 1), (0, 2)]), (1, &wall)]);
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+        // snake 1 walls snake 0 in to the bottom row
+        assert_eq!(compact.longest_escape_corridor(&SnakeId(0)), 6);
+        assert_eq!(compact.longest_escape_corridor(&SnakeId(3)), 0);
+    }
+
+    #[test]
+    fn test_effective_health() {
+        let mut g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let mut compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        assert_eq!(compact.effective_health(&SnakeId(0)), 58);
+        assert_eq!(compact.effective_health(&SnakeId(2)), 0);
+
+        let head = compact.get_head_as_native_position(&SnakeId(0));
+        compact.set_hazard(head);
+        assert_eq!(compact.effective_health(&SnakeId(0)), 58 - 15);
+
+        // 10 health against 15 damage a turn is effectively already dead
+        let you = g.you.id.clone();
+        for snake in g.board.snakes.iter_mut().filter(|s| s.id == you) {
+            snake.health = 10;
+        }
+        let mut compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        compact.set_hazard(head);
+        assert_eq!(compact.effective_health(&SnakeId(0)), -5);
+    }
+
+    #[test]
+    fn test_simulate_with_settings_food_health() {
+        let add = |amount| SimSettings {
+            food_health: FoodHealthMode::Add { amount, cap: 100 },
+            ..SimSettings::default()
+        };
+        // our head is at (4, 6), the food is to its left
+        let eat = |health: i32, hazard: bool, settings: SimSettings| {
+            let mut g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+            let you = g.you.id.clone();
+            for snake in g.board.snakes.iter_mut().filter(|s| s.id == you) {
+                snake.health = health;
+            }
+            g.board.food.push(Position::new(3, 6));
+            if hazard {
+                g.board.hazards.push(Position::new(3, 6));
+            }
+            let snake_id_mapping = build_snake_id_map(&g);
+            let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+            let moves = [
+                (SnakeId(0), [Move::Left].as_slice()),
+                (SnakeId(1), [Move::Up].as_slice()),
+            ];
+            let results = compact
+                .simulate_with_settings(&Instruments, moves.iter().cloned(), settings)
+                .collect_vec();
+            assert_eq!(results.len(), 1);
+            results[0].1.get_health(&SnakeId(0))
+        };
+
+        assert_eq!(eat(40, false, SimSettings::default()), 100);
+        assert_eq!(eat(40, false, add(25)), 40 - 1 + 25);
+        assert_eq!(eat(40, false, add(80)), 100);
+
+        // eating on a hazard skips its 15 damage, so a weak snake doesn't drop to 0 first
+        assert_eq!(eat(40, true, SimSettings::default()), 100);
+        assert_eq!(eat(40, true, add(25)), 40 - 1 + 25);
+        assert_eq!(eat(10, true, add(25)), 10 - 1 + 25);
+    }
+
+    #[test]
+    fn test_is_standard_start() {
+        for (fixture, expected) in [
+            (
+                include_str!("../../../fixtures/e80b70e7-a916-40ca-82d2-ad76e074efe1_0.json"),
+                true,
+            ),
+            (
+                include_str!("../../../fixtures/this_one_crashed.json"),
+                true,
+            ),
+            // a turn in, the snakes have already moved off their spawn points
+            (include_str!("../../../fixtures/start_of_game.json"), false),
+            (include_str!("../../../fixtures/body_collision.json"), false),
+        ] {
+            let g = game_fixture(fixture);
+            let snake_id_mapping = build_snake_id_map(&g);
+            let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+            assert_eq!(compact.is_standard_start(), expected);
+        }
+
+        let empty = CellContents::Empty { hazards: 0 };
+        let spawned = |x: usize, y: usize| {
+            let mut grid = vec![vec![empty; 7]; 7];
+            grid[y][x] = CellContents::Snake {
+                id: SnakeId(0),
+                segment: 0,
+                stack: 3,
+                hazards: 0,
+            };
+            CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap()
+        };
+        assert!(spawned(1, 1).is_standard_start());
+        assert!(spawned(3, 5).is_standard_start());
+        assert!(!spawned(2, 1).is_standard_start());
+        assert!(!CellBoard4Snakes7x7::from_grid(&vec![vec![empty; 7]; 7], 0)
+            .unwrap()
+            .is_standard_start());
+    }
+
+    #[test]
+    fn test_compare_snakes() {
+        use std::cmp::Ordering;
+
+        // every snake starts out 3 long with 99 health
+        let mut g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        g.board.snakes[1].health = 95;
+        g.board.snakes[2].health = 100;
+        g.board.snakes[3].body = [(9, 0), (9, 1), (9, 2), (9, 3)]
+            .iter()
+            .map(|(x, y)| Position::new(*x, *y))
+            .collect();
+        g.board.snakes[3].health = 10;
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let ids = g
+            .board
+            .snakes
+            .iter()
+            .map(|snake| *snake_id_mapping.get(&snake.id).unwrap())
+            .collect_vec();
+        let id = |snake: usize| ids[snake];
+
+        assert_eq!(compact.total_health(&id(1)), 95);
+        // length beats health
+        assert_eq!(compact.compare_snakes(&id(3), &id(2)), Ordering::Greater);
+        // among equal lengths more health is ahead
+        assert_eq!(compact.compare_snakes(&id(2), &id(0)), Ordering::Greater);
+        assert_eq!(compact.compare_snakes(&id(1), &id(0)), Ordering::Less);
+        assert_eq!(compact.compare_snakes(&id(0), &id(0)), Ordering::Equal);
+
+        let mut standings = compact.get_snake_ids();
+        standings.sort_by(|a, b| compact.compare_snakes(b, a));
+        assert_eq!(standings, vec![id(3), id(2), id(0), id(1)]);
+
+        // a full tie goes to the lower id, and dead snakes rank last
+        g.board.snakes[2].health = 99;
+        g.board.snakes[3].health = 0;
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let expected = if id(0).0 < id(2).0 {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+        assert_eq!(compact.compare_snakes(&id(0), &id(2)), expected);
+        assert_eq!(compact.compare_snakes(&id(3), &id(1)), Ordering::Less);
+    }
+
+    #[test]
+    fn test_length_advantage() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.length_advantage(&SnakeId(0)), 7 - 10);
+        assert_eq!(compact.length_advantage(&SnakeId(1)), 10 - 7);
+        assert_eq!(compact.length_advantage(&SnakeId(2)), -10);
+
+        let g = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let alone: CellBoard4Snakes7x7 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(
+            alone.length_advantage(&SnakeId(0)),
+            alone.get_length(&SnakeId(0)) as i32
+        );
+    }
+
+    #[test]
+    fn test_display_snapshot() {
+        let g = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes7x7 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let expected = concat!(
+            "\n",
+            ". . . . . \n",
+            "f 0 s . . \n",
+            ". . s . . \n",
+            ". . s . . \n",
+            "s s s . . \n",
+            "{\"actual_width\":[5],\"cells\":[65537,131073,458753,5,5,5,5,786433,5,5,5,5,1114113,5,5,4,6,1048577,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5,5],\"game_mode\":[3],\"hazard_damage\":[15],\"heads\":[16,0,0,0],\"healths\":[100,0,0,0],\"lengths\":[7,0,0,0],\"turn\":[0],\"you\":[0]}\n",
+        );
+        assert_eq!(compact.to_string(), expected);
+    }
+
+    #[test]
+    fn test_serialize_game_round_trip() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let mut board: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let mut frames = vec![board];
+        for _ in 0..20 {
+            board = board.successors(&Instruments).remove(0).1;
+            frames.push(board);
+        }
+
+        let bytes = CellBoard4Snakes11x11::serialize_game(&frames);
+        assert_eq!(
+            CellBoard4Snakes11x11::deserialize_game(&bytes).unwrap(),
+            frames
+        );
+        let full: usize = frames.iter().map(|frame| frame.to_bytes().len()).sum();
+        // after the first frame only a handful of cells change each turn
+        assert!(bytes.len() * 3 < full);
+
+        assert_eq!(
+            CellBoard4Snakes11x11::deserialize_game(&bytes[..bytes.len() - 1]).unwrap_err(),
+            ConversionError::MalformedBytes
+        );
+        assert_eq!(
+            CellBoard4Snakes11x11::deserialize_game(&CellBoard4Snakes11x11::serialize_game(&[]))
+                .unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_game_rejects_huge_lengths() {
+        let header = |frames: u32, len: u32, runs: u32| {
+            [frames, len, runs]
+                .iter()
+                .flat_map(|n| n.to_le_bytes())
+                .collect_vec()
+        };
+        // a single frame claiming to be 4GiB long, or to have billions of runs
+        for bytes in [
+            header(1, u32::MAX, 0),
+            header(1, 100, u32::MAX),
+            header(u32::MAX, 100, 0),
+        ] {
+            assert_eq!(
+                CellBoard4Snakes11x11::deserialize_game(&bytes).unwrap_err(),
+                ConversionError::MalformedBytes
+            );
+        }
+    }
+
+    #[test]
+    fn test_bytes_and_cache_key_round_trip() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let bytes = compact.to_bytes();
+        assert_eq!(CellBoard4Snakes11x11::from_bytes(&bytes).unwrap(), compact);
+        let key = compact.cache_key();
+        assert_eq!(key.len(), bytes.len() * 2);
+        assert_eq!(
+            CellBoard4Snakes11x11::from_cache_key(&key).unwrap(),
+            compact
+        );
+        let again: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(again.cache_key(), key);
+
+        // a 4 snake board fits in a board with room for 8 snakes
+        let larger = CellBoard8Snakes15x15::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            larger.to_string().lines().next(),
+            compact.to_string().lines().next()
+        );
+        assert_eq!(larger.get_snake_ids(), compact.get_snake_ids());
+        assert_eq!(
+            CellBoard4Snakes11x11::from_bytes(&larger.to_bytes()).unwrap_err(),
+            ConversionError::TooManySnakes { count: 8 }
+        );
+
+        assert_eq!(
+            CellBoard4Snakes11x11::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            ConversionError::MalformedBytes
+        );
+        assert_eq!(
+            CellBoard4Snakes11x11::from_cache_key("zz").unwrap_err(),
+            ConversionError::MalformedBytes
+        );
+    }
+
+    #[test]
+    fn test_crop() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        // the enemy's head pokes in to this region, so it is dropped
+        let cropped = compact.crop(Position::new(3, 8), 5, 5).unwrap();
+        assert!(matches!(cropped, BestCellBoard::Tiny(_)));
+        cropped.with_board(|b| {
+            assert_eq!((b.width(), b.height()), (5, 5));
+            assert_eq!(b.snake_ids(), vec![SnakeId(0)]);
+            assert_eq!(b.head(SnakeId(0)), Position::new(1, 2));
+            assert_eq!(b.health(SnakeId(0)), 58);
+            assert!(b.food().is_empty());
+        });
+
+        let cropped = compact.crop(Position::new(3, 8), 7, 7).unwrap();
+        assert!(matches!(cropped, BestCellBoard::SmallExact(_)));
+        cropped.with_board(|b| {
+            assert_eq!(b.snake_ids(), vec![SnakeId(0), SnakeId(1)]);
+            assert_eq!(b.head(SnakeId(1)), Position::new(4, 5));
+            assert_eq!(b.length(SnakeId(1)), 10);
+            assert_eq!(b.food(), vec![Position::new(1, 1)]);
+            assert_eq!(
+                b.is_hazard(Position::new(0, 0)),
+                compact.is_hazard(&CellIndex::new(Position::new(3, 2), 11))
+            );
+        });
+
+        // you are cut
+        assert!(compact.crop(Position::new(5, 8), 5, 5).is_none());
+        // the region runs off the board
+        assert!(compact.crop(Position::new(3, 8), 9, 5).is_none());
+        assert!(compact.crop(Position::new(3, 3), 5, 5).is_none());
+    }
+
+    #[test]
+    fn test_area_and_perimeter_cells() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.area(), 121);
+        let perimeter = compact.perimeter_cells().collect_vec();
+        assert_eq!(perimeter.len(), 40);
+        assert_eq!(&perimeter[..12], &(0..12).map(CellIndex).collect_vec()[..]);
+        assert!(!perimeter.contains(&CellIndex(12)));
+        assert!(perimeter.contains(&CellIndex(21)));
+
+        // only the playable part of a larger backing board counts
+        let g = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes7x7 = g.as_cell_board(&snake_id_mapping).unwrap();
+        assert_eq!(compact.area(), 25);
+        assert_eq!(compact.perimeter_cells().count(), 16);
+    }
+
+    #[test]
+    fn test_wall_distance_map() {
+        let g = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let distances = compact.wall_distance_map();
+        assert_eq!(distances[CellIndex::<u8>::from_xy(0, 0, 11).as_usize()], 0);
+        assert_eq!(distances[CellIndex::<u8>::from_xy(10, 4, 11).as_usize()], 0);
+        assert_eq!(distances[CellIndex::<u8>::from_xy(1, 1, 11).as_usize()], 1);
+        assert_eq!(distances[CellIndex::<u8>::from_xy(3, 8, 11).as_usize()], 2);
+        assert_eq!(distances[CellIndex::<u8>::from_xy(5, 5, 11).as_usize()], 5);
+        let perimeter = compact.perimeter_cells().collect_vec();
+        for (idx, distance) in distances.iter().enumerate().take(121) {
+            assert_eq!(
+                *distance == 0,
+                perimeter.contains(&CellIndex::from_usize(idx))
+            );
+        }
+
+        // the playable 5x5 of a 7x7 board peaks at 2 and leaves the rest at 0
+        let g = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes7x7 = g.as_cell_board(&snake_id_mapping).unwrap();
+        let distances = compact.wall_distance_map();
+        assert_eq!(distances.iter().max(), Some(&2));
+        assert_eq!(distances[CellIndex::<u8>::from_xy(2, 2, 5).as_usize()], 2);
+        assert!(distances[25..].iter().all(|d| *d == 0));
+    }
+
+    #[test]
+    fn test_food_approach_cells() {
+        let g = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes7x7 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        // the only food is at (0, 3), and our head at (1, 3) is not an approach cell
+        assert_eq!(
+            compact.food_approach_cells(),
+            vec![CellIndex(2 * 5), CellIndex(4 * 5)]
+        );
+    }
+
+    #[test]
+    fn test_food_within() {
+        let g = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes7x7 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        // the only food is right next to our head
+        assert!(!compact.food_within(&SnakeId(0), 0));
+        assert!(compact.food_within(&SnakeId(0), 1));
+        assert!(!compact.food_within(&SnakeId(3), 10));
+    }
+
+    #[test]
+    fn test_dyn_cell_board_holds_mixed_sizes() {
+        use crate::compact_representation::{DynCellBoard, WrappedCellBoard4Snakes11x11};
+
+        let small = game_fixture(include_str!("../../../fixtures/goes_for_food.json"));
+        let small_ids = build_snake_id_map(&small);
+        let small: CellBoard4Snakes7x7 = small.as_cell_board(&small_ids).unwrap();
+        let large = game_fixture(include_str!("../../../fixtures/start_of_game.json"));
+        let large_ids = build_snake_id_map(&large);
+        let large = CellBoard8Snakes15x15::convert_from_game(large, &large_ids).unwrap();
+        let wrapped = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let wrapped_ids = build_snake_id_map(&wrapped);
+        let wrapped =
+            WrappedCellBoard4Snakes11x11::convert_from_game(wrapped, &wrapped_ids).unwrap();
+
+        let boards: Vec<Box<dyn DynCellBoard>> =
+            vec![Box::new(small), Box::new(large), Box::new(wrapped)];
+        assert_eq!(
+            boards.iter().map(|b| b.width()).collect_vec(),
+            vec![5, 11, 11]
+        );
+        assert_eq!(
+            boards[0].head(SnakeId(0)),
+            small.get_head_as_position(&SnakeId(0))
+        );
+        assert_eq!(
+            boards[0].body(SnakeId(0)).len(),
+            small.get_length(&SnakeId(0)) as usize
+        );
+        assert_eq!(boards[1].snake_ids().len(), 4);
+
+        for board in boards.iter() {
+            let moves = board.reasonable_moves();
+            let results = board.simulate_with_moves(&Instruments, &moves);
+            // moves that are certain death are pruned, so this is at most the full product
+            let product: usize = moves.iter().map(|(_, mvs)| mvs.len()).product();
+            assert!(!results.is_empty() && results.len() <= product);
+            for (taken, next) in results {
+                assert_eq!(taken.len(), moves.len());
+                assert_eq!(next.width(), board.width());
+            }
+        }
+
+        let copy = boards[0].clone();
+        assert_eq!(copy.to_string(), boards[0].to_string());
+    }
+
+    #[test]
+    fn test_set_hazard() {
+        let mut c: Cell<u8> = Cell::empty();
+        c.set_food();
+        assert!(c.is_food());
+        c.set_hazard();
+        assert!(c.is_food());
+        assert!(c.is_hazard());
+        assert!(!c.is_head());
+        assert!(!c.is_body());
+    }
+
+    #[test]
+    fn test_clear_hazard() {
+        let mut c: Cell<u8> = Cell::empty();
+        c.set_food();
+        assert!(c.is_food());
+        c.set_hazard();
+        c.clear_hazard();
+        assert!(c.is_food());
+        assert!(!c.is_hazard());
+        assert!(!c.is_head());
+        assert!(!c.is_body());
+        let mut c: Cell<u8> = Cell::make_double_stacked_piece(SnakeId(0), CellIndex(0));
+        c.set_hazard();
+        c.clear_hazard();
+        assert!(c.is_body());
+        assert!(!c.is_hazard());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut c: Cell<u8> = Cell::make_body_piece(SnakeId(3), CellIndex(17));
+        c.remove();
+        c.set_hazard();
+        assert!(c.is_empty());
+        assert!(c.is_hazard());
+        assert!(c.get_snake_id().is_none());
+        assert!(c.get_idx() == CellIndex(0));
+    }
+    #[test]
+    fn test_set_food() {
+        let mut c: Cell<u8> = Cell::empty();
+        c.set_food();
+        c.set_hazard();
+        assert!(c.is_food());
+        assert!(c.is_hazard());
+        assert!(c.get_snake_id().is_none());
+        assert!(c.get_idx() == CellIndex(0));
+    }
+
+    #[test]
+    fn test_set_head() {
+        let mut c: Cell<u8> = Cell::empty();
+        c.set_head(SnakeId(3), CellIndex(17));
+        c.set_hazard();
+        assert!(c.is_head());
+        assert!(c.is_hazard());
+        assert!(c.get_snake_id().unwrap() == SnakeId(3));
+        assert!(c.get_idx() == CellIndex(17));
+    }
+
+    #[test]
+    fn test_food_queryable() {
+        let game_fixture = include_str!("../../../fixtures/late_stage.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        assert!(!compact.is_food(&CellIndex(6 * 11 + 4)));
+
+        assert!(compact.is_food(&CellIndex(2 * 11)));
+        assert!(compact.is_food(&CellIndex(9 * 11)));
+        assert!(compact.is_food(&CellIndex(3 * 11 + 4)));
+    }
+
+    #[test]
+    fn test_neighbors_and_possible_moves_start_of_game() {
+        let game_fixture = include_str!("../../../fixtures/start_of_game.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let head = compact.get_head_as_native_position(&SnakeId(0));
+        assert_eq!(head, CellIndex(8 * 11 + 5));
+
+        let expected_possible_moves = vec![
+            (Move::Up, CellIndex(9 * 11 + 5)),
+            (Move::Down, CellIndex(7 * 11 + 5)),
+            (Move::Left, CellIndex(8 * 11 + 4)),
+            (Move::Right, CellIndex(8 * 11 + 6)),
+        ];
+
+        assert_eq!(
+            compact.possible_moves(&head).collect::<Vec<_>>(),
+            expected_possible_moves
+        );
+
+        assert_eq!(
+            compact.neighbors(&head).collect::<Vec<_>>(),
+            expected_possible_moves
+                .into_iter()
+                .map(|(_, pos)| pos)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_neighbors_and_possible_moves_cornered() {
+        let game_fixture = include_str!("../../../fixtures/cornered.json");
+        let g: Result<DEGame, _> = serde_json::from_slice(game_fixture.as_bytes());
+        let g = g.expect("the json literal is valid");
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let head = compact.get_head_as_native_position(&SnakeId(0));
+        assert_eq!(head, CellIndex(10 * 11));
+
+        let expected_possible_moves = vec![
+            (Move::Down, CellIndex(9 * 11)),
+            (Move::Right, CellIndex(10 * 11 + 1)),
+        ];
 
         assert_eq!(
             compact.possible_moves(&head).collect::<Vec<_>>(),
@@ -502,6 +3365,117 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_no_reasonable_moves_falls_back_to_all_moves() {
+        use rand::SeedableRng;
+
+        let empty = CellContents::Empty { hazards: 0 };
+        let snake = |id, segment, stack| CellContents::Snake {
+            id: SnakeId(id),
+            segment,
+            stack,
+            hazards: 0,
+        };
+        // we are in the bottom left corner, boxed in by our stacked tail and the other snake
+        let mut grid = vec![vec![empty; 3]; 3];
+        grid[0][0] = snake(0, 0, 1);
+        grid[0][1] = snake(0, 1, 2);
+        grid[1][1] = snake(1, 0, 1);
+        grid[1][0] = snake(1, 1, 1);
+        grid[2][0] = snake(1, 2, 1);
+        let compact = CellBoard4Snakes7x7::from_grid(&grid, 0).unwrap();
+
+        let (_, moves) = compact.reasonable_moves_for_each_snake().next().unwrap();
+        assert_eq!(moves, Move::all().to_vec());
+
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(42);
+        let picked = (0..100)
+            .map(|_| {
+                compact
+                    .random_reasonable_move_for_each_snake(&mut rng)
+                    .next()
+                    .unwrap()
+                    .1
+            })
+            .unique()
+            .count();
+        assert_eq!(picked, 4);
+    }
+
+    #[test]
+    fn test_is_move_immediately_fatal() {
+        let g = game_fixture(include_str!("../../../fixtures/cornered.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+        // we are in the top left corner
+        assert!(compact.is_move_immediately_fatal(&SnakeId(0), Move::Up));
+        assert!(compact.is_move_immediately_fatal(&SnakeId(0), Move::Left));
+
+        for fixture in [
+            include_str!("../../../fixtures/late_stage.json"),
+            include_str!("../../../fixtures/tail_chase.json"),
+            include_str!("../../../fixtures/start_of_game.json"),
+        ] {
+            let g = game_fixture(fixture);
+            let snake_id_mapping = build_snake_id_map(&g);
+            let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+            for (sid, reasonable) in compact.reasonable_moves_for_each_snake() {
+                let not_fatal = Move::all_iter()
+                    .filter(|mv| !compact.is_move_immediately_fatal(&sid, *mv))
+                    .collect_vec();
+                assert_eq!(not_fatal, reasonable);
+            }
+        }
+    }
+
+    #[cfg(feature = "bitboard")]
+    #[test]
+    fn test_body_masks() {
+        use crate::compact_representation::BitBoard;
+        use crate::types::{HeadGettableGame, SnakeBodyGettableGame};
+
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let mut union = BitBoard::empty();
+        for sid in compact.get_snake_ids() {
+            let mask = compact.body_mask(&sid);
+            let body = compact.get_snake_body_vec(&sid);
+            let unique = body.iter().unique().count() as u32;
+            assert_eq!(mask.count(), unique);
+            for pos in body {
+                assert!(mask.contains(pos.as_usize()));
+            }
+            assert!(mask.contains(compact.get_head_as_native_position(&sid).as_usize()));
+            assert!((union & mask).is_empty());
+            union |= mask;
+        }
+        assert_eq!(union, compact.all_bodies_mask());
+    }
+
+    #[cfg(feature = "bitboard")]
+    #[test]
+    fn test_body_frontier() {
+        let g = game_fixture(include_str!("../../../fixtures/late_stage.json"));
+        let snake_id_mapping = build_snake_id_map(&g);
+        let compact: CellBoard4Snakes11x11 = g.as_cell_board(&snake_id_mapping).unwrap();
+
+        let frontier = compact.body_frontier();
+        let bodies = compact.all_bodies_mask();
+        assert!(!frontier.is_empty());
+        assert!((frontier & bodies).is_empty());
+        for idx in 0..11 * 11 {
+            let touches_body = compact
+                .neighbors(&CellIndex(idx as u8))
+                .any(|neighbor| bodies.contains(neighbor.as_usize()));
+            assert_eq!(
+                frontier.contains(idx),
+                !bodies.contains(idx) && touches_body
+            );
+        }
+    }
+
     #[test]
     fn test_tail_chase() {
         let game_fixture = include_str!("../../../fixtures/tail_chase.json");
@@ -1,4 +1,5 @@
 //! A compact board representation that is efficient for simulation
+use crate::impl_best_cell_board_dispatch;
 use crate::impl_common_board_traits;
 use crate::types::*;
 
@@ -19,7 +20,7 @@ use crate::{
     wire_representation::Position,
 };
 
-use super::core::{simulate_with_moves, EvaluateMode};
+use super::core::{simulate_with_moves, CellContents, ConversionError, EvaluateMode};
 use super::core::{CellBoard as CCB, CellIndex};
 use super::dimensions::{ArcadeMaze, Custom, Dimensions, Fixed, Square};
 use super::CellNum as CN;
@@ -41,15 +42,37 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
         self.embedded.assert_consistency()
     }
 
-    /// creates a wrapped board from a Wire Representation game
-    pub fn convert_from_game(game: Game, snake_ids: &SnakeIDMap) -> Result<Self, Box<dyn Error>> {
-        if game.game.ruleset.name != "wrapped" {
-            return Err("only wrapped games are supported".into());
+    /// creates a wrapped board from a Wire Representation game, any other ruleset is rejected
+    /// with `ConversionError::UnsupportedRuleset`
+    pub fn convert_from_game(game: Game, snake_ids: &SnakeIDMap) -> Result<Self, ConversionError> {
+        if !game.is_wrapped() {
+            return Err(ConversionError::UnsupportedRuleset {
+                name: game.game.ruleset.name,
+            });
         }
         let embedded = CCB::convert_from_game(game, snake_ids)?;
         Ok(CellBoard { embedded })
     }
 
+    /// Builds a board from rows of cells indexed as `grid[y][x]`, the inverse of `to_grid`.
+    ///
+    /// Every snake gets 100 health and `SnakeId(0)` is you. Each snake's cells must form a chain
+    /// ordered by `segment`: the head is segment 0, every following cell's segment is the previous
+    /// cell's segment plus its `stack`, and consecutive cells must be adjacent. Only the tail may be
+    /// stacked, and a stack of 3 is only valid for a snake that occupies a single cell. Anything
+    /// else is rejected with `ConversionError::MalformedSnake`
+    pub fn from_grid(
+        grid: &[Vec<CellContents>],
+        hazard_damage: u8,
+    ) -> Result<Self, ConversionError> {
+        let embedded = CCB::from_grid(grid, hazard_damage, EvaluateMode::Wrapped)?;
+        Ok(CellBoard { embedded })
+    }
+
+    fn evaluate_mode(&self) -> EvaluateMode {
+        EvaluateMode::Wrapped
+    }
+
     /// for debugging, packs this board into a custom json representation
     pub fn pack_as_hash(&self) -> HashMap<String, Vec<u32>> {
         self.embedded.pack_as_hash()
@@ -76,7 +99,12 @@ pub type CellBoard8SnakesSquare15x15 = CellBoard<u8, Square, { 15 * 15 }, 8>;
 /// Used to represent the largest UI Selectable board with 8 snakes.
 pub type CellBoard8SnakesSquare25x25 = CellBoard<u16, Custom, { 25 * 25 }, 8>;
 
-/// Used to represent an absolutely silly game board
+/// Used to represent an absolutely silly game board.
+///
+/// Boards store their cells inline so they can be copied cheaply while simulating, so this one
+/// is about 10KiB and conversion and every simulated move put a copy of it on the stack. That is
+/// fine for the default thread stacks, but keep it in mind for deep recursive searches on threads
+/// with small stacks. `BestCellBoard` boxes the board once it is built
 pub type CellBoard16SnakesSquare50x50 = CellBoard<u16, Custom, { 50 * 50 }, 16>;
 
 /// Enum that holds a Cell Board sized right for the given game
@@ -104,41 +132,89 @@ pub enum BestCellBoard {
     Silly(Box<CellBoard16SnakesSquare50x50>),
 }
 
+impl_best_cell_board_dispatch!(BestCellBoard);
+
 /// Trait to get the best sized cellboard for the given game. It returns the smallest Compact board
 /// that has enough room to fit the given Wire game. If the game can't fit in any of our Compact
 /// boards we panic. However the largest board available is MUCH larger than the biggest selectable
 /// board in the Battlesnake UI
+///
+/// The boards are tried in this order and the first one the game fits in is used. If you want
+/// your own ladder, e.g. to keep your enum small, these are the thresholds to copy
+///
+/// | variant | width x height | snakes | cells |
+/// |---|---|---|---|
+/// | `SmallExact` | exactly 7x7 | up to 4 | u8 |
+/// | `Tiny` | square, up to 7x7 | up to 4 | u8 |
+/// | `MediumExact` | exactly 11x11 | up to 4 | u8 |
+/// | `Standard` | square, up to 11x11 | up to 4 | u8 |
+/// | `LargestU8` | square, up to 15x15 | up to 8 | u8 |
+/// | `LargeExact` | exactly 19x19 | up to 4 | u16 |
+/// | `ArcadeMaze` | exactly 19x21 | up to 4 | u16 |
+/// | `ArcadeMaze8Snake` | exactly 19x21 | up to 8 | u16 |
+/// | `Large` | up to 25 wide and 24 high | up to 8 | u16 |
+/// | `Silly` | up to 50x50 | up to 16 | u16 |
 pub trait ToBestCellBoard {
     #[allow(missing_docs)]
     fn to_best_cell_board(self) -> Result<BestCellBoard, Box<dyn Error>>;
+
+    /// Like `to_best_cell_board`, but skips every board with more than `MAX_CELLS` cells. A game
+    /// that only fits in a bigger board returns `ConversionError::BoardTooLarge`, so
+    /// `to_best_cell_board_up_to::<{ 15 * 15 }>()` only ever gives the u8 boards
+    fn to_best_cell_board_up_to<const MAX_CELLS: usize>(
+        self,
+    ) -> Result<BestCellBoard, Box<dyn Error>>;
 }
 
 impl ToBestCellBoard for Game {
     fn to_best_cell_board(self) -> Result<BestCellBoard, Box<dyn Error>> {
+        self.to_best_cell_board_up_to::<{ usize::MAX }>()
+    }
+
+    fn to_best_cell_board_up_to<const MAX_CELLS: usize>(
+        self,
+    ) -> Result<BestCellBoard, Box<dyn Error>> {
         let width = self.board.width;
         let height = self.board.height;
         let num_snakes = self.board.snakes.len();
         let id_map = build_snake_id_map(&self);
+        let fits = |cells: usize| {
+            if cells > MAX_CELLS {
+                Err(ConversionError::BoardTooLarge { width, height })
+            } else {
+                Ok(())
+            }
+        };
 
         let best_board = if width == 7 && height == 7 && num_snakes <= 4 {
+            fits(7 * 7)?;
             BestCellBoard::SmallExact(Box::new(CellBoard::convert_from_game(self, &id_map)?))
-        } else if width <= 7 && height <= 7 && num_snakes <= 4 {
+        } else if width == height && width <= 7 && num_snakes <= 4 {
+            fits(7 * 7)?;
             BestCellBoard::Tiny(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 11 && height == 11 && num_snakes <= 4 {
+            fits(11 * 11)?;
             BestCellBoard::MediumExact(Box::new(CellBoard::convert_from_game(self, &id_map)?))
-        } else if width <= 11 && height <= 11 && num_snakes <= 4 {
+        } else if width == height && width <= 11 && num_snakes <= 4 {
+            fits(11 * 11)?;
             BestCellBoard::Standard(Box::new(CellBoard::convert_from_game(self, &id_map)?))
-        } else if width <= 15 && height <= 15 && num_snakes <= 8 {
+        } else if width == height && width <= 15 && num_snakes <= 8 {
+            fits(15 * 15)?;
             BestCellBoard::LargestU8(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 19 && height == 19 && num_snakes <= 4 {
+            fits(19 * 19)?;
             BestCellBoard::LargeExact(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 19 && height == 21 && num_snakes <= 4 {
+            fits(19 * 21)?;
             BestCellBoard::ArcadeMaze(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width == 19 && height == 21 && num_snakes <= 8 {
+            fits(19 * 21)?;
             BestCellBoard::ArcadeMaze8Snake(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width <= 25 && height < 25 && num_snakes <= 8 {
+            fits(25 * 25)?;
             BestCellBoard::Large(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else if width <= 50 && height <= 50 && num_snakes <= 16 {
+            fits(50 * 50)?;
             BestCellBoard::Silly(Box::new(CellBoard::convert_from_game(self, &id_map)?))
         } else {
             panic!("No board was big enough")
@@ -169,42 +245,39 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> Rea
         &self,
     ) -> Box<dyn std::iter::Iterator<Item = (SnakeId, Vec<Move>)> + '_> {
         let width = self.embedded.get_actual_width();
-        Box::new(
-            self.embedded
-                .iter_healths()
-                .enumerate()
-                .filter(|(_, health)| **health > 0)
-                .map(move |(idx, _)| {
-                    let head_pos = self.get_head_as_position(&SnakeId(idx as u8));
-
-                    let mvs = IntoIterator::into_iter(Move::all())
-                        .filter(|mv| {
-                            let mut new_head = head_pos.add_vec(mv.to_vector());
-                            let wrapped_x = new_head.x.rem_euclid(self.get_width() as i32);
-                            let wrapped_y = new_head.y.rem_euclid(self.get_height() as i32);
-
-                            new_head = Position {
-                                x: wrapped_x,
-                                y: wrapped_y,
-                            };
-
-                            let ci = CellIndex::new(new_head, width);
-
-                            if self.off_board(new_head) {
-                                return false;
-                            };
-
-                            !self.off_board(new_head)
-                                && ((!self.embedded.cell_is_body(ci)
-                                    && !self.embedded.cell_is_snake_head(ci))
-                                    || self.embedded.cell_is_single_tail(ci))
-                        })
-                        .collect_vec();
-                    let mvs = if mvs.is_empty() { vec![Move::Up] } else { mvs };
-
-                    (SnakeId(idx as u8), mvs)
-                }),
-        )
+        Box::new(self.embedded.alive_heads().map(move |(sid, head)| {
+            let head_pos = head.into_position(width);
+
+            let mvs = IntoIterator::into_iter(Move::all())
+                .filter(|mv| {
+                    let mut new_head = head_pos.add_vec(mv.to_vector());
+                    let wrapped_x = new_head.x.rem_euclid(self.get_width() as i32);
+                    let wrapped_y = new_head.y.rem_euclid(self.get_height() as i32);
+
+                    new_head = Position {
+                        x: wrapped_x,
+                        y: wrapped_y,
+                    };
+
+                    let ci = CellIndex::new(new_head, width);
+
+                    if self.off_board(new_head) {
+                        return false;
+                    };
+
+                    !self.off_board(new_head) && self.embedded.will_be_free_next_turn(ci)
+                })
+                .collect_vec();
+            // with no reasonable move every move is fatal, so offer all of them rather than
+            // always picking the same one
+            let mvs = if mvs.is_empty() {
+                Move::all().to_vec()
+            } else {
+                mvs
+            };
+
+            (sid, mvs)
+        }))
     }
 }
 
@@ -246,7 +319,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     fn possible_moves<'a>(
         &'a self,
         pos: &Self::NativePositionType,
-    ) -> Box<(dyn std::iter::Iterator<Item = (Move, CellIndex<T>)> + 'a)> {
+    ) -> Box<dyn std::iter::Iterator<Item = (Move, CellIndex<T>)> + 'a> {
         let width = self.embedded.get_actual_width();
         let head_pos = pos.into_position(width);
 
@@ -267,7 +340,7 @@ impl<T: CN, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize>
     fn neighbors<'a>(
         &'a self,
         pos: &Self::NativePositionType,
-    ) -> Box<(dyn Iterator<Item = CellIndex<T>> + 'a)> {
+    ) -> Box<dyn Iterator<Item = CellIndex<T>> + 'a> {
         Box::new(self.possible_moves(pos).map(|(_, ci)| ci))
     }
 }
@@ -280,17 +353,20 @@ mod test {
     use rand::{RngCore, SeedableRng};
 
     use crate::{
-        compact_representation::core::Cell,
+        compact_representation::{core::Cell, ConversionError},
         game_fixture,
         types::{
-            build_snake_id_map, HeadGettableGame, HealthGettableGame, Move,
+            build_snake_id_map, HazardQueryableGame, HeadGettableGame, HealthGettableGame, Move,
             NeighborDeterminableGame, RandomReasonableMovesGame, ReasonableMovesGame,
             SimulableGame, SimulatorInstruments, SnakeId,
         },
         wire_representation::Position,
     };
 
-    use super::{CellBoard4SnakesSquare11x11, CellIndex};
+    use super::{
+        BestCellBoard, CellBoard, CellBoard4SnakesSquare11x11, CellContents, CellIndex, Fixed,
+        ToBestCellBoard,
+    };
 
     #[derive(Debug)]
     struct Instruments {}
@@ -306,6 +382,10 @@ mod test {
         let snake_ids = build_snake_id_map(&g);
         let orig_wrapped_cell: CellBoard4SnakesSquare11x11 =
             g.as_wrapped_cell_board(&snake_ids).unwrap();
+        assert_eq!(
+            orig_wrapped_cell.game_mode(),
+            Some(crate::compact_representation::GameMode::Wrapped)
+        );
         let hash = orig_wrapped_cell.pack_as_hash();
         eprintln!("{}", serde_json::to_string(&hash).unwrap());
         eprintln!(
@@ -321,6 +401,138 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_best_cell_board_up_to() {
+        let arcade = game_fixture(include_str!("../../../fixtures/arcade_maze_map.json"));
+        assert!(arcade
+            .clone()
+            .to_best_cell_board_up_to::<{ 15 * 15 }>()
+            .is_err());
+        match arcade.to_best_cell_board_up_to::<{ 19 * 21 }>() {
+            Ok(BestCellBoard::ArcadeMaze(_)) => {}
+            _ => panic!("expected arcade maze board"),
+        }
+    }
+
+    #[test]
+    fn test_reachable_all_wraps() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let wrapped: CellBoard4SnakesSquare11x11 = g.as_wrapped_cell_board(&snake_ids).unwrap();
+        assert_eq!(wrapped.reachable_all(), [51, 53, 0, 0]);
+    }
+
+    #[test]
+    fn test_wrapped_board_has_no_perimeter() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let wrapped: CellBoard4SnakesSquare11x11 = g.as_wrapped_cell_board(&snake_ids).unwrap();
+        assert_eq!(wrapped.area(), 121);
+        assert_eq!(wrapped.perimeter_cells().count(), 0);
+        assert!(wrapped.wall_distance_map()[..121]
+            .iter()
+            .all(|d| *d == u8::MAX));
+    }
+
+    #[test]
+    fn test_nearest_enemy_head_wraps() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let wrapped: CellBoard4SnakesSquare11x11 = g.as_wrapped_cell_board(&snake_ids).unwrap();
+        // (4, 7) to (1, 0) is 10 moves across the board, but only 7 going over the top edge
+        assert_eq!(
+            wrapped.nearest_enemy_head(&SnakeId(0)),
+            Some((SnakeId(1), 7))
+        );
+    }
+
+    #[test]
+    fn test_food_within_wraps() {
+        let mut g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        g.board.food.push(Position::new(4, 0));
+        let snake_ids = build_snake_id_map(&g);
+        let wrapped: CellBoard4SnakesSquare11x11 = g.as_wrapped_cell_board(&snake_ids).unwrap();
+        // (4, 7) to (4, 0) is 7 moves down, but only 4 going over the top edge
+        assert!(wrapped.food_within(&SnakeId(0), 4));
+        assert!(!wrapped.food_within(&SnakeId(0), 3));
+    }
+
+    #[test]
+    fn test_snake_centroid_is_head_when_wrapped() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let wrapped: CellBoard4SnakesSquare11x11 = g.as_wrapped_cell_board(&snake_ids).unwrap();
+        assert_eq!(
+            wrapped.snake_centroid(&SnakeId(0)),
+            Some(wrapped.get_head_as_position(&SnakeId(0)))
+        );
+    }
+
+    #[test]
+    fn test_off_board_snake_is_rejected() {
+        let mut g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let id = g.board.snakes[0].id.clone();
+        // wrapping only applies to moves, a position past the edge is still malformed
+        for pos in g.board.snakes[0].body.iter_mut() {
+            pos.x += 11;
+        }
+        g.board.snakes[0].head = g.board.snakes[0].body[0];
+
+        let err = CellBoard4SnakesSquare11x11::convert_from_game(g, &snake_ids).unwrap_err();
+        assert_eq!(err, ConversionError::BodyOffBoard { id });
+    }
+
+    #[test]
+    fn test_fixed_dimensions_reject_other_sizes() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_ids = build_snake_id_map(&g);
+        // an 11x11 game fits in the storage, but not in the fixed 19x19 dimensions
+        let err = CellBoard::<u16, Fixed<19, 19>, { 19 * 19 }, 4>::convert_from_game(g, &snake_ids)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "game size 11x11 doesn't fit in the given board size"
+        );
+
+        // grids are held to the same size
+        let grid = vec![vec![CellContents::Empty { hazards: 0 }; 11]; 11];
+        assert_eq!(
+            CellBoard::<u16, Fixed<19, 19>, { 19 * 19 }, 4>::from_grid(&grid, 0).unwrap_err(),
+            ConversionError::BoardTooLarge {
+                width: 11,
+                height: 11
+            }
+        );
+    }
+
+    #[test]
+    fn test_open_degree_counts_wrapped_neighbors() {
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 11]; 11];
+        grid[0][10] = CellContents::Snake {
+            id: SnakeId(0),
+            segment: 0,
+            stack: 3,
+            hazards: 0,
+        };
+        let wrapped = CellBoard4SnakesSquare11x11::from_grid(&grid, 0).unwrap();
+        assert_eq!(wrapped.open_degree(CellIndex(0)), 3);
+        assert_eq!(wrapped.open_degree(CellIndex(5 * 11 + 5)), 4);
+    }
+
+    #[test]
+    fn test_from_grid_round_trips() {
+        let g = game_fixture(include_str!("../../../fixtures/wrapped_fixture.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let wrapped: CellBoard4SnakesSquare11x11 = g.as_wrapped_cell_board(&snake_ids).unwrap();
+
+        let grid = wrapped.to_grid();
+        let imported =
+            CellBoard4SnakesSquare11x11::from_grid(&grid, wrapped.get_hazard_damage()).unwrap();
+        assert!(imported.assert_consistency());
+        assert_eq!(imported.to_grid(), grid);
+    }
+
     #[test]
     fn test_cell_round_trips() {
         let mut c: Cell<u8> = Cell::empty();
@@ -391,7 +603,6 @@ mod test {
         for _ in 0..15 {
             let move_map = wrapped
                 .random_reasonable_move_for_each_snake(&mut rng)
-                .into_iter()
                 .map(|(sid, mv)| (sid, [mv]))
                 .collect_vec();
             wrapped = wrapped
@@ -494,7 +705,8 @@ mod test {
             dbg!(mvs);
             eprintln!("{}", g);
             g.assert_consistency();
-            g.simulate(&instruments, &compact_ids.clone()).for_each(drop);
+            g.simulate(&instruments, &compact_ids.clone())
+                .for_each(drop);
         }
         {
             let snakes_and_moves = vec![
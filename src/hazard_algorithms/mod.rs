@@ -106,7 +106,7 @@ fn next_perfect_odd_square(n: u16) -> u16 {
     // next square base
     // e.g. if the input is 2, this is 2
     let mut next_base = current_base + 1;
-    if next_base % 2 == 0 {
+    if next_base.is_multiple_of(2) {
         next_base += 1;
     }
 
@@ -164,7 +164,10 @@ impl ForwardOnlyHazardAlgorithm<Position> for SpiralHazard {
 
     fn inc_turn(&mut self) -> Box<dyn Iterator<Item = Position>> {
         self.current_turn += 1;
-        if self.current_turn % self.hazard_every_turns as u16 == 0 {
+        if self
+            .current_turn
+            .is_multiple_of(self.hazard_every_turns as u16)
+        {
             let turns_elapsed = self.current_turn - self.first_turn_seen;
             // plus 1 because the seed cell
             let spawns_elapsed = (turns_elapsed / self.hazard_every_turns as u16) + 1;
@@ -340,7 +343,7 @@ mod tests {
             } else {
                 let new_hazards = hazard_alg.inc_turn();
                 maintained_hazards.extend(new_hazards);
-                let hazards_set = HashSet::from_iter(game.board.hazards.into_iter());
+                let hazards_set = HashSet::from_iter(game.board.hazards);
                 assert!(hazard_alg.current_turn == game.turn as u16);
                 assert!(hazards_set == maintained_hazards);
             }
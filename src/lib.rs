@@ -33,6 +33,10 @@ use wire_representation::Game;
 
 pub mod compact_representation;
 pub mod hazard_algorithms;
+pub mod rollout;
+#[cfg(feature = "search")]
+pub mod search;
+pub mod transposition;
 pub mod types;
 pub mod wire_representation;
 
@@ -0,0 +1,368 @@
+//! Random playouts (rollouts) for Monte Carlo style evaluation of games
+//!
+//! A rollout plays a game forward from a given position by choosing a random reasonable move for
+//! every snake each turn and spawning food with the standard rules, until the game is over.
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+use rand::Rng;
+
+use crate::types::{
+    Move, RandomReasonableMovesGame, SimulableGame, SimulatorInstruments, SnakeIDGettableGame,
+    SnakeId, StandardFoodPlaceableGame, VictorDeterminableGame, YouDeterminableGame,
+};
+
+/// Rollouts are cut off after this many turns, so that a playout where snakes keep finding food
+/// can't run forever. A cut off rollout is reported as a draw
+pub const MAX_ROLLOUT_TURNS: u16 = 1000;
+
+/// The result of a single rollout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameOutcome<const N_SNAKES: usize> {
+    /// The winning snake, `None` for a draw or if the rollout was cut off
+    pub winner: Option<SnakeId>,
+    /// How many turns were played before the game ended
+    pub turns: u16,
+    /// How many turns each snake stayed alive for, indexed by `SnakeId`. A snake that survived
+    /// until the end of the rollout has `turns`, and a snake that was already dead has 0
+    pub survival_turns: [u16; N_SNAKES],
+}
+
+/// Aggregated results of many rollouts, counted from the perspective of the `you` snake
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RolloutStats {
+    /// How many rollouts were played
+    pub rollouts: u32,
+    /// Rollouts won by you
+    pub wins: u32,
+    /// Rollouts won by another snake
+    pub losses: u32,
+    /// Rollouts without a winner
+    pub draws: u32,
+    /// The sum of the number of turns played in every rollout
+    pub total_turns: u64,
+}
+
+impl RolloutStats {
+    /// records the outcome of a single rollout
+    pub fn record<const N_SNAKES: usize>(&mut self, outcome: &GameOutcome<N_SNAKES>, you: SnakeId) {
+        self.rollouts += 1;
+        self.total_turns += outcome.turns as u64;
+        match outcome.winner {
+            Some(winner) if winner == you => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    /// the fraction of rollouts won by you, 0 if no rollouts were played
+    pub fn win_rate(&self) -> f64 {
+        if self.rollouts == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.rollouts as f64
+        }
+    }
+
+    /// the average number of turns a rollout lasted, 0 if no rollouts were played
+    pub fn average_depth(&self) -> f64 {
+        if self.rollouts == 0 {
+            0.0
+        } else {
+            self.total_turns as f64 / self.rollouts as f64
+        }
+    }
+}
+
+/// Plays a single random game forward from `game` until it is over, or until
+/// `MAX_ROLLOUT_TURNS` have been played
+pub fn rollout<G, I, R, const N_SNAKES: usize>(
+    game: &G,
+    instruments: &I,
+    rng: &mut R,
+) -> GameOutcome<N_SNAKES>
+where
+    G: SimulableGame<I, N_SNAKES>
+        + RandomReasonableMovesGame
+        + VictorDeterminableGame
+        + StandardFoodPlaceableGame
+        + Clone,
+    G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+    I: SimulatorInstruments,
+    R: Rng,
+{
+    let mut current = game.clone();
+    let mut turns = 0;
+    let mut survival_turns = [0; N_SNAKES];
+
+    while !current.is_over() && turns < MAX_ROLLOUT_TURNS {
+        let moves = current
+            .random_reasonable_move_for_each_snake(rng)
+            .map(|(sid, mv)| (sid, [mv]))
+            .collect_vec();
+        let next = current
+            .simulate_with_moves(instruments, moves)
+            .next()
+            .map(|(_, next)| next);
+        current = match next {
+            Some(next) => next,
+            None => break,
+        };
+        current.place_food(rng);
+        turns += 1;
+        for sid in current.get_snake_ids() {
+            survival_turns[sid.as_usize()] = turns;
+        }
+    }
+
+    GameOutcome {
+        winner: if current.is_over() {
+            current.get_winner()
+        } else {
+            None
+        },
+        turns,
+        survival_turns,
+    }
+}
+
+/// Runs random rollouts from `game` until the wall clock `budget` runs out, and reports the
+/// results from the perspective of the `you` snake. At least one rollout is always played, so
+/// the budget may be slightly exceeded by the last rollout
+pub fn rollout_with_budget<G, I, R, const N_SNAKES: usize>(
+    game: &G,
+    instruments: &I,
+    budget: Duration,
+    rng: &mut R,
+) -> RolloutStats
+where
+    G: SimulableGame<I, N_SNAKES>
+        + RandomReasonableMovesGame
+        + VictorDeterminableGame
+        + YouDeterminableGame
+        + StandardFoodPlaceableGame
+        + Clone,
+    G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+    I: SimulatorInstruments,
+    R: Rng,
+{
+    let start = Instant::now();
+    let you = *game.you_id();
+    let mut stats = RolloutStats::default();
+
+    loop {
+        let outcome = rollout(game, instruments, rng);
+        stats.record(&outcome, you);
+        if start.elapsed() >= budget {
+            break;
+        }
+    }
+
+    stats
+}
+
+/// Compares two candidate first moves for `me` by their random rollout win rates.
+///
+/// For each move `samples` rollouts are played, where `me` commits to the move on the first turn
+/// and every other snake picks a random reasonable move. Returns `Ordering::Greater` if `a` won
+/// more rollouts than `b`, `Ordering::Less` if `b` won more and `Ordering::Equal` on a tie
+#[allow(clippy::too_many_arguments)]
+pub fn compare_moves<G, I, R, const N_SNAKES: usize>(
+    game: &G,
+    instruments: &I,
+    me: SnakeId,
+    a: Move,
+    b: Move,
+    samples: usize,
+    rng: &mut R,
+) -> Ordering
+where
+    G: SimulableGame<I, N_SNAKES>
+        + RandomReasonableMovesGame
+        + VictorDeterminableGame
+        + StandardFoodPlaceableGame
+        + Clone,
+    G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+    I: SimulatorInstruments,
+    R: Rng,
+{
+    let mut wins_for = |mv: Move| {
+        (0..samples)
+            .filter(|_| rollout_after_move(game, instruments, me, mv, rng).winner == Some(me))
+            .count()
+    };
+    let a_wins = wins_for(a);
+    let b_wins = wins_for(b);
+
+    a_wins.cmp(&b_wins)
+}
+
+/// a rollout where `me` plays `mv` on the first turn. If that kills `me` the rollout stops there,
+/// with `me` eliminated on the first turn
+fn rollout_after_move<G, I, R, const N_SNAKES: usize>(
+    game: &G,
+    instruments: &I,
+    me: SnakeId,
+    mv: Move,
+    rng: &mut R,
+) -> GameOutcome<N_SNAKES>
+where
+    G: SimulableGame<I, N_SNAKES>
+        + RandomReasonableMovesGame
+        + VictorDeterminableGame
+        + StandardFoodPlaceableGame
+        + Clone,
+    G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+    I: SimulatorInstruments,
+    R: Rng,
+{
+    let moves = game
+        .random_reasonable_move_for_each_snake(rng)
+        .map(|(sid, random_move)| (sid, [if sid == me { mv } else { random_move }]))
+        .collect_vec();
+    let next = game
+        .simulate_with_moves(instruments, moves)
+        .next()
+        .map(|(_, next)| next);
+    let mut next = match next {
+        Some(next) if next.get_snake_ids().contains(&me) => next,
+        // playing on from `game` would score a random move instead of `mv`, so the rollout ends
+        // with `me` dead
+        next => {
+            let mut survival_turns = [0; N_SNAKES];
+            let survivors = next.as_ref().map(|next| next.get_snake_ids());
+            for sid in survivors.iter().flatten().filter(|sid| **sid != me) {
+                survival_turns[sid.as_usize()] = 1;
+            }
+            return GameOutcome {
+                winner: next
+                    .filter(|next| next.is_over())
+                    .and_then(|next| next.get_winner()),
+                turns: 1,
+                survival_turns,
+            };
+        }
+    };
+    next.place_food(rng);
+
+    let mut outcome = rollout(&next, instruments, rng);
+    // every snake alive after the first move survived one more turn than the playout counted
+    for sid in next.get_snake_ids() {
+        outcome.survival_turns[sid.as_usize()] += 1;
+    }
+    GameOutcome {
+        turns: outcome.turns + 1,
+        ..outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        compact_representation::StandardCellBoard4Snakes11x11, game_fixture,
+        types::build_snake_id_map,
+    };
+
+    #[derive(Debug)]
+    struct Instruments;
+    impl SimulatorInstruments for Instruments {
+        fn observe_simulation(&self, _: std::time::Duration) {}
+    }
+
+    fn board() -> StandardCellBoard4Snakes11x11 {
+        let g = game_fixture(include_str!("../../fixtures/late_stage.json"));
+        let snake_ids = build_snake_id_map(&g);
+        StandardCellBoard4Snakes11x11::convert_from_game(g, &snake_ids).unwrap()
+    }
+
+    #[test]
+    fn test_rollout_finishes() {
+        let board = board();
+        let mut rng = SmallRng::seed_from_u64(42);
+        let outcome = rollout(&board, &Instruments, &mut rng);
+        assert!(outcome.turns > 0);
+        assert!(outcome.turns <= MAX_ROLLOUT_TURNS);
+
+        // everyone who is alive at the end survived the whole rollout, the others died earlier
+        assert!(outcome.survival_turns.iter().all(|t| *t <= outcome.turns));
+        if let Some(winner) = outcome.winner {
+            assert_eq!(outcome.survival_turns[winner.as_usize()], outcome.turns);
+        }
+        // the late stage fixture only has two living snakes
+        assert_eq!(&outcome.survival_turns[2..], &[0, 0]);
+    }
+
+    #[test]
+    fn test_compare_moves() {
+        let g = game_fixture(include_str!("../../fixtures/cornered.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let board = StandardCellBoard4Snakes11x11::convert_from_game(g, &snake_ids).unwrap();
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        // we are in the top left corner, so moving up or left is instant death
+        assert_eq!(
+            compare_moves(
+                &board,
+                &Instruments,
+                SnakeId(0),
+                Move::Up,
+                Move::Left,
+                10,
+                &mut rng
+            ),
+            Ordering::Equal
+        );
+        assert_ne!(
+            compare_moves(
+                &board,
+                &Instruments,
+                SnakeId(0),
+                Move::Up,
+                Move::Right,
+                10,
+                &mut rng
+            ),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_rollout_after_fatal_move() {
+        let board = board();
+        let mut rng = SmallRng::seed_from_u64(42);
+        // moving down runs in to our own neck
+        for _ in 0..10 {
+            let outcome =
+                rollout_after_move(&board, &Instruments, SnakeId(0), Move::Down, &mut rng);
+            assert_eq!(outcome.turns, 1);
+            assert_eq!(outcome.survival_turns, [0, 1, 0, 0]);
+            assert_eq!(outcome.winner, Some(SnakeId(1)));
+        }
+
+        // the same goes for a snake other than `you`
+        for _ in 0..10 {
+            let outcome =
+                rollout_after_move(&board, &Instruments, SnakeId(1), Move::Down, &mut rng);
+            assert_ne!(outcome.winner, Some(SnakeId(1)));
+            assert_eq!(outcome.survival_turns[1], 0);
+        }
+    }
+
+    #[test]
+    fn test_rollout_with_budget() {
+        let board = board();
+        let mut rng = SmallRng::seed_from_u64(42);
+        let stats = rollout_with_budget(&board, &Instruments, Duration::from_millis(20), &mut rng);
+        assert!(stats.rollouts >= 1);
+        assert_eq!(stats.rollouts, stats.wins + stats.losses + stats.draws);
+        assert!(stats.average_depth() > 0.0);
+        assert!((0.0..=1.0).contains(&stats.win_rate()));
+    }
+}
@@ -0,0 +1,499 @@
+//! A minimal alpha-beta search over any simulable game, a baseline bot to build on
+//!
+//! The search is paranoid: every other snake is assumed to work together against `me`, so each
+//! ply is `me` picking the move with the best worst case over every joint move of the opponents.
+//! Positions are scored by an evaluation function passed in by the caller, [default_eval] scores
+//! the compact boards with their flood fill and danger helpers. [iterative_deepening] searches
+//! as deep as a time budget allows, which is what a bot wants to call every turn.
+
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use itertools::Itertools;
+
+use crate::{
+    compact_representation::{
+        dimensions::Dimensions, CellNum, DangerWeights, StandardCellBoard, WrappedCellBoard,
+    },
+    transposition::TranspositionTable,
+    types::{
+        HealthGettableGame, Move, SimulableGame, SimulatorInstruments, SnakeIDGettableGame,
+        SnakeId, VictorDeterminableGame,
+    },
+};
+
+/// The score of a position where `me` is dead. Losses further from the root score slightly
+/// higher, so the search puts off a death it can't avoid
+pub const LOSS: i32 = -1_000_000;
+
+/// The score of a position where `me` is the last snake standing. Wins closer to the root score
+/// slightly higher. In a game that `me` started alone nothing counts as a win, only as a loss
+pub const WIN: i32 = 1_000_000;
+
+/// Games that have a reasonable evaluation without any tuning, see [default_eval]
+pub trait DefaultEval {
+    /// scores this position for `me`, higher is better
+    fn default_eval(&self, me: SnakeId) -> i32;
+}
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> DefaultEval
+    for StandardCellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    fn default_eval(&self, me: SnakeId) -> i32 {
+        let space = self.reachable_all()[me.as_usize()] as i32;
+        let danger = self.danger_score(&me, &DangerWeights::default());
+        space * 10 - (danger * 100.0) as i32
+    }
+}
+
+impl<T: CellNum, D: Dimensions, const BOARD_SIZE: usize, const MAX_SNAKES: usize> DefaultEval
+    for WrappedCellBoard<T, D, BOARD_SIZE, MAX_SNAKES>
+{
+    fn default_eval(&self, me: SnakeId) -> i32 {
+        let space = self.reachable_all()[me.as_usize()] as i32;
+        let danger = self.danger_score(&me, &DangerWeights::default());
+        space * 10 - (danger * 100.0) as i32
+    }
+}
+
+/// An evaluation to pass to [alphabeta] when you don't have your own: the cells `me` reaches
+/// before anyone else (`reachable_all`), minus its `danger_score` with the default weights
+pub fn default_eval<G: DefaultEval>(game: &G, me: SnakeId) -> i32 {
+    game.default_eval(me)
+}
+
+/// Searches `depth` plies deep and returns `me`'s best move along with its score.
+///
+/// Each ply `me` commits to a move, then every joint move of the other living snakes is
+/// simulated and the worst outcome for `me` counts. Leaves are scored with `eval`, except
+/// that positions where `me` is dead score [LOSS] and positions `me` has won score [WIN].
+/// A depth of 0 searches 1 ply. The tree grows by up to `4^n` boards per ply for `n` snakes,
+/// so keep the depth low on crowded boards
+pub fn alphabeta<G, I, E, const N_SNAKES: usize>(
+    game: &G,
+    instruments: &I,
+    me: SnakeId,
+    depth: u8,
+    eval: E,
+) -> (Move, i32)
+where
+    G: SimulableGame<I, N_SNAKES> + VictorDeterminableGame + HealthGettableGame,
+    G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+    I: SimulatorInstruments,
+    E: Fn(&G, SnakeId) -> i32,
+{
+    let mut search = Search {
+        instruments,
+        me,
+        eval: &eval,
+        ordering: (),
+        deadline: None,
+        timed_out: false,
+        has_opponents: false,
+    };
+    search.root(game, depth.max(1))
+}
+
+/// How many boards the transposition table used by [iterative_deepening] holds
+pub const ITERATIVE_DEEPENING_TABLE_SIZE: usize = 100_000;
+
+/// Runs [alphabeta] at increasing depths until `budget` runs out and returns the best move of
+/// the deepest search that finished, the entry point a bot calls every turn.
+///
+/// The best move found for every position is kept in a [TranspositionTable] and searched first
+/// by the next, deeper iteration, which makes alpha-beta cut off much more of the tree. A search
+/// that runs out of time is thrown away. Depth 1 always finishes, so the budget can be exceeded
+/// on crowded boards, and deepening stops early once a forced win or loss is found
+pub fn iterative_deepening<G, I, E, const N_SNAKES: usize>(
+    game: &G,
+    instruments: &I,
+    me: SnakeId,
+    budget: Duration,
+    eval: E,
+) -> Move
+where
+    G: SimulableGame<I, N_SNAKES> + VictorDeterminableGame + HealthGettableGame,
+    G: SnakeIDGettableGame<SnakeIDType = SnakeId> + Hash + Eq + Clone,
+    I: SimulatorInstruments,
+    E: Fn(&G, SnakeId) -> i32,
+{
+    let deadline = Instant::now() + budget;
+    let mut search = Search {
+        instruments,
+        me,
+        eval: &eval,
+        ordering: TranspositionTable::new(ITERATIVE_DEEPENING_TABLE_SIZE),
+        deadline: None,
+        timed_out: false,
+        has_opponents: false,
+    };
+
+    let (mut best, mut score) = search.root(game, 1);
+    search.deadline = Some(deadline);
+    for depth in 2..=u8::MAX {
+        if score <= LOSS + u8::MAX as i32 || score >= WIN - u8::MAX as i32 {
+            break;
+        }
+        let result = search.root(game, depth);
+        if search.timed_out {
+            break;
+        }
+        (best, score) = result;
+    }
+
+    best
+}
+
+/// Remembers the best move found in positions, so it can be searched first next time
+trait MoveOrdering<G> {
+    fn best_move(&self, game: &G) -> Option<Move>;
+    fn record(&mut self, game: &G, mv: Move);
+}
+
+impl<G> MoveOrdering<G> for () {
+    fn best_move(&self, _: &G) -> Option<Move> {
+        None
+    }
+
+    fn record(&mut self, _: &G, _: Move) {}
+}
+
+impl<G: Hash + Eq + Clone> MoveOrdering<G> for TranspositionTable<G, Move> {
+    fn best_move(&self, game: &G) -> Option<Move> {
+        self.get(game).copied()
+    }
+
+    fn record(&mut self, game: &G, mv: Move) {
+        self.insert(game.clone(), mv);
+    }
+}
+
+/// every move, with `first` moved to the front if there is one
+fn ordered_moves(first: Option<Move>) -> [Move; 4] {
+    let mut moves = Move::all();
+    if let Some(first) = first {
+        moves[..=first.as_index()].rotate_right(1);
+    }
+    moves
+}
+
+struct Search<'a, I, E, O> {
+    instruments: &'a I,
+    me: SnakeId,
+    eval: &'a E,
+    ordering: O,
+    /// once this has passed every node returns straight away and `timed_out` is set
+    deadline: Option<Instant>,
+    timed_out: bool,
+    /// whether any other snake was alive at the root, outliving them is only a win if so
+    has_opponents: bool,
+}
+
+impl<'a, I: SimulatorInstruments, E, O> Search<'a, I, E, O> {
+    fn root<G, const N_SNAKES: usize>(&mut self, game: &G, depth: u8) -> (Move, i32)
+    where
+        G: SimulableGame<I, N_SNAKES> + VictorDeterminableGame + HealthGettableGame,
+        G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+        E: Fn(&G, SnakeId) -> i32,
+        O: MoveOrdering<G>,
+    {
+        self.has_opponents = game
+            .get_snake_ids()
+            .iter()
+            .any(|sid| *sid != self.me && game.is_alive(sid));
+        let mut best = (Move::Up, i32::MIN);
+        let mut alpha = i32::MIN;
+        for mv in ordered_moves(self.ordering.best_move(game)) {
+            let score = self.min_over_opponents(game, mv, depth, alpha);
+            if score > best.1 {
+                best = (mv, score);
+            }
+            alpha = alpha.max(score);
+        }
+        if !self.timed_out {
+            self.ordering.record(game, best.0);
+        }
+
+        best
+    }
+
+    /// the value of `me` playing `mv` here, when the opponents pick the joint move that is
+    /// worst for `me`. Stops early once that is no better than `alpha`
+    fn min_over_opponents<G, const N_SNAKES: usize>(
+        &mut self,
+        game: &G,
+        mv: Move,
+        depth: u8,
+        alpha: i32,
+    ) -> i32
+    where
+        G: SimulableGame<I, N_SNAKES> + VictorDeterminableGame + HealthGettableGame,
+        G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+        E: Fn(&G, SnakeId) -> i32,
+        O: MoveOrdering<G>,
+    {
+        let moves = game
+            .get_snake_ids()
+            .into_iter()
+            .map(|sid| {
+                let moves = if sid == self.me {
+                    vec![mv]
+                } else {
+                    Move::all().to_vec()
+                };
+                (sid, moves)
+            })
+            .collect_vec();
+
+        let mut beta = i32::MAX;
+        for (_, child) in game.simulate_with_moves(self.instruments, moves) {
+            beta = beta.min(self.max_for_me(&child, depth - 1, alpha, beta));
+            if beta <= alpha || self.timed_out {
+                break;
+            }
+        }
+
+        beta
+    }
+
+    /// the value of this position for `me` with `depth` plies left to search
+    fn max_for_me<G, const N_SNAKES: usize>(
+        &mut self,
+        game: &G,
+        depth: u8,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32
+    where
+        G: SimulableGame<I, N_SNAKES> + VictorDeterminableGame + HealthGettableGame,
+        G: SnakeIDGettableGame<SnakeIDType = SnakeId>,
+        E: Fn(&G, SnakeId) -> i32,
+        O: MoveOrdering<G>,
+    {
+        // `is_over` can't be used here, it ends the game when `you` dies even if `me` is
+        // someone else
+        if !game.is_alive(&self.me) {
+            return LOSS - depth as i32;
+        }
+        if self.has_opponents && game.alive_snake_count() == 1 {
+            return WIN + depth as i32;
+        }
+        if depth == 0 {
+            return (self.eval)(game, self.me);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out = true;
+        }
+        if self.timed_out {
+            return 0;
+        }
+
+        let mut best = (Move::Up, i32::MIN);
+        for mv in ordered_moves(self.ordering.best_move(game)) {
+            let score = self.min_over_opponents(game, mv, depth, alpha);
+            if score > best.1 {
+                best = (mv, score);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        if !self.timed_out {
+            self.ordering.record(game, best.0);
+        }
+
+        best.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compact_representation::{CellContents, StandardCellBoard4Snakes11x11},
+        game_fixture,
+        types::build_snake_id_map,
+    };
+
+    #[derive(Debug)]
+    struct Instruments;
+    impl SimulatorInstruments for Instruments {
+        fn observe_simulation(&self, _: std::time::Duration) {}
+    }
+
+    fn board(fixture: &str) -> StandardCellBoard4Snakes11x11 {
+        let g = game_fixture(fixture);
+        let snake_ids = build_snake_id_map(&g);
+        StandardCellBoard4Snakes11x11::convert_from_game(g, &snake_ids).unwrap()
+    }
+
+    /// `you` (`SnakeId(0)`) is walled in to the corner by the body of the last snake and dies
+    /// whatever it does. With `snakes` of 3 `SnakeId(1)` is free in the middle of the board
+    fn you_trapped(snakes: u8) -> StandardCellBoard4Snakes11x11 {
+        let mut grid = vec![vec![CellContents::Empty { hazards: 0 }; 11]; 11];
+        let mut bodies = vec![vec![(0, 0), (0, 1), (0, 2)]];
+        if snakes == 3 {
+            bodies.push(vec![(5, 5), (5, 6), (5, 7)]);
+        }
+        bodies.push(vec![(3, 0), (2, 0), (1, 0), (1, 1), (1, 2)]);
+        for (id, body) in bodies.into_iter().enumerate() {
+            for (segment, (x, y)) in body.into_iter().enumerate() {
+                grid[y][x] = CellContents::Snake {
+                    id: SnakeId(id as u8),
+                    segment: segment as u16,
+                    stack: 1,
+                    hazards: 0,
+                };
+            }
+        }
+        StandardCellBoard4Snakes11x11::from_grid(&grid, 0).unwrap()
+    }
+
+    #[test]
+    fn test_alphabeta_for_snake_other_than_you() {
+        // `you` dying doesn't end the game for SnakeId(1) while SnakeId(2) is alive
+        let board = you_trapped(3);
+        for depth in 1..=2 {
+            let (_, score) = alphabeta(&board, &Instruments, SnakeId(1), depth, default_eval);
+            assert!(score < WIN - u8::MAX as i32, "{} at depth {}", score, depth);
+        }
+
+        // but outliving `you` when it is the only other snake is a win
+        let board = you_trapped(2);
+        let (_, score) = alphabeta(&board, &Instruments, SnakeId(1), 1, default_eval);
+        assert_eq!(score, WIN);
+    }
+
+    #[test]
+    fn test_alphabeta_avoids_walls() {
+        // SnakeId(1) starts on the left edge, so moving left or in to its neck is instant death
+        let start = board(include_str!("../../fixtures/start_of_game.json"));
+        for depth in 1..=2 {
+            let (mv, score) = alphabeta(&start, &Instruments, SnakeId(1), depth, default_eval);
+            assert!(score > LOSS);
+            let (_, next) = start
+                .simulate_with_moves(&Instruments, vec![(SnakeId(1), [mv])])
+                .next()
+                .unwrap();
+            assert!(next.is_alive(&SnakeId(1)), "{:?} at depth {}", mv, depth);
+        }
+
+        // every move from here is fatal, dying on the first of two plies scores just below LOSS
+        let cornered = board(include_str!("../../fixtures/cornered.json"));
+        let (_, score) = alphabeta(&cornered, &Instruments, SnakeId(0), 2, default_eval);
+        assert_eq!(score, LOSS - 1);
+    }
+
+    #[test]
+    fn test_iterative_deepening() {
+        let start = board(include_str!("../../fixtures/start_of_game.json"));
+        let survives = |mv: Move| {
+            start
+                .simulate_with_moves(&Instruments, vec![(SnakeId(1), [mv])])
+                .all(|(_, next)| next.is_alive(&SnakeId(1)))
+        };
+
+        let mv = iterative_deepening(
+            &start,
+            &Instruments,
+            SnakeId(1),
+            Duration::from_millis(50),
+            default_eval,
+        );
+        assert!(survives(mv), "{:?}", mv);
+
+        // without any time the first ply is still searched
+        let mv = iterative_deepening(
+            &start,
+            &Instruments,
+            SnakeId(1),
+            Duration::ZERO,
+            default_eval,
+        );
+        assert_eq!(
+            mv,
+            alphabeta(&start, &Instruments, SnakeId(1), 1, default_eval).0
+        );
+
+        // a forced loss stops deepening long before the budget runs out
+        let cornered = board(include_str!("../../fixtures/cornered.json"));
+        let started = Instant::now();
+        iterative_deepening(
+            &cornered,
+            &Instruments,
+            SnakeId(0),
+            Duration::from_secs(10),
+            default_eval,
+        );
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_iterative_deepening_for_snake_other_than_you() {
+        // `you` dying isn't a forced win for SnakeId(1) while SnakeId(2) is alive, so the whole
+        // budget is spent deepening
+        let board = you_trapped(3);
+        let budget = Duration::from_millis(50);
+        let started = Instant::now();
+        iterative_deepening(&board, &Instruments, SnakeId(1), budget, default_eval);
+        assert!(started.elapsed() >= budget);
+
+        // with no one else left it is, and deepening stops straight away
+        let board = you_trapped(2);
+        let started = Instant::now();
+        iterative_deepening(
+            &board,
+            &Instruments,
+            SnakeId(1),
+            Duration::from_secs(10),
+            default_eval,
+        );
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_alphabeta_matches_exhaustive_search() {
+        let board = board(include_str!("../../fixtures/late_stage.json"));
+        let eval = |game: &StandardCellBoard4Snakes11x11, me: SnakeId| default_eval(game, me);
+
+        // without pruning: the best move by its worst case over every opponent reply
+        let exhaustive = Move::all()
+            .iter()
+            .map(|mv| {
+                let moves = board
+                    .get_snake_ids()
+                    .into_iter()
+                    .map(|sid| {
+                        let moves = if sid == SnakeId(0) {
+                            vec![*mv]
+                        } else {
+                            Move::all().to_vec()
+                        };
+                        (sid, moves)
+                    })
+                    .collect_vec();
+                board
+                    .simulate_with_moves(&Instruments, moves)
+                    .map(|(_, child)| {
+                        if !child.is_alive(&SnakeId(0)) {
+                            LOSS
+                        } else if child.is_over() {
+                            WIN
+                        } else {
+                            eval(&child, SnakeId(0))
+                        }
+                    })
+                    .min()
+                    .unwrap()
+            })
+            .max()
+            .unwrap();
+
+        let (_, score) = alphabeta(&board, &Instruments, SnakeId(0), 1, eval);
+        assert_eq!(score, exhaustive);
+    }
+}
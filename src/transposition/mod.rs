@@ -0,0 +1,282 @@
+//! A transposition table for caching search results keyed by board
+//!
+//! Boards are keyed by their hash, and the full board is stored alongside each value so that a
+//! hash collision can never return the value for a different board. A table can be saved and
+//! loaded again, to reuse an opening book or the results of a long analysis between runs.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::io::{Read, Write};
+
+use fxhash::FxHashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The format version `TranspositionTable::save` writes. `TranspositionTable::load` rejects
+/// tables saved with any other version
+pub const TABLE_FORMAT_VERSION: u32 = 1;
+
+/// Errors that can occur when loading a saved `TranspositionTable`
+#[derive(Debug)]
+pub enum TableLoadError {
+    /// The input isn't a saved table, or its boards or values couldn't be decoded
+    Malformed(serde_json::Error),
+    /// The table was saved with a different `TABLE_FORMAT_VERSION`
+    UnsupportedVersion {
+        /// the version the table was saved with
+        found: u64,
+    },
+}
+
+impl Display for TableLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableLoadError::Malformed(error) => {
+                write!(f, "malformed transposition table: {}", error)
+            }
+            TableLoadError::UnsupportedVersion { found } => write!(
+                f,
+                "transposition table has version {}, expected {}",
+                found, TABLE_FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for TableLoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TableLoadError::Malformed(error) => Some(error),
+            TableLoadError::UnsupportedVersion { .. } => None,
+        }
+    }
+}
+
+/// the layout of a saved table, entries are oldest first
+#[derive(Serialize, Deserialize)]
+struct SavedTable<E> {
+    version: u32,
+    capacity: usize,
+    entries: Vec<E>,
+}
+
+/// A fixed capacity cache from boards to values of type `V` (e.g. an evaluation and best move).
+///
+/// When the table is full the oldest inserted board is evicted first. If two different boards
+/// share a hash, the most recently inserted one replaces the other
+#[derive(Debug, Clone)]
+pub struct TranspositionTable<K, V> {
+    entries: FxHashMap<u64, (K, V)>,
+    insertion_order: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl<K: Hash + Eq, V> TranspositionTable<K, V> {
+    /// creates an empty table that holds at most `capacity` boards
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: FxHashMap::default(),
+            insertion_order: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// the hash used to key a board in the table
+    pub fn hash_board(board: &K) -> u64 {
+        fxhash::hash64(board)
+    }
+
+    /// gets the value stored for this exact board
+    pub fn get(&self, board: &K) -> Option<&V> {
+        self.entries
+            .get(&Self::hash_board(board))
+            .filter(|(stored, _)| stored == board)
+            .map(|(_, value)| value)
+    }
+
+    /// stores a value for a board, returning the value previously stored for this exact board
+    pub fn insert(&mut self, board: K, value: V) -> Option<V> {
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let hash = Self::hash_board(&board);
+        if let Some((stored, old_value)) = self.entries.get_mut(&hash) {
+            let same_board = *stored == board;
+            *stored = board;
+            let old_value = std::mem::replace(old_value, value);
+            return if same_board { Some(old_value) } else { None };
+        }
+
+        if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.insertion_order.push_back(hash);
+        self.entries.insert(hash, (board, value));
+
+        None
+    }
+
+    /// how many boards are stored
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// whether no boards are stored
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// the maximum number of boards this table holds
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// removes every stored board
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.insertion_order.clear();
+    }
+
+    /// Writes the table as JSON: a header with `TABLE_FORMAT_VERSION` and the capacity, then
+    /// every board and its value from oldest to newest, so a loaded table evicts in the same
+    /// order. The compact boards are written as the bytes of their `to_bytes`
+    pub fn save<W: Write>(&self, writer: W) -> serde_json::Result<()>
+    where
+        K: Serialize,
+        V: Serialize,
+    {
+        let entries = self
+            .insertion_order
+            .iter()
+            .filter_map(|hash| self.entries.get(hash))
+            .collect();
+        serde_json::to_writer(
+            writer,
+            &SavedTable {
+                version: TABLE_FORMAT_VERSION,
+                capacity: self.capacity,
+                entries,
+            },
+        )
+    }
+
+    /// Reads a table written by `save`, with the capacity it was saved with
+    pub fn load<R: Read>(reader: R) -> Result<Self, TableLoadError>
+    where
+        K: DeserializeOwned,
+        V: DeserializeOwned,
+    {
+        let saved: serde_json::Value =
+            serde_json::from_reader(reader).map_err(TableLoadError::Malformed)?;
+        // checked before decoding the entries, whose layout may have changed between versions
+        match saved.get("version").and_then(|version| version.as_u64()) {
+            Some(version) if version == TABLE_FORMAT_VERSION as u64 => {}
+            Some(found) => return Err(TableLoadError::UnsupportedVersion { found }),
+            None => {}
+        }
+        let saved: SavedTable<(K, V)> =
+            serde_json::from_value(saved).map_err(TableLoadError::Malformed)?;
+
+        let mut table = Self::new(saved.capacity);
+        for (board, value) in saved.entries {
+            table.insert(board, value);
+        }
+
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TableLoadError, TranspositionTable};
+    use crate::{
+        compact_representation::StandardCellBoard4Snakes11x11,
+        game_fixture,
+        types::{build_snake_id_map, Move, SimulableGame, SimulatorInstruments, SnakeId},
+    };
+
+    #[derive(Debug)]
+    struct Instruments;
+    impl SimulatorInstruments for Instruments {
+        fn observe_simulation(&self, _: std::time::Duration) {}
+    }
+
+    #[test]
+    fn test_get_insert_and_evict() {
+        let g = game_fixture(include_str!("../../fixtures/start_of_game.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let board = StandardCellBoard4Snakes11x11::convert_from_game(g, &snake_ids).unwrap();
+        let successors: Vec<_> = board
+            .simulate_with_moves(&Instruments, vec![(SnakeId(0), Move::all().as_slice())])
+            .map(|(_, b)| b)
+            .collect();
+
+        let mut table = TranspositionTable::new(2);
+        assert_eq!(table.insert(board, 1), None);
+        assert_eq!(table.get(&board), Some(&1));
+        assert_eq!(table.insert(board, 2), Some(1));
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.get(&successors[0]), None);
+
+        table.insert(successors[0], 3);
+        table.insert(successors[1], 4);
+        assert_eq!(table.len(), 2);
+        // the original board was inserted first so it is evicted first
+        assert_eq!(table.get(&board), None);
+        assert_eq!(table.get(&successors[0]), Some(&3));
+        assert_eq!(table.get(&successors[1]), Some(&4));
+
+        table.clear();
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let g = game_fixture(include_str!("../../fixtures/start_of_game.json"));
+        let snake_ids = build_snake_id_map(&g);
+        let board = StandardCellBoard4Snakes11x11::convert_from_game(g, &snake_ids).unwrap();
+        let successors: Vec<_> = board
+            .simulate_with_moves(&Instruments, vec![(SnakeId(0), Move::all().as_slice())])
+            .map(|(_, b)| b)
+            .collect();
+
+        let mut table = TranspositionTable::new(3);
+        table.insert(board, (10, Move::Up));
+        table.insert(successors[0], (-5, Move::Left));
+        table.insert(successors[1], (7, Move::Down));
+        let mut saved = Vec::new();
+        table.save(&mut saved).unwrap();
+
+        let mut loaded: TranspositionTable<StandardCellBoard4Snakes11x11, (i32, Move)> =
+            TranspositionTable::load(saved.as_slice()).unwrap();
+        assert_eq!(loaded.capacity(), 3);
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded.get(&board), Some(&(10, Move::Up)));
+        assert_eq!(loaded.get(&successors[0]), Some(&(-5, Move::Left)));
+        assert_eq!(loaded.get(&successors[1]), Some(&(7, Move::Down)));
+        // the eviction order survives too
+        loaded.insert(successors[2], (0, Move::Right));
+        assert_eq!(loaded.get(&board), None);
+        assert_eq!(loaded.get(&successors[0]), Some(&(-5, Move::Left)));
+
+        let newer = String::from_utf8(saved)
+            .unwrap()
+            .replacen("\"version\":1", "\"version\":2", 1);
+        let err = TranspositionTable::<StandardCellBoard4Snakes11x11, (i32, Move)>::load(
+            newer.as_bytes(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            TableLoadError::UnsupportedVersion { found: 2 }
+        ));
+        assert!(matches!(
+            TranspositionTable::<StandardCellBoard4Snakes11x11, i32>::load(&b"[]"[..]),
+            Err(TableLoadError::Malformed(_))
+        ));
+    }
+}
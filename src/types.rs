@@ -25,7 +25,7 @@ pub struct Vector {
 pub const N_MOVES: usize = 4;
 
 /// Represents a move
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Move {
     #[allow(missing_docs)]
     Left,
@@ -101,6 +101,18 @@ impl Move {
         }
     }
 
+    /// a compact code for this move, for packing moves in to bytes. The codes are the
+    /// `as_index` order: up is 0, down is 1, left is 2 and right is 3. This mapping is stable,
+    /// so it is safe to use in on disk formats
+    pub fn to_u8(self) -> u8 {
+        self.as_index() as u8
+    }
+
+    /// the move for a code written by `to_u8`, `None` for codes above 3
+    pub fn from_u8(code: u8) -> Option<Move> {
+        Move::all().get(code as usize).copied()
+    }
+
     #[allow(dead_code)]
     /// checks if a given move is not opposibe this move. e.g. Up is not opposite to Left, but is opposite to Down
     pub fn is_not_opposite(&self, other: &Move) -> bool {
@@ -158,6 +170,11 @@ impl Serialize for SnakeId {
 /// this function once per game at the start, and store the result
 /// that way you can stabally have integer IDs for a given snake
 /// throughout a game
+///
+/// The other snakes get IDs counting up from 1 in the order they appear in `board.snakes`.
+/// Eliminated snakes are dropped from that list, so a map rebuilt later in the game can number
+/// the survivors differently. `remap_snake_ids` on the compact boards translates a board
+/// between two maps
 pub fn build_snake_id_map(g: &Game) -> SnakeIDMap {
     let mut hm = HashMap::new();
     hm.insert(g.you.id.clone(), SnakeId(0));
@@ -172,6 +189,23 @@ pub fn build_snake_id_map(g: &Game) -> SnakeIDMap {
     hm
 }
 
+/// the wire id of the snake that `id` refers to in `map`, the reverse of looking up a wire id
+/// in a map from `build_snake_id_map`. A map only ever holds a handful of snakes, so this scans
+/// it rather than keeping a second map in the other direction
+pub fn wire_id_for(map: &SnakeIDMap, id: SnakeId) -> Option<&str> {
+    map.iter()
+        .find(|(_, sid)| **sid == id)
+        .map(|(wire_id, _)| wire_id.as_str())
+}
+
+/// every `SnakeId` assigned in `map`, sorted by id. Iterating the map directly visits its wire
+/// ids in an unspecified order
+pub fn all_snake_ids(map: &SnakeIDMap) -> Vec<SnakeId> {
+    let mut ids: Vec<SnakeId> = map.values().copied().collect();
+    ids.sort_by_key(|id| id.0);
+    ids
+}
+
 /// A game for which one can get the snake ids
 pub trait SnakeIDGettableGame {
     #[allow(missing_docs)]
@@ -210,7 +244,9 @@ pub trait VictorDeterminableGame: std::fmt::Debug + SnakeIDGettableGame {
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
-/// Represents moves taken for a given simulation
+/// Represents moves taken for a given simulation, indexed by `SnakeId`. A `None` entry means
+/// that snake wasn't simulated, either because it was already dead or because it was left out of
+/// the moves passed to `simulate_with_moves` and held still
 pub struct Action<const N_SNAKES: usize> {
     moves: [Option<Move>; N_SNAKES],
 }
@@ -227,6 +263,25 @@ impl<const N_SNAKES: usize> Action<N_SNAKES> {
         Self { moves }
     }
 
+    /// create an action where every snake made a move, for comparing against the actions
+    /// returned by simulation
+    pub fn from_moves(moves: [Move; N_SNAKES]) -> Self {
+        Self {
+            moves: moves.map(Some),
+        }
+    }
+
+    /// the move made by every snake, indexed by `SnakeId`. `None` for snakes that weren't
+    /// simulated
+    pub fn moves(&self) -> &[Option<Move>; N_SNAKES] {
+        &self.moves
+    }
+
+    /// the move made by `id`, `None` if it wasn't simulated or is out of range
+    pub fn move_for(&self, id: SnakeId) -> Option<Move> {
+        self.moves.get(id.as_usize()).copied().flatten()
+    }
+
     /// collects an action from an iterator of moves
     pub fn collect_from<'a, T: Iterator<Item = &'a (SnakeId, Move)>>(ids_and_moves: T) -> Self {
         let mut moves = [None; N_SNAKES];
@@ -267,12 +322,21 @@ pub trait SimulableGame<T: SimulatorInstruments, const N_SNAKES: usize>:
     ) -> Box<dyn Iterator<Item = (Action<N_SNAKES>, Self)> + '_> {
         let moves_to_simulate = Move::all();
         let build = snake_ids
-            .into_iter()
+            .iter()
             .map(|s| (s.clone(), moves_to_simulate.as_slice()));
         self.simulate_with_moves(instruments, build)
     }
     /// simulates the next possible states for a game with a given set of snakes and moves, producing a list of the new games,
     /// along with the moves that got to that position
+    ///
+    /// Snakes that are absent from `snake_ids_and_moves` are frozen: they hold their position and
+    /// their health and length are left untouched. They still act as obstacles, so a simulated
+    /// snake moving into a frozen snake's body or head dies. The returned actions have `None`
+    /// for every frozen snake.
+    ///
+    /// Simulation is deterministic: the same game and moves always produce identical successors
+    /// in the same order, so results can be cached and reproduced. Implementations must not
+    /// depend on hash map iteration order, e.g. when resolving collisions.
     #[allow(clippy::type_complexity)]
     fn simulate_with_moves<S>(
         &self,
@@ -281,6 +345,134 @@ pub trait SimulableGame<T: SimulatorInstruments, const N_SNAKES: usize>:
     ) -> Box<dyn Iterator<Item = (Action<N_SNAKES>, Self)> + '_>
     where
         S: Borrow<[Move]>;
+
+    /// materializes every board reachable from this one in a single ply, by simulating all
+    /// moves for every snake returned by `get_snake_ids`.
+    ///
+    /// The ordering is reproducible: successors are the cartesian product of each snake's moves,
+    /// with snakes taken in `get_snake_ids` order and moves in `Move::all` order, where the
+    /// last snake's move varies fastest. Moves that are certain death for a snake (e.g. off
+    /// the board) are pruned by the simulator, unless all of that snake's moves are deadly.
+    ///
+    /// The number of successors grows exponentially with the number of snakes, up to `4^n`:
+    /// 16 boards for 2 snakes, 256 boards for 4 snakes and 65536 for 8 snakes, so prefer
+    /// `simulate_with_moves` with a reduced move set on crowded boards.
+    fn successors(&self, instruments: &T) -> Vec<(Action<N_SNAKES>, Self)> {
+        let snake_ids = self.get_snake_ids();
+        self.simulate(instruments, &snake_ids).collect()
+    }
+
+    /// Counts the distinct joint move sequences `depth` plies deep, expanding every node with
+    /// `successors`. Like perft in chess engines this is meant for validating move generation
+    /// and simulation: pin the counts for a fixture and any change to them is a regression.
+    ///
+    /// A depth of 0 counts 1. Games are expanded even once they're over, since this counts move
+    /// sequences rather than games. The count grows combinatorially, by up to `4^n` per ply for
+    /// `n` snakes, so keep fixtures small and depths shallow
+    fn perft(&self, instruments: &T, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        self.successors(instruments)
+            .iter()
+            .map(|(_, game)| game.perft(instruments, depth - 1))
+            .sum()
+    }
+
+    /// simulates a single joint move where only the given snakes move and every other snake is
+    /// frozen in place (see `simulate_with_moves`). Useful for isolating one snake's dynamics.
+    ///
+    /// A `None` move holds that snake exactly as it is, the same as leaving it out: its head and
+    /// tail don't move and it loses no health. Holding is not part of the battlesnake rules, so
+    /// the resulting games are only meant for analysis and what-if questions, never for
+    /// predicting real games. If no snake moves an unchanged copy of this game is returned
+    fn simulate_partial(&self, instruments: &T, moves: &[(Self::SnakeIDType, Option<Move>)]) -> Self
+    where
+        Self: Clone,
+    {
+        let build = moves
+            .iter()
+            .filter_map(|(id, mv)| mv.map(|mv| (id.clone(), [mv])));
+        self.simulate_with_moves(instruments, build)
+            .next()
+            .map(|(_, game)| game)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// simulates a single turn from `me`'s perspective: `me` commits to `my_move` and
+    /// `opponent_sampler` picks the move of every other living snake, e.g. by sampling an
+    /// opponent model. The sampler returns a move per `SnakeId`, and the entries for `me` and
+    /// for dead snakes are ignored. This is the expectimax pattern of fixing your own move and
+    /// averaging over the opponents' moves
+    fn my_successors(
+        &self,
+        instruments: &T,
+        me: SnakeId,
+        my_move: Move,
+        mut opponent_sampler: impl FnMut(&Self) -> [Move; N_SNAKES],
+    ) -> Self
+    where
+        Self: SnakeIDGettableGame<SnakeIDType = SnakeId> + Clone,
+    {
+        let opponent_moves = opponent_sampler(self);
+        let moves = self.get_snake_ids().into_iter().map(|sid| {
+            let mv = if sid == me {
+                my_move
+            } else {
+                opponent_moves[sid.as_usize()]
+            };
+            (sid, [mv])
+        });
+        self.simulate_with_moves(instruments, moves)
+            .next()
+            .map(|(_, game)| game)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// simulates a single turn where `me` commits to `my_move` and every other living snake
+    /// makes the move `model` predicts for it. A thin layer over `my_successors` that keeps your
+    /// own decision separate from the opponent prediction
+    fn simulate_against_model(
+        &self,
+        instruments: &T,
+        me: SnakeId,
+        my_move: Move,
+        model: &impl OpponentModel<Self>,
+    ) -> Self
+    where
+        Self: SnakeIDGettableGame<SnakeIDType = SnakeId> + Clone,
+    {
+        self.my_successors(instruments, me, my_move, |game| {
+            let mut moves = [Move::Up; N_SNAKES];
+            for sid in game.get_snake_ids() {
+                if sid != me {
+                    moves[sid.as_usize()] = model.predict(game, sid);
+                }
+            }
+            moves
+        })
+    }
+
+    /// simulates the given moves like `simulate_with_moves`, but only yields the resulting
+    /// games in which `me` is still alive. Useful for pruning your own deaths when expanding
+    /// a node in a search tree
+    #[allow(clippy::type_complexity)]
+    fn simulate_surviving<S>(
+        &self,
+        instruments: &T,
+        me: Self::SnakeIDType,
+        snake_ids_and_moves: impl IntoIterator<Item = (Self::SnakeIDType, S)>,
+    ) -> Box<dyn Iterator<Item = (Action<N_SNAKES>, Self)> + '_>
+    where
+        S: Borrow<[Move]>,
+        Self: HealthGettableGame,
+        Self::SnakeIDType: 'static,
+    {
+        Box::new(
+            self.simulate_with_moves(instruments, snake_ids_and_moves)
+                .filter(move |(_, game)| game.is_alive(&me)),
+        )
+    }
 }
 
 /// A game where positions can be checked for hazards
@@ -386,11 +578,32 @@ pub trait HealthGettableGame: SnakeIDGettableGame {
     fn is_alive(&self, snake_id: &Self::SnakeIDType) -> bool {
         self.get_health(snake_id) != Self::ZERO
     }
+
+    /// get the health for a given snake bucketed in to a small category, which is useful for
+    /// compact feature encodings. The bucket boundaries are stable across versions:
+    ///
+    /// | health   | bucket |
+    /// |----------|--------|
+    /// | 0        | 0      |
+    /// | 1..=25   | 1      |
+    /// | 26..=50  | 2      |
+    /// | 51..=75  | 3      |
+    /// | 76..=100 | 4      |
+    fn health_bucket(&self, snake_id: &Self::SnakeIDType) -> u8 {
+        match self.get_health_i64(snake_id) {
+            i64::MIN..=0 => 0,
+            1..=25 => 1,
+            26..=50 => 2,
+            51..=75 => 3,
+            _ => 4,
+        }
+    }
 }
 
 /// a game for which random reasonable moves for a given snake can be determined. e.g. do not collide with yourself
 pub trait RandomReasonableMovesGame: SnakeIDGettableGame {
-    #[allow(missing_docs)]
+    /// picks a random reasonable move for every living snake. A snake without any reasonable
+    /// move gets a random move, so its deaths aren't biased towards a single direction
     fn random_reasonable_move_for_each_snake<'a>(
         &'a self,
         rng: &'a mut impl Rng,
@@ -399,7 +612,8 @@ pub trait RandomReasonableMovesGame: SnakeIDGettableGame {
 
 /// a game for which reasonable moves for a given snake can be determined. e.g. do not collide with yourself
 pub trait ReasonableMovesGame: SnakeIDGettableGame {
-    #[allow(missing_docs)]
+    /// the reasonable moves for every living snake. A snake without any reasonable move gets
+    /// every move, as they are all equally fatal
     fn reasonable_moves_for_each_snake(
         &self,
     ) -> Box<dyn Iterator<Item = (Self::SnakeIDType, Vec<Move>)> + '_>;
@@ -434,6 +648,26 @@ pub trait SizeDeterminableGame {
     fn get_height(&self) -> u32;
 }
 
+/// Predicts the move an opponent makes, see `SimulableGame::simulate_against_model`
+pub trait OpponentModel<G> {
+    /// the move `opponent` will make in `game`. Models are free to sample
+    fn predict(&self, game: &G, opponent: SnakeId) -> Move;
+}
+
+/// An opponent model where every opponent makes its first reasonable move, the simplest model
+/// that doesn't have opponents kill themselves
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysSafeModel;
+
+impl<G: ReasonableMovesGame<SnakeIDType = SnakeId>> OpponentModel<G> for AlwaysSafeModel {
+    fn predict(&self, game: &G, opponent: SnakeId) -> Move {
+        game.reasonable_moves_for_each_snake()
+            .find(|(sid, _)| *sid == opponent)
+            .and_then(|(_, moves)| moves.first().copied())
+            .unwrap_or(Move::Up)
+    }
+}
+
 /// a game for which the current turn is determinable
 pub trait TurnDeterminableGame {
     #[allow(missing_docs)]
@@ -476,6 +710,64 @@ pub trait StandardFoodPlaceableGame {
     fn place_food(&mut self, rng: &mut impl Rng);
 }
 
+/// A game where the standard rules decide how many food spawn, but a `FoodPlacer` decides
+/// where each one goes
+pub trait FoodPlacerGame: StandardFoodPlaceableGame + PositionGettableGame + Sized {
+    /// place food on the board following the standard rules for how many food spawn, asking
+    /// `placer` where each one goes. Food the placer puts on a cell that isn't empty is skipped
+    fn place_food_with(&mut self, rng: &mut impl Rng, placer: &mut impl FoodPlacer<Self>);
+}
+
+/// Decides where spawned food goes, while `FoodPlacerGame::place_food_with` decides when food
+/// spawns
+pub trait FoodPlacer<G: PositionGettableGame> {
+    /// the cell the next food spawns on, or `None` to spawn no more food this turn
+    fn next_food(&mut self, board: &G) -> Option<G::NativePositionType>;
+}
+
+/// Places food on a random empty cell, like the official rules
+#[derive(Debug, Clone)]
+pub struct RandomFoodPlacer<R> {
+    rng: R,
+}
+
+impl<R: Rng> RandomFoodPlacer<R> {
+    /// a placer choosing cells with `rng`
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+}
+
+impl<R: Rng, G: EmptyCellGettableGame> FoodPlacer<G> for RandomFoodPlacer<R> {
+    fn next_food(&mut self, board: &G) -> Option<G::NativePositionType> {
+        use rand::seq::IteratorRandom;
+
+        board.get_empty_cells().choose(&mut self.rng)
+    }
+}
+
+/// Places food on a fixed list of cells in order, then stops spawning food. Makes simulations
+/// that spawn food deterministic for tests
+#[derive(Debug, Clone)]
+pub struct ScriptedFoodPlacer<P> {
+    positions: std::collections::VecDeque<P>,
+}
+
+impl<P> ScriptedFoodPlacer<P> {
+    /// a placer spawning food on `positions`, first to last
+    pub fn new(positions: Vec<P>) -> Self {
+        Self {
+            positions: positions.into(),
+        }
+    }
+}
+
+impl<G: PositionGettableGame> FoodPlacer<G> for ScriptedFoodPlacer<G::NativePositionType> {
+    fn next_food(&mut self, _board: &G) -> Option<G::NativePositionType> {
+        self.positions.pop_front()
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -485,4 +777,51 @@ mod test {
     fn test_move_all_order_matches_iter() {
         assert_eq!(Move::all().to_vec(), Move::all_iter().collect::<Vec<_>>());
     }
+
+    #[test]
+    fn test_move_u8_codes() {
+        for mv in Move::all() {
+            assert_eq!(Move::from_u8(mv.to_u8()), Some(mv));
+        }
+        assert_eq!(Move::Up.to_u8(), 0);
+        assert_eq!(Move::Down.to_u8(), 1);
+        assert_eq!(Move::Left.to_u8(), 2);
+        assert_eq!(Move::Right.to_u8(), 3);
+        assert_eq!(Move::from_u8(4), None);
+    }
+
+    #[test]
+    fn test_wire_id_for() {
+        let g = crate::game_fixture(include_str!("../fixtures/late_stage.json"));
+        let map = build_snake_id_map(&g);
+        assert_eq!(wire_id_for(&map, SnakeId(0)), Some(g.you.id.as_str()));
+        for snake in g.board.snakes.iter() {
+            assert_eq!(wire_id_for(&map, map[&snake.id]), Some(snake.id.as_str()));
+        }
+        assert_eq!(wire_id_for(&map, SnakeId(g.board.snakes.len() as u8)), None);
+    }
+
+    #[test]
+    fn test_all_snake_ids() {
+        let g = crate::game_fixture(include_str!("../fixtures/start_of_game.json"));
+        let map = build_snake_id_map(&g);
+        assert_eq!(
+            all_snake_ids(&map),
+            vec![SnakeId(0), SnakeId(1), SnakeId(2), SnakeId(3)]
+        );
+        assert_eq!(all_snake_ids(&SnakeIDMap::new()), vec![]);
+    }
+
+    #[test]
+    fn test_action_accessors() {
+        let action = Action::from_moves([Move::Up, Move::Left]);
+        assert_eq!(action.moves(), &[Some(Move::Up), Some(Move::Left)]);
+        assert_eq!(action.move_for(SnakeId(1)), Some(Move::Left));
+        assert_eq!(action.move_for(SnakeId(2)), None);
+
+        let action = Action::<4>::collect_from([(SnakeId(2), Move::Down)].iter());
+        assert_eq!(action.move_for(SnakeId(2)), Some(Move::Down));
+        assert_eq!(action.move_for(SnakeId(0)), None);
+        assert_eq!(action, Action::new([None, None, Some(Move::Down), None]));
+    }
 }
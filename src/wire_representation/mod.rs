@@ -10,7 +10,6 @@ use rand::prelude::IteratorRandom;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::convert::TryInto;
-use std::error::Error;
 use std::fmt::{self, Display};
 
 /// Struct that matches the `battlesnake` object from the wire representation
@@ -181,6 +180,10 @@ impl Game {
         }
     }
 
+    /// Converts this game to a standard compact board. This is a thin wrapper around
+    /// `StandardCellBoard::convert_from_game` that clones the game, so it fails in the same ways:
+    /// a `ConversionError` when the game doesn't fit the board size or snake count, has a
+    /// malformed snake, a snake missing from `snake_ids`, or is a wrapped game
     pub fn as_cell_board<
         T: CellNum,
         D: Dimensions,
@@ -189,10 +192,15 @@ impl Game {
     >(
         &self,
         snake_ids: &SnakeIDMap,
-    ) -> Result<StandardCellBoard<T, D, BOARD_SIZE, MAX_SNAKES>, Box<dyn Error>> {
+    ) -> Result<
+        StandardCellBoard<T, D, BOARD_SIZE, MAX_SNAKES>,
+        compact_representation::ConversionError,
+    > {
         StandardCellBoard::convert_from_game(self.clone(), snake_ids)
     }
 
+    /// Converts this game to a wrapped compact board. Like `as_cell_board` this is a thin
+    /// wrapper around `wrapped::CellBoard::convert_from_game`, which only accepts wrapped games
     pub fn as_wrapped_cell_board<
         T: compact_representation::CellNum,
         D: Dimensions,
@@ -203,13 +211,9 @@ impl Game {
         snake_ids: &SnakeIDMap,
     ) -> Result<
         compact_representation::wrapped::CellBoard<T, D, BOARD_SIZE, MAX_SNAKES>,
-        Box<dyn Error>,
+        compact_representation::ConversionError,
     > {
-        if self.is_wrapped() {
-            compact_representation::wrapped::CellBoard::convert_from_game(self.clone(), snake_ids)
-        } else {
-            panic!("Cannot convert a non-wrapped game to a wrapped game")
-        }
+        compact_representation::wrapped::CellBoard::convert_from_game(self.clone(), snake_ids)
     }
 
     pub fn off_board(&self, position: Position) -> bool {
@@ -630,6 +634,26 @@ mod tests {
         assert!(g.is_you(&"gs_MMxyjByhGFbtGSV8KJv3tqdV".to_string()));
     }
 
+    #[test]
+    fn test_health_bucket() {
+        let mut g = fixture();
+        let id = g.board.snakes[0].id.clone();
+        for (health, bucket) in [
+            (0, 0),
+            (1, 1),
+            (25, 1),
+            (26, 2),
+            (50, 2),
+            (51, 3),
+            (75, 3),
+            (76, 4),
+            (100, 4),
+        ] {
+            g.board.snakes[0].health = health;
+            assert_eq!(g.health_bucket(&id), bucket, "health {}", health);
+        }
+    }
+
     #[test]
     fn test_snake_id_gettable() {
         let g = fixture();